@@ -0,0 +1,141 @@
+// Measures how long it takes to build the index of the test fixtures, scaled up to the size of a
+// game. The scale can be changed with the PDX_EXPLORER_BENCH_SCALE environment variable.
+
+use pdx_explorer::database::Database;
+use pdx_explorer::database::dir::WalkOptions;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+// Per unit of scale.
+const LOCALIZATION_COPIES: usize = 10;
+const KEY_COPIES: usize = 20;
+const DIRECTORIES: usize = 10;
+const FILES_PER_DIRECTORY: usize = 20;
+
+fn main() {
+    let scale = std::env::var("PDX_EXPLORER_BENCH_SCALE")
+        .ok()
+        .and_then(|scale| scale.parse().ok())
+        .unwrap_or(10);
+
+    let work_dir = std::env::temp_dir().join("pdx-explorer-bench");
+    let game = work_dir.join("game");
+    let database_file_path = work_dir.join("index.sqlite3");
+
+    let _ = fs::remove_dir_all(&work_dir);
+    let (files, keys) = generate_game(&game, scale);
+
+    println!("indexing {} files with {} localization keys", files, keys);
+
+    let mut timings = Vec::new();
+
+    for _ in 0..RUNS {
+        remove_database(&database_file_path);
+
+        let start = Instant::now();
+        Database::new(
+            std::slice::from_ref(&game),
+            &WalkOptions::default(),
+            &database_file_path,
+        )
+        .expect("failed to build the index");
+        timings.push(start.elapsed());
+    }
+
+    timings.sort();
+
+    let total: Duration = timings.iter().sum();
+
+    println!(
+        "index: min {:.3?}, median {:.3?}, mean {:.3?} ({} runs)",
+        timings[0],
+        timings[RUNS / 2],
+        total / RUNS as u32,
+        RUNS
+    );
+
+    let _ = fs::remove_dir_all(&work_dir);
+}
+
+// Copies the localization fixtures with their keys renamed so that they don't override each
+// other, and adds plain files to fill up the directory tree. Returns the number of files and keys.
+fn generate_game(game: &Path, scale: usize) -> (usize, usize) {
+    let fixtures_dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_input/game/localization");
+    let mut fixtures: Vec<_> = fs::read_dir(&fixtures_dir)
+        .expect("failed to read the fixtures")
+        .map(|entry| entry.expect("failed to read the fixtures").path())
+        .collect();
+    fixtures.sort();
+
+    let mut files = 0;
+    let mut keys = 0;
+
+    for copy in 0..LOCALIZATION_COPIES * scale {
+        let dir = game.join("localization").join(format!("copy_{}", copy));
+        fs::create_dir_all(&dir).expect("failed to create a directory");
+
+        for fixture in &fixtures {
+            let content = fs::read(fixture).expect("failed to read a fixture");
+            let (content, key_count) = rename_keys(&content, copy);
+
+            fs::write(dir.join(fixture.file_name().unwrap()), content)
+                .expect("failed to write a file");
+            files += 1;
+            keys += key_count;
+        }
+    }
+
+    for dir_idx in 0..DIRECTORIES * scale {
+        let dir = game.join("common").join(format!("dir_{}", dir_idx));
+        fs::create_dir_all(&dir).expect("failed to create a directory");
+
+        for file_idx in 0..FILES_PER_DIRECTORY {
+            fs::write(dir.join(format!("file_{}.txt", file_idx)), "")
+                .expect("failed to write a file");
+            files += 1;
+        }
+    }
+
+    (files, keys)
+}
+
+// Repeats every key line of a localization file with a unique name. Files which aren't valid
+// UTF-8 are left as they are.
+fn rename_keys(content: &[u8], copy: usize) -> (Vec<u8>, usize) {
+    let Ok(text) = std::str::from_utf8(content) else {
+        return (content.to_owned(), 0);
+    };
+
+    let mut output = String::new();
+    let mut key_count = 0;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        match trimmed.split_once(':') {
+            Some((key, rest))
+                if line.starts_with(' ') && !key.is_empty() && !key.starts_with('#') =>
+            {
+                for key_copy in 0..KEY_COPIES {
+                    let rest = rest.trim_end_matches(['\r', '\n']);
+                    output.push_str(&format!(" {}_{}_{}:{}\n", key, copy, key_copy, rest));
+                    key_count += 1;
+                }
+            }
+            _ => output.push_str(line),
+        }
+    }
+
+    (output.into_bytes(), key_count)
+}
+
+fn remove_database(database_file_path: &Path) {
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let mut path = database_file_path.as_os_str().to_owned();
+        path.push(suffix);
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,441 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pdx_explorer::core::*;
+use pdx_explorer::database::dir::{DirTree, WalkOptions};
+use pdx_explorer::database::{ContentType, Database, Language, models};
+use pdx_explorer::export::{Export, ExportFormat};
+use pdx_explorer::import::{ImportPreview, ImportedKey};
+use pdx_explorer::lint::{self, ReportFormat};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "pdx-explorer-cli",
+    version,
+    about = "Index and query Paradox game files"
+)]
+struct Cli {
+    /// Print the output as JSON instead of human-readable text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index a game and the mods on top of it into a database file.
+    Index {
+        /// Root directories or zip archives: the game first, then the mods in the load order.
+        #[arg(required = true)]
+        roots: Vec<PathBuf>,
+        /// Skip symbolic links instead of following them.
+        #[arg(long)]
+        no_follow_symlinks: bool,
+        /// Leave out files and directories matching a gitignore-style pattern; can be given more
+        /// than once.
+        #[arg(long = "ignore", value_name = "PATTERN")]
+        ignore_patterns: Vec<String>,
+        #[command(flatten)]
+        db: DatabaseArgs,
+    },
+    /// Query localization keys.
+    Loc {
+        #[command(subcommand)]
+        command: LocCommand,
+    },
+    /// List the indexed files.
+    Files {
+        /// Only list files with the given content type.
+        #[arg(long, value_parser = parse_content_type)]
+        content_type: Option<ContentType>,
+        #[command(flatten)]
+        db: DatabaseArgs,
+    },
+    /// Check the localization files for errors. Exits with a non-zero code if any are found.
+    Check {
+        /// Root directory or zip archive of the game or mod.
+        #[arg(required_unless_present = "db")]
+        root: Option<PathBuf>,
+        /// Check the topmost layer (the last mod) of a previously indexed database instead.
+        #[arg(long, conflicts_with = "root")]
+        db: Option<PathBuf>,
+        /// Format of the report. Overrides --json.
+        #[arg(long, value_enum)]
+        format: Option<CheckFormat>,
+        /// Treat warnings as errors.
+        #[arg(long)]
+        warnings_as_errors: bool,
+    },
+    /// Export localization keys to CSV, TSV or JSON.
+    Export {
+        /// Languages to export; can be given more than once.
+        #[arg(long = "lang", value_parser = parse_language, default_value = "english")]
+        languages: Vec<Language>,
+        /// Only export keys whose key or value contains the given string.
+        #[arg(long, default_value = "")]
+        filter: String,
+        /// Format of the export. Inferred from the output file extension if not given.
+        #[arg(long, value_enum)]
+        format: Option<TableFormat>,
+        /// File to write the export to. Printed to stdout if not given.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        db: DatabaseArgs,
+    },
+    /// Import translations from a CSV, TSV or JSON file into the localization files.
+    Import {
+        /// File to import.
+        file: PathBuf,
+        /// Language of the imported values.
+        #[arg(long, value_parser = parse_language)]
+        lang: Language,
+        /// Language the translations were made from. Used to detect source strings which changed
+        /// after the export.
+        #[arg(long, value_parser = parse_language, default_value = "english")]
+        source_lang: Language,
+        /// Format of the file. Inferred from the file extension if not given.
+        #[arg(long, value_enum)]
+        format: Option<TableFormat>,
+        /// Only print the changes without writing them.
+        #[arg(long)]
+        dry_run: bool,
+        /// Also apply the changes whose source string changed after the export.
+        #[arg(long)]
+        include_conflicts: bool,
+        #[command(flatten)]
+        db: DatabaseArgs,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TableFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl From<TableFormat> for ExportFormat {
+    fn from(format: TableFormat) -> Self {
+        match format {
+            TableFormat::Csv => Self::Csv,
+            TableFormat::Tsv => Self::Tsv,
+            TableFormat::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CheckFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl From<CheckFormat> for ReportFormat {
+    fn from(format: CheckFormat) -> Self {
+        match format {
+            CheckFormat::Text => Self::Text,
+            CheckFormat::Json => Self::Json,
+            CheckFormat::Sarif => Self::Sarif,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum LocCommand {
+    /// Print the value of a localization key.
+    Get {
+        key: String,
+        /// Only print the value for the given language.
+        #[arg(long, value_parser = parse_language)]
+        lang: Option<Language>,
+        #[command(flatten)]
+        db: DatabaseArgs,
+    },
+    /// List the localization keys of a language.
+    List {
+        #[arg(long, value_parser = parse_language, default_value = "english")]
+        lang: Language,
+        /// Only list keys whose key or value contains the given string.
+        #[arg(long)]
+        filter: Option<String>,
+        #[command(flatten)]
+        db: DatabaseArgs,
+    },
+}
+
+#[derive(Args)]
+struct DatabaseArgs {
+    /// Path to the database file.
+    #[arg(long)]
+    db: PathBuf,
+}
+
+fn parse_language(value: &str) -> Result<Language, String> {
+    Language::from_name(value).ok_or_else(|| format!("unknown language `{}`", value))
+}
+
+fn parse_content_type(value: &str) -> Result<ContentType, String> {
+    ContentType::from_name(value).ok_or_else(|| format!("unknown content type `{}`", value))
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|error| error!("{}", error))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    match cli.command {
+        Command::Index {
+            roots,
+            no_follow_symlinks,
+            ignore_patterns,
+            db,
+        } => {
+            // The roots are stored in the database, so they have to stay valid when the database
+            // is opened from a different working directory.
+            let roots = roots
+                .iter()
+                .map(std::path::absolute)
+                .collect::<std::io::Result<Vec<_>>>()?;
+            let walk_options = WalkOptions {
+                follow_symlinks: !no_follow_symlinks,
+                ignore_patterns,
+            };
+            let mut database = Database::new(&roots, &walk_options, &db.db)?;
+            let warnings: Vec<_> = database
+                .dir_trees()
+                .iter()
+                .flat_map(|dir_tree| dir_tree.warnings())
+                .cloned()
+                .collect();
+            let files = database.get_files(None)?;
+            let localization_files = files
+                .iter()
+                .filter(|file| file.content_type == ContentType::Localization.name())
+                .count();
+            let parse_errors = database.get_parse_errors()?;
+
+            if cli.json {
+                let parse_errors: Vec<_> = parse_errors
+                    .iter()
+                    .map(|(error, file)| serde_json::json!({ "file": file, "message": error.message }))
+                    .collect();
+
+                let layers: Vec<_> = database
+                    .dir_trees()
+                    .iter()
+                    .map(|dir_tree| {
+                        serde_json::json!({
+                            "name": dir_tree.layer_name(),
+                            "root": dir_tree.root_path(),
+                            "workshop_id": dir_tree.workshop_id(),
+                        })
+                    })
+                    .collect();
+
+                print_json(&serde_json::json!({
+                    "roots": roots,
+                    "layers": layers,
+                    "database": db.db,
+                    "files": files.len(),
+                    "localization_files": localization_files,
+                    "parse_errors": parse_errors,
+                    "warnings": warnings,
+                }))?;
+            } else {
+                for warning in &warnings {
+                    eprintln!("warning: {}", warning);
+                }
+
+                for (error, file) in &parse_errors {
+                    eprintln!("warning: {}: {}", file, error.message);
+                }
+
+                println!(
+                    "Indexed {} files ({} localization files, {} parse errors) from {} into `{}`.",
+                    files.len(),
+                    localization_files,
+                    parse_errors.len(),
+                    roots
+                        .iter()
+                        .map(|root| format!("`{}`", root.display()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    db.db.display()
+                );
+            }
+        }
+        Command::Loc {
+            command: LocCommand::Get { key, lang, db },
+        } => {
+            let mut database = Database::open(&db.db)?;
+            let keys = database.get_localization_key(&key, lang)?;
+
+            if keys.is_empty() {
+                fail!("localization key `{}` not found", key);
+            }
+
+            if cli.json {
+                let keys: Vec<_> = keys
+                    .iter()
+                    .map(|(key, relative_path)| {
+                        serde_json::json!({
+                            "key": key.key,
+                            "value": key.value,
+                            "language": key.language,
+                            "file": relative_path,
+                        })
+                    })
+                    .collect();
+                print_json(&keys)?;
+            } else {
+                for (key, relative_path) in keys {
+                    println!("{}: \"{}\" ({})", key.language, key.value, relative_path);
+                }
+            }
+        }
+        Command::Loc {
+            command: LocCommand::List { lang, filter, db },
+        } => {
+            let mut database = Database::open(&db.db)?;
+            // The filter is matched the same way as by `export --filter`.
+            let keys: Vec<_> = database
+                .get_localization_keys_with_files(&[lang], filter.as_deref().unwrap_or_default())?
+                .into_iter()
+                .map(|(key, _)| models::LocalizationKeyKeyValue {
+                    key: key.key,
+                    value: key.value,
+                })
+                .collect();
+
+            if cli.json {
+                print_json(&keys)?;
+            } else {
+                for key in keys {
+                    println!("{}: \"{}\"", key.key, key.value);
+                }
+            }
+        }
+        Command::Files { content_type, db } => {
+            let mut database = Database::open(&db.db)?;
+            let files = database.get_files(content_type)?;
+
+            if cli.json {
+                print_json(&files)?;
+            } else {
+                for file in files {
+                    println!("{} ({})", file.relative_path, file.content_type);
+                }
+            }
+        }
+        Command::Check {
+            root,
+            db,
+            format,
+            warnings_as_errors,
+        } => {
+            let dir_tree = match (root, db) {
+                (Some(root), _) => DirTree::new(&root, &WalkOptions::default())?,
+                (None, Some(db)) => Database::open(&db)?.dir_tree().clone(),
+                (None, None) => fail!("either a root directory or a database is required"),
+            };
+
+            let report = lint::check(&dir_tree)?;
+            let format = match format {
+                Some(format) => format.into(),
+                None if cli.json => ReportFormat::Json,
+                None => ReportFormat::Text,
+            };
+
+            print!("{}", report.render(format)?);
+
+            if format != ReportFormat::Text {
+                println!();
+            }
+
+            if report.is_failure(warnings_as_errors) {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Export {
+            languages,
+            filter,
+            format,
+            output,
+            db,
+        } => {
+            let format = match (format, &output) {
+                (Some(format), _) => format.into(),
+                (None, Some(output)) => output
+                    .extension()
+                    .and_then(|extension| {
+                        ExportFormat::from_extension(&extension.to_string_lossy())
+                    })
+                    .unwrap_or_default(),
+                (None, None) => ExportFormat::default(),
+            };
+
+            let mut database = Database::open(&db.db)?;
+            let export = Export::new(&mut database, &languages, &filter)?;
+
+            match output {
+                Some(output) => {
+                    export.write(&output, format)?;
+                    eprintln!(
+                        "Exported {} keys to `{}`.",
+                        export.keys().len(),
+                        output.display()
+                    );
+                }
+                None => {
+                    print!("{}", export.render(format)?);
+
+                    if format == ExportFormat::Json {
+                        println!();
+                    }
+                }
+            }
+        }
+        Command::Import {
+            file,
+            lang,
+            source_lang,
+            format,
+            dry_run,
+            include_conflicts,
+            db,
+        } => {
+            let keys = ImportedKey::read(&file, format.map(Into::into), lang, source_lang)?;
+            let mut database = Database::open(&db.db)?;
+            let preview = ImportPreview::new(&mut database, lang, source_lang, keys)?;
+
+            print!("{}", preview.to_text());
+
+            if !dry_run {
+                let applied = preview.apply(&mut database, include_conflicts)?;
+                println!("Applied {} change(s).", applied);
+            }
+
+            if preview.conflict_count() > 0 && !include_conflicts {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,25 +1,40 @@
+pub mod cache;
 pub mod dir;
+pub mod ignore;
 pub mod models;
-mod parser;
+pub(crate) mod parallel;
+pub(crate) mod parser;
 mod schema;
+pub mod vfs;
+mod writer;
 
 use crate::core::*;
 use diesel::connection::SimpleConnection;
 use diesel::{
-    BelongingToDsl, Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper,
+    BelongingToDsl, BoolExpressionMethods, Connection, EscapeExpressionMethods, ExpressionMethods,
+    OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper, TextExpressionMethods,
 };
-use dir::DirTree;
+use dir::{DirTree, WalkOptions};
 use parser::Parser;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::{Path, PathBuf};
+use writer::Writer;
 
-#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum ContentType {
     Localization,
     Indeterminate,
 }
 
 impl ContentType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::values()
+            .iter()
+            .copied()
+            .find(|value| value.name().eq_ignore_ascii_case(name))
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Self::Localization => "Localization",
@@ -32,6 +47,7 @@ impl ContentType {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for ContentType {
     fn default() -> Self {
         Self::Indeterminate
@@ -77,6 +93,37 @@ impl Language {
         }
     }
 
+    // Accepts the name (English), the language specifier (l_english) or the folder name (english).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::values().iter().copied().find(|value| {
+            value.name().eq_ignore_ascii_case(name)
+                || value.specifier() == name
+                || value.folder_name() == name
+        })
+    }
+
+    pub fn specifier(&self) -> &'static str {
+        match self {
+            Self::English => "l_english",
+            Self::BrazilianPortuguese => "l_braz_por",
+            Self::French => "l_french",
+            Self::German => "l_german",
+            Self::Polish => "l_polish",
+            Self::Russian => "l_russian",
+            Self::Spanish => "l_spanish",
+            Self::Japanese => "l_japanese",
+            Self::SimplifiedChinese => "l_simp_chinese",
+            Self::Korean => "l_korean",
+            Self::Turkish => "l_turkish",
+        }
+    }
+
+    // Name of the subfolder of the localization folder which holds the files for this language
+    // (e.g. localization/english).
+    pub fn folder_name(&self) -> &'static str {
+        &self.specifier()[2..]
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Self::English => "English",
@@ -110,6 +157,7 @@ impl Language {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Language {
     fn default() -> Self {
         Self::English
@@ -122,35 +170,437 @@ impl fmt::Display for Language {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalizationUpdate {
+    Change {
+        key: String,
+        value: String,
+    },
+    // Adds a key to <file_name>_l_<language>.yml.
+    Add {
+        key: String,
+        value: String,
+        file_name: String,
+    },
+    Remove {
+        key: String,
+    },
+}
+
 pub struct Database {
     connection: diesel::SqliteConnection,
-    dir_tree: DirTree,
+    // The game and the mods on top of it, in the load order. There's always at least one.
+    dir_trees: Vec<DirTree>,
 }
 
 impl Database {
-    const DATABASE_INIT_SCRIPT: &'static str = include_str!("database/sql/init.sql");
+    // The migrations of the schema, in order. The version of a database (`PRAGMA user_version`)
+    // is the number of migrations which were applied to it.
+    const MIGRATIONS: &'static [&'static str] = &[
+        include_str!("database/sql/migrations/001_initial.sql"),
+        include_str!("database/sql/migrations/002_walk_options.sql"),
+        include_str!("database/sql/migrations/003_raw_paths.sql"),
+        include_str!("database/sql/migrations/004_file_versions.sql"),
+        include_str!("database/sql/migrations/005_raw_root_paths.sql"),
+    ];
+    const CLEAR_SCRIPT: &'static str = include_str!("database/sql/clear.sql");
+    // The database is only written to by one connection at a time, but it can be read while it's
+    // written to, e.g. by the command line tool.
+    const CONNECTION_PRAGMAS: &'static str = "
+        PRAGMA journal_mode = WAL;
+        PRAGMA synchronous = NORMAL;
+        PRAGMA busy_timeout = 5000;
+    ";
+    // The index can always be built again, so it's not worth syncing to the disk while it's being
+    // built.
+    const BULK_LOAD_PRAGMAS: &'static str = "
+        PRAGMA synchronous = OFF;
+        PRAGMA cache_size = -65536;
+        PRAGMA temp_store = MEMORY;
+    ";
+    // The number of rows inserted by one statement. It's kept well below the limit on the number
+    // of bound parameters.
+    const INSERT_BATCH_SIZE: usize = 1000;
+    const REPLACE_FOLDER_NAME: &'static str = "replace";
+    // The name of the file keys of the layers below are overridden in, without the
+    // _l_<language>.yml suffix.
+    const OVERRIDES_FILE_NAME: &'static str = "overrides";
 
-    pub fn new(base_path: &Path, database_file_path: &Path) -> Result<Self> {
-        let dir_tree = DirTree::new(base_path)?;
+    // Builds the database from the given root directories: the game first and then the mods in
+    // the load order. Keys of the later layers override the keys of the earlier ones.
+    pub fn new(
+        roots: &[PathBuf],
+        walk_options: &WalkOptions,
+        database_file_path: &Path,
+    ) -> Result<Self> {
+        let dir_trees = Self::build_dir_trees(roots, walk_options)?;
 
-        let mut connection =
-            diesel::SqliteConnection::establish(Self::path_to_str(database_file_path)?)?;
+        let mut connection = Self::establish(database_file_path)?;
+
+        Self::migrate(&mut connection)?;
 
-        connection.batch_execute(Self::DATABASE_INIT_SCRIPT)?;
+        // Everything is inserted in one transaction, so that a failure doesn't leave a partial
+        // index behind.
+        connection.batch_execute(Self::BULK_LOAD_PRAGMAS)?;
+        connection.immediate_transaction(|connection| {
+            connection.batch_execute(Self::CLEAR_SCRIPT)?;
+
+            Self::insert_languages(connection)?;
+            Self::insert_content_types(connection)?;
+            Self::insert_walk_options(connection, walk_options)?;
+
+            for dir_tree in &dir_trees {
+                Self::insert_dir_tree(connection, dir_tree)?;
+            }
 
-        Self::insert_languages(&mut connection)?;
-        Self::insert_content_types(&mut connection)?;
-        Self::insert_dir_tree(&mut connection, &dir_tree)?;
-        Self::parse_and_insert_localization_keys(&mut connection)?;
+            Self::parse_and_insert_localization_keys(connection)
+        })?;
+        connection.batch_execute(Self::CONNECTION_PRAGMAS)?;
 
         Ok(Self {
             connection,
-            dir_tree,
+            dir_trees,
         })
     }
 
+    // Opens a database which was previously built with `Database::new`, without parsing the
+    // files again. The dir trees are rebuilt from the root directories and the walk options
+    // stored in the database. Fails if the files on disk no longer match the indexed ones.
+    pub fn open(database_file_path: &Path) -> Result<Self> {
+        let mut connection = Self::connect(database_file_path)?;
+        let roots = Self::load_roots(&mut connection, database_file_path)?;
+        let walk_options = Self::load_walk_options(&mut connection)?;
+        let dir_trees = Self::build_dir_trees(&roots, &walk_options)?;
+
+        let mut indexed_files: Vec<(i64, i32, String)> = schema::file::table
+            .select((
+                schema::file::id,
+                schema::file::layer,
+                schema::file::relative_path,
+            ))
+            .load(&mut connection)?;
+        let mut files: Vec<(i64, i32, String)> = dir_trees
+            .iter()
+            .flat_map(|dir_tree| {
+                dir_tree.files().into_iter().map(|file| {
+                    (
+                        file.id() as i64,
+                        dir_tree.layer() as i32,
+                        file.relative_path().to_string_lossy().into_owned(),
+                    )
+                })
+            })
+            .collect();
+
+        indexed_files.sort_unstable();
+        files.sort_unstable();
+
+        if indexed_files != files {
+            fail!(
+                "database file `{}` is out of date",
+                database_file_path.display()
+            );
+        }
+
+        let mut database = Self {
+            connection,
+            dir_trees,
+        };
+
+        // The keys of the localization files which were changed since they were indexed are
+        // parsed again.
+        if database.update_file_versions()? {
+            Self::reparse_localization_keys(&mut database.connection)?;
+        }
+
+        Ok(database)
+    }
+
+    // Reads the root directories of the layers stored in a database file.
+    pub fn read_roots(database_file_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut connection = Self::connect(database_file_path)?;
+
+        Self::load_roots(&mut connection, database_file_path)
+    }
+
+    // Reads the options the directories of a database file were walked with.
+    pub fn read_walk_options(database_file_path: &Path) -> Result<WalkOptions> {
+        let mut connection = Self::connect(database_file_path)?;
+
+        Self::load_walk_options(&mut connection)
+    }
+
+    // Connects to a database which was previously built with `Database::new`, upgrading its schema
+    // if it's older than the current one.
+    fn connect(database_file_path: &Path) -> Result<diesel::SqliteConnection> {
+        if !database_file_path.is_file() {
+            fail!(
+                "database file `{}` doesn't exist",
+                database_file_path.display()
+            );
+        }
+
+        let mut connection = Self::establish(database_file_path)?;
+
+        if Self::schema_version(&mut connection)? == 0 {
+            fail!(
+                "database file `{}` was created by an older version of the application and has to \
+                 be rebuilt",
+                database_file_path.display()
+            );
+        }
+
+        Self::migrate(&mut connection)?;
+
+        Ok(connection)
+    }
+
+    fn establish(database_file_path: &Path) -> Result<diesel::SqliteConnection> {
+        let mut connection =
+            diesel::SqliteConnection::establish(Self::path_to_str(database_file_path)?)?;
+
+        connection.batch_execute(Self::CONNECTION_PRAGMAS)?;
+
+        Ok(connection)
+    }
+
+    fn schema_version(connection: &mut diesel::SqliteConnection) -> Result<usize> {
+        #[derive(diesel::QueryableByName)]
+        struct UserVersion {
+            #[diesel(sql_type = diesel::sql_types::Integer)]
+            user_version: i32,
+        }
+
+        let version = diesel::sql_query("PRAGMA user_version")
+            .get_result::<UserVersion>(connection)?
+            .user_version;
+
+        Ok(version as usize)
+    }
+
+    // Applies the migrations the database doesn't have yet. A database with a newer schema is
+    // left untouched, since it's not known what changed.
+    fn migrate(connection: &mut diesel::SqliteConnection) -> Result<()> {
+        #[derive(diesel::QueryableByName)]
+        struct ForeignKeyViolation {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            table: String,
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+            rowid: Option<i64>,
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            parent: String,
+        }
+
+        let version = Self::schema_version(connection)?;
+
+        if version > Self::MIGRATIONS.len() {
+            fail!(
+                "the database was created by a newer version of the application (schema version \
+                 {}, expected at most {})",
+                version,
+                Self::MIGRATIONS.len()
+            );
+        }
+
+        if version == Self::MIGRATIONS.len() {
+            return Ok(());
+        }
+
+        // Foreign keys can't be switched off within a transaction, and tables which other tables
+        // refer to can't be changed while they're on.
+        connection.batch_execute("PRAGMA foreign_keys = OFF")?;
+
+        let result = connection.immediate_transaction(|connection| {
+            for (idx, migration) in Self::MIGRATIONS.iter().enumerate().skip(version) {
+                connection.batch_execute(migration)?;
+                connection.batch_execute(&format!("PRAGMA user_version = {}", idx + 1))?;
+            }
+
+            // The migrations run without foreign keys, so they're checked before committing.
+            let violations = diesel::sql_query("PRAGMA foreign_key_check")
+                .load::<ForeignKeyViolation>(connection)?;
+
+            if let Some(violation) = violations.first() {
+                fail!(
+                    "the migration left {} row(s) which violate foreign keys, e.g. row {} of table \
+                     `{}`, which refers to table `{}`",
+                    violations.len(),
+                    violation
+                        .rowid
+                        .map_or("?".to_owned(), |rowid| rowid.to_string()),
+                    violation.table,
+                    violation.parent
+                );
+            }
+
+            Ok(())
+        });
+
+        connection.batch_execute("PRAGMA foreign_keys = ON")?;
+
+        result
+    }
+
+    fn load_roots(
+        connection: &mut diesel::SqliteConnection,
+        database_file_path: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let roots: Vec<PathBuf> = schema::layer::table
+            .order_by(schema::layer::id)
+            .select(models::Layer::as_select())
+            .load(connection)?
+            .iter()
+            .map(models::Layer::root_path)
+            .collect();
+
+        if roots.is_empty() {
+            fail!(
+                "database file `{}` doesn't contain a dir tree",
+                database_file_path.display()
+            );
+        }
+
+        Ok(roots)
+    }
+
+    fn load_walk_options(connection: &mut diesel::SqliteConnection) -> Result<WalkOptions> {
+        let Some(walk_options) = schema::walk_options::table
+            .select(models::WalkOptions::as_select())
+            .first(connection)
+            .optional()?
+        else {
+            return Ok(WalkOptions::default());
+        };
+
+        Ok(WalkOptions {
+            follow_symlinks: walk_options.follow_symlinks,
+            ignore_patterns: walk_options
+                .ignore_patterns
+                .lines()
+                .map(str::to_owned)
+                .collect(),
+        })
+    }
+
+    // The tree of the topmost layer, i.e. the mod which is being worked on. New localization
+    // files are created in it.
     pub fn dir_tree(&self) -> &DirTree {
-        &self.dir_tree
+        &self.dir_trees[self.dir_trees.len() - 1]
+    }
+
+    pub fn dir_trees(&self) -> &[DirTree] {
+        &self.dir_trees
+    }
+
+    pub fn get_file(&mut self, id: u64) -> Result<models::File> {
+        schema::file::table
+            .find(id as i64)
+            .select(models::File::as_select())
+            .first(&mut self.connection)
+            .optional()?
+            .ok_or_else(|| error!("file with id {} doesn't exist", id))
+    }
+
+    // Returns the key for every language it's defined for (or only for the given language) along
+    // with the path of the file it's defined in, relative to the root directory.
+    pub fn get_localization_key(
+        &mut self,
+        key: &str,
+        language: Option<Language>,
+    ) -> Result<Vec<(models::LocalizationKey, String)>> {
+        let mut query = schema::localization_key::table
+            .inner_join(schema::file::table)
+            .filter(schema::localization_key::key.eq(key))
+            .select((
+                models::LocalizationKey::as_select(),
+                schema::file::relative_path,
+            ))
+            .into_boxed();
+
+        if let Some(language) = language {
+            query = query.filter(schema::localization_key::language.eq(language.name()));
+        }
+
+        Ok(query.get_results(&mut self.connection)?)
+    }
+
+    // Returns the keys of the given languages whose key or value contains the filter, along with
+    // the path of the file they're defined in, relative to the root directory. The keys are
+    // ordered by key and then by the order of the given languages.
+    pub fn get_localization_keys_with_files(
+        &mut self,
+        languages: &[Language],
+        filter: &str,
+    ) -> Result<Vec<(models::LocalizationKey, String)>> {
+        let language_names: Vec<&str> = languages.iter().map(Language::name).collect();
+        let mut query = schema::localization_key::table
+            .inner_join(schema::file::table)
+            .filter(schema::localization_key::language.eq_any(language_names))
+            .select((
+                models::LocalizationKey::as_select(),
+                schema::file::relative_path,
+            ))
+            .into_boxed();
+
+        if !filter.is_empty() {
+            let pattern = format!("%{}%", Self::escape_like_pattern(filter));
+            query = query.filter(
+                schema::localization_key::key
+                    .like(pattern.clone())
+                    .escape('\\')
+                    .or(schema::localization_key::value.like(pattern).escape('\\')),
+            );
+        }
+
+        let language_position = |key: &models::LocalizationKey| {
+            languages
+                .iter()
+                .position(|language| language.name() == key.language)
+        };
+
+        let mut keys = query.get_results(&mut self.connection)?;
+        keys.sort_by(|(a, _), (b, _)| {
+            (&a.key, language_position(a)).cmp(&(&b.key, language_position(b)))
+        });
+
+        Ok(keys)
+    }
+
+    pub fn get_files(&mut self, content_type: Option<ContentType>) -> Result<Vec<models::File>> {
+        let mut query = schema::file::table
+            .order_by(schema::file::relative_path)
+            .select(models::File::as_select())
+            .into_boxed();
+
+        if let Some(content_type) = content_type {
+            query = query.filter(schema::file::content_type.eq(content_type.name()));
+        }
+
+        Ok(query.get_results(&mut self.connection)?)
+    }
+
+    // Returns the errors of the files which couldn't be parsed, along with the path of the file,
+    // relative to the root directory.
+    pub fn get_parse_errors(&mut self) -> Result<Vec<(models::ParseError, String)>> {
+        Ok(schema::parse_error::table
+            .inner_join(schema::file::table)
+            .order_by((schema::file::relative_path, schema::parse_error::id))
+            .select((models::ParseError::as_select(), schema::file::relative_path))
+            .get_results(&mut self.connection)?)
+    }
+
+    // Returns the number of localization keys defined in each file, by file ID. Keys which are
+    // overridden by another file are counted for the file which overrides them.
+    pub fn get_localization_key_counts(&mut self) -> Result<HashMap<u64, usize>> {
+        let counts: Vec<(i64, i64)> = schema::localization_key::table
+            .group_by(schema::localization_key::file_id)
+            .select((schema::localization_key::file_id, diesel::dsl::count_star()))
+            .get_results(&mut self.connection)?;
+
+        Ok(counts
+            .into_iter()
+            .map(|(file_id, count)| (file_id as u64, count as usize))
+            .collect())
     }
 
     pub fn get_localization_keys_for_language(
@@ -168,125 +618,1261 @@ impl Database {
         Ok(keys)
     }
 
-    fn insert_languages(connection: &mut diesel::SqliteConnection) -> Result<()> {
-        for value in Language::values() {
-            diesel::insert_into(schema::language::table)
-                .values(models::NewLanguage { name: value.name() })
-                .execute(connection)?;
+    // Adds a new localization key for each of the given languages. The key is written into
+    // <file_name>_l_<language>.yml, which is created in the localization folder for that language
+    // if it doesn't exist yet. Keys which are already defined for a language are refused, unless
+    // `allow_override` is set. The definition which is used is then changed in place if it's in
+    // the topmost layer, and overridden in the replace folder otherwise, like in
+    // `update_localization_keys`. Adding the key to <file_name>_l_<language>.yml instead could
+    // have no effect, since another file may take precedence over it.
+    pub fn create_localization_key(
+        &mut self,
+        key: &str,
+        value: &str,
+        languages: &[Language],
+        file_name: &str,
+        allow_override: bool,
+    ) -> Result<()> {
+        Self::validate_new_localization_key(key, file_name)?;
+
+        if languages.is_empty() {
+            fail!("no language selected for localization key `{}`", key);
+        }
+
+        // Everything is validated before anything is written, so that a failure doesn't leave
+        // the key defined for only some of the languages.
+        let topmost_layer = self.dir_tree().layer();
+        let mut rewrites = Vec::new();
+        let mut additions = Vec::new();
+        let mut overrides = Vec::new();
+
+        for language in languages {
+            let Some((full_path, layer)) = self.find_localization_key_full_path(key, *language)?
+            else {
+                let (relative_path, exists) =
+                    self.localization_file_for_new_keys(*language, file_name);
+                additions.push((*language, relative_path, exists));
+                continue;
+            };
+
+            if !allow_override {
+                fail!(
+                    "localization key `{}` already exists for {} in `{}`",
+                    key,
+                    language,
+                    self.find_localization_key_file(key, *language)?
+                        .unwrap_or_default()
+                );
+            }
+
+            if layer == topmost_layer {
+                rewrites.push(full_path);
+            } else {
+                let (relative_path, exists) = self.localization_file_for_overrides(*language);
+                overrides.push((*language, relative_path, exists));
+            }
+        }
+
+        for path in &rewrites {
+            Self::check_writable(path)?;
+        }
+
+        for (_, relative_path, _) in additions.iter().chain(&overrides) {
+            Self::check_writable(&self.dir_tree().root_path().join(relative_path))?;
+        }
+
+        for full_path in &rewrites {
+            Writer::rewrite_localization_keys(full_path, &HashMap::from([(key, Some(value))]))?;
+        }
+
+        // Whether an overriding key takes precedence over the existing one depends on the order
+        // in which the files are processed, so all the keys have to be parsed again then.
+        let overrides_existing_key = !rewrites.is_empty() || !overrides.is_empty();
+
+        for (language, relative_path, exists) in overrides {
+            self.write_new_localization_keys(language, &relative_path, exists, &[(key, value)])?;
+        }
+
+        for (language, relative_path, exists) in additions {
+            let file_id = self.write_new_localization_keys(
+                language,
+                &relative_path,
+                exists,
+                &[(key, value)],
+            )?;
+
+            if !overrides_existing_key {
+                diesel::insert_into(schema::localization_key::table)
+                    .values(models::NewLocalizationKey {
+                        key,
+                        value,
+                        revision: Some(0),
+                        file_id: file_id as i64,
+                        language: language.name(),
+                    })
+                    .execute(&mut self.connection)?;
+            }
+        }
+
+        // The files which were written are recorded as they're now, so that they aren't parsed
+        // again when the database is opened.
+        self.update_file_versions()?;
+
+        if overrides_existing_key {
+            Self::reparse_localization_keys(&mut self.connection)?;
         }
 
         Ok(())
     }
 
-    fn insert_content_types(connection: &mut diesel::SqliteConnection) -> Result<()> {
-        for value in ContentType::values() {
-            diesel::insert_into(schema::content_type::table)
-                .values(models::NewContentType { name: value.name() })
-                .execute(connection)?;
+    // Applies a batch of changes to the localization keys of a language. Only the topmost layer is
+    // written to. Its keys are changed or removed in place, in the files they're defined in. Keys
+    // of the layers below are changed by overriding them in
+    // <localization>/replace/overrides_l_<language>.yml of the topmost layer; they can't be
+    // removed. New keys are added to <file_name>_l_<language>.yml, like in
+    // `create_localization_key`.
+    pub fn update_localization_keys(
+        &mut self,
+        language: Language,
+        updates: &[LocalizationUpdate],
+    ) -> Result<()> {
+        let topmost_layer = self.dir_tree().layer();
+        // Files are rewritten in a fixed order, so that a failure always leaves the same files
+        // changed.
+        let mut rewrites: BTreeMap<PathBuf, HashMap<&str, Option<&str>>> = BTreeMap::new();
+        let mut additions: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+        let mut overrides: Vec<(&str, &str)> = Vec::new();
+
+        for update in updates {
+            match update {
+                LocalizationUpdate::Change { key, value } => {
+                    let (path, layer) = self.localization_key_full_path(key, language)?;
+
+                    if layer == topmost_layer {
+                        rewrites
+                            .entry(path)
+                            .or_default()
+                            .insert(key, Some(value.as_str()));
+                    } else {
+                        overrides.push((key, value));
+                    }
+                }
+                LocalizationUpdate::Remove { key } => {
+                    let (path, layer) = self.localization_key_full_path(key, language)?;
+
+                    if layer != topmost_layer {
+                        fail!(
+                            "localization key `{}` is defined by `{}` and can't be removed from `{}`",
+                            key,
+                            self.dir_trees[layer as usize].layer_name(),
+                            self.dir_tree().layer_name()
+                        );
+                    }
+
+                    rewrites.entry(path).or_default().insert(key, None);
+                }
+                LocalizationUpdate::Add {
+                    key,
+                    value,
+                    file_name,
+                } => {
+                    Self::validate_new_localization_key(key, file_name)?;
+                    additions.entry(file_name).or_default().push((key, value));
+                }
+            }
+        }
+
+        for path in rewrites.keys() {
+            Self::check_writable(path)?;
+        }
+
+        if !additions.is_empty() || !overrides.is_empty() {
+            Self::check_writable(&self.dir_tree().root_path().join("localization"))?;
         }
 
+        for (path, changes) in &rewrites {
+            Writer::rewrite_localization_keys(path, changes)?;
+        }
+
+        for (file_name, keys) in additions {
+            let (relative_path, exists) = self.localization_file_for_new_keys(language, file_name);
+            self.write_new_localization_keys(language, &relative_path, exists, &keys)?;
+        }
+
+        if !overrides.is_empty() {
+            let (relative_path, exists) = self.localization_file_for_overrides(language);
+            self.write_new_localization_keys(language, &relative_path, exists, &overrides)?;
+        }
+
+        self.update_file_versions()?;
+        Self::reparse_localization_keys(&mut self.connection)?;
+
         Ok(())
     }
 
+    // Returns the names of localization files without the _l_<language>.yml suffix, which can be
+    // used as targets for new localization keys.
+    pub fn localization_file_names(&mut self) -> Result<Vec<String>> {
+        let content_type = models::ContentType {
+            name: ContentType::Localization.name().to_owned(),
+        };
+
+        let file_names: Vec<String> = models::File::belonging_to(&content_type)
+            .select(schema::file::file_name)
+            .distinct()
+            .get_results(&mut self.connection)?;
+
+        let mut names: Vec<String> = file_names
+            .iter()
+            .filter_map(|file_name| {
+                let stem = file_name.strip_suffix(".yml")?;
+                Language::values().iter().find_map(|language| {
+                    stem.strip_suffix(language.specifier())?
+                        .strip_suffix('_')
+                        .map(str::to_owned)
+                })
+            })
+            .collect();
+
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    // The layers which are read from archives can't be written to.
+    fn check_writable(path: &Path) -> Result<()> {
+        if let Some(archive_path) = vfs::archive_of(path) {
+            fail!(
+                "`{}` is in archive `{}`, which can't be changed",
+                path.display(),
+                archive_path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn validate_new_localization_key(key: &str, file_name: &str) -> Result<()> {
+        if key.is_empty() || key.contains(|c: char| c.is_whitespace() || c == ':' || c == '"') {
+            fail!("`{}` is not a valid localization key", key);
+        }
+
+        if file_name.is_empty() || file_name.contains(['/', '\\', ':', '.']) {
+            fail!("`{}` is not a valid localization file name", file_name);
+        }
+
+        Ok(())
+    }
+
+    // Returns the full path of the file the key is defined in, along with the layer of the file.
+    fn localization_key_full_path(
+        &mut self,
+        key: &str,
+        language: Language,
+    ) -> Result<(PathBuf, u32)> {
+        match self.find_localization_key_full_path(key, language)? {
+            Some(file) => Ok(file),
+            None => fail!("localization key `{}` doesn't exist for {}", key, language),
+        }
+    }
+
+    fn find_localization_key_full_path(
+        &mut self,
+        key: &str,
+        language: Language,
+    ) -> Result<Option<(PathBuf, u32)>> {
+        let file = schema::localization_key::table
+            .inner_join(schema::file::table)
+            .filter(schema::localization_key::key.eq(key))
+            .filter(schema::localization_key::language.eq(language.name()))
+            .select((schema::file::full_path, schema::file::layer))
+            .first::<(String, i32)>(&mut self.connection)
+            .optional()?;
+
+        Ok(file.map(|(full_path, layer)| (PathBuf::from(full_path), layer as u32)))
+    }
+
+    // Returns the path (relative to the root of the topmost layer) of the file which overrides
+    // the keys of the layers below and whether the file already exists. Keys in a replace folder
+    // take precedence over all the other keys in the game.
+    fn localization_file_for_overrides(&self, language: Language) -> (PathBuf, bool) {
+        let relative_path = self
+            .localization_dir_for_language(language)
+            .join(Self::REPLACE_FOLDER_NAME)
+            .join(Writer::localization_file_name(
+                Self::OVERRIDES_FILE_NAME,
+                language,
+            ));
+        let exists = self.dir_tree().find_node(&relative_path).is_some();
+
+        (relative_path, exists)
+    }
+
+    // Returns the path (relative to the root of the topmost layer) of the file that new keys with
+    // the given file name are written to and whether the file already exists.
+    pub fn localization_file_for_new_keys(
+        &self,
+        language: Language,
+        file_name: &str,
+    ) -> (PathBuf, bool) {
+        let target_file_name = Writer::localization_file_name(file_name, language);
+
+        match Self::find_file_by_name(self.dir_tree().root(), &target_file_name) {
+            Some(file) => (file.relative_path().to_owned(), true),
+            None => (
+                self.localization_dir_for_language(language)
+                    .join(target_file_name),
+                false,
+            ),
+        }
+    }
+
+    // Writes the keys into the given file, creating it (and adding it to the dir tree and the
+    // database) if it doesn't exist. Returns the id of the file.
+    fn write_new_localization_keys(
+        &mut self,
+        language: Language,
+        relative_path: &Path,
+        exists: bool,
+        keys: &[(&str, &str)],
+    ) -> Result<u64> {
+        let layer = self.dir_trees.len() - 1;
+        let dir_tree = &mut self.dir_trees[layer];
+        let full_path = dir_tree.root_path().join(relative_path);
+
+        if exists {
+            Writer::append_localization_keys(&full_path, keys)?;
+        } else {
+            Writer::create_localization_file(&full_path, language, keys)?;
+
+            let topmost_created = dir_tree.insert_file(relative_path)?;
+            let Some(node) = dir_tree.find_node(&topmost_created) else {
+                fail!(
+                    "failed to find `{}` in the dir tree",
+                    topmost_created.display()
+                );
+            };
+            Self::insert_node(&mut self.connection, node, dir_tree.layer())?;
+        }
+
+        match dir_tree.find_node(relative_path) {
+            Some(node) => Ok(node.id()),
+            None => fail!(
+                "failed to find `{}` in the dir tree",
+                relative_path.display()
+            ),
+        }
+    }
+
+    fn find_localization_key_file(
+        &mut self,
+        key: &str,
+        language: Language,
+    ) -> Result<Option<String>> {
+        let relative_path = schema::localization_key::table
+            .inner_join(schema::file::table)
+            .filter(schema::localization_key::key.eq(key))
+            .filter(schema::localization_key::language.eq(language.name()))
+            .select(schema::file::relative_path)
+            .first(&mut self.connection)
+            .optional()?;
+
+        Ok(relative_path)
+    }
+
+    // Localization files are either placed directly in the localization folder or (in newer
+    // games) in a subfolder named after the language. The subfolder is used if it exists or if
+    // the subfolders of other languages exist.
+    fn localization_dir_for_language(&self, language: Language) -> PathBuf {
+        let localization_dir = PathBuf::from("localization");
+        let language_dir = localization_dir.join(language.folder_name());
+
+        let Some(dir::Node::Directory(dir)) = self.dir_tree().find_node(&localization_dir) else {
+            return localization_dir;
+        };
+
+        let has_language_subfolders = dir.children().iter().any(|child| {
+            matches!(child, dir::Node::Directory(_))
+                && Language::values()
+                    .iter()
+                    .any(|language| child.file_name() == language.folder_name())
+        });
+
+        if has_language_subfolders {
+            language_dir
+        } else {
+            localization_dir
+        }
+    }
+
+    fn find_file_by_name<'a>(node: &'a dir::Node, file_name: &str) -> Option<&'a dir::File> {
+        match node {
+            dir::Node::Directory(dir) => dir
+                .children()
+                .iter()
+                .find_map(|child| Self::find_file_by_name(child, file_name)),
+            dir::Node::File(file) => (file.content_type() == ContentType::Localization
+                && file.file_name() == file_name)
+                .then_some(file),
+        }
+    }
+
+    fn insert_languages(connection: &mut diesel::SqliteConnection) -> Result<()> {
+        let languages: Vec<_> = Language::values()
+            .iter()
+            .map(|value| models::NewLanguage { name: value.name() })
+            .collect();
+
+        diesel::insert_into(schema::language::table)
+            .values(&languages)
+            .execute(connection)?;
+
+        Ok(())
+    }
+
+    fn insert_content_types(connection: &mut diesel::SqliteConnection) -> Result<()> {
+        let content_types: Vec<_> = ContentType::values()
+            .iter()
+            .map(|value| models::NewContentType { name: value.name() })
+            .collect();
+
+        diesel::insert_into(schema::content_type::table)
+            .values(&content_types)
+            .execute(connection)?;
+
+        Ok(())
+    }
+
+    fn insert_walk_options(
+        connection: &mut diesel::SqliteConnection,
+        walk_options: &WalkOptions,
+    ) -> Result<()> {
+        diesel::insert_into(schema::walk_options::table)
+            .values(models::NewWalkOptions {
+                id: 0,
+                follow_symlinks: walk_options.follow_symlinks,
+                ignore_patterns: &walk_options.ignore_patterns.join("\n"),
+            })
+            .execute(connection)?;
+
+        Ok(())
+    }
+
+    fn build_dir_trees(roots: &[PathBuf], walk_options: &WalkOptions) -> Result<Vec<DirTree>> {
+        let mut dir_trees: Vec<DirTree> = Vec::new();
+
+        for root in roots {
+            let dir_tree = match dir_trees.last() {
+                Some(below) => DirTree::new_layer(root, below)?,
+                None => DirTree::new(root, walk_options)?,
+            };
+
+            dir_trees.push(dir_tree);
+        }
+
+        if dir_trees.is_empty() {
+            fail!("no root directory given");
+        }
+
+        Ok(dir_trees)
+    }
+
     fn insert_dir_tree(
         connection: &mut diesel::SqliteConnection,
         dir_tree: &DirTree,
     ) -> Result<()> {
-        Self::insert_node(connection, dir_tree.root())
+        diesel::insert_into(schema::layer::table)
+            .values(models::NewLayer {
+                id: dir_tree.layer() as i32,
+                name: &dir_tree.layer_name(),
+                root_path: dir_tree.root_path().to_string_lossy(),
+                raw_root_path: Self::raw_path(dir_tree.root()),
+            })
+            .execute(connection)?;
+
+        Self::insert_node(connection, dir_tree.root(), dir_tree.layer())
+    }
+
+    // Inserts the node along with its subtree.
+    fn insert_node(
+        connection: &mut diesel::SqliteConnection,
+        node: &dir::Node,
+        layer: u32,
+    ) -> Result<()> {
+        let mut directories = Vec::new();
+        let mut files = Vec::new();
+
+        Self::collect_nodes(node, layer, &mut directories, &mut files)?;
+
+        for chunk in directories.chunks(Self::INSERT_BATCH_SIZE) {
+            diesel::insert_into(schema::directory::table)
+                .values(chunk)
+                .execute(connection)?;
+        }
+
+        for chunk in files.chunks(Self::INSERT_BATCH_SIZE) {
+            diesel::insert_into(schema::file::table)
+                .values(chunk)
+                .execute(connection)?;
+        }
+
+        Ok(())
     }
 
-    fn insert_node(connection: &mut diesel::SqliteConnection, node: &dir::Node) -> Result<()> {
+    fn collect_nodes<'a>(
+        node: &'a dir::Node,
+        layer: u32,
+        directories: &mut Vec<models::NewDirectory<'a>>,
+        files: &mut Vec<models::NewFile<'a>>,
+    ) -> Result<()> {
         match node {
             dir::Node::Directory(dir) => {
-                diesel::insert_into(schema::directory::table)
-                    .values(models::NewDirectory {
-                        id: dir.id() as i32,
-                        full_path: Self::path_to_str(dir.full_path())?,
-                        relative_path: Self::path_to_str(dir.relative_path())?,
-                        dir_name: Self::path_to_str(dir.dir_name())?,
-                        content_type: dir.content_type().name(),
-                    })
-                    .execute(connection)?;
+                directories.push(models::NewDirectory {
+                    id: dir.id() as i64,
+                    full_path: dir.full_path().to_string_lossy(),
+                    relative_path: dir.relative_path().to_string_lossy(),
+                    dir_name: dir.dir_name().to_string_lossy(),
+                    content_type: dir.content_type().name(),
+                    layer: layer as i32,
+                    raw_full_path: Self::raw_path(node),
+                });
 
                 for child in dir.children() {
-                    Self::insert_node(connection, child)?;
+                    Self::collect_nodes(child, layer, directories, files)?;
                 }
             }
             dir::Node::File(file) => {
-                diesel::insert_into(schema::file::table)
-                    .values(models::NewFile {
-                        id: file.id() as i32,
-                        full_path: Self::path_to_str(file.full_path())?,
-                        relative_path: Self::path_to_str(file.relative_path())?,
-                        file_name: Self::path_to_str(file.file_name())?,
-                        content_type: file.content_type().name(),
-                    })
-                    .execute(connection)?;
+                let (size, modified) = Self::file_version(file.full_path());
+
+                files.push(models::NewFile {
+                    id: file.id() as i64,
+                    full_path: file.full_path().to_string_lossy(),
+                    relative_path: file.relative_path().to_string_lossy(),
+                    file_name: file.file_name().to_string_lossy(),
+                    content_type: file.content_type().name(),
+                    layer: layer as i32,
+                    raw_full_path: Self::raw_path(node),
+                    size,
+                    modified,
+                });
             }
         }
 
         Ok(())
     }
 
-    fn parse_and_insert_localization_keys(connection: &mut diesel::SqliteConnection) -> Result<()> {
-        // The rules for parsing localization keys are as follows:
-        //
-        // 1. Localization files must be .yml format encoded in UTF-8-BOM. Otherwise, the game
-        // ignores the file.
-        // 2. The filename must end with _l_<language>.
-        // 3. Localization files are processed in reverse alphabetical order (from Z to A), adding
-        // a or 0 at the beginning of the localization file will make sure it is applied last.
-        // 4. File within a 'replace' folder work slightly differently as the localization keys
-        // within are checked specifically and overwrite any other identical localization keys.
+    // The size and the modification time of a file, as they're stored in the database. They're
+    // NULL if they can't be read, so that the file counts as changed.
+    fn file_version(path: &Path) -> (Option<i64>, Option<i64>) {
+        let Ok((size, modified)) = vfs::metadata(path) else {
+            return (None, None);
+        };
 
-        let files = Self::select_localization_files_for_parsing(connection)?;
+        let modified = modified
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos() as i64);
 
-        for file in files {
-            let path = PathBuf::from(file.full_path);
+        (Some(size as i64), modified)
+    }
 
-            let (language, keys) = Parser::parse_localization_file(&path)?;
+    // Stores the current size and modification time of the files which changed since they were
+    // indexed or last checked. Returns whether any of them is a localization file.
+    fn update_file_versions(&mut self) -> Result<bool> {
+        let files: Vec<(models::File, Option<i64>, Option<i64>)> = schema::file::table
+            .select((
+                models::File::as_select(),
+                schema::file::size,
+                schema::file::modified,
+            ))
+            .load(&mut self.connection)?;
+        let mut has_localization_changes = false;
 
-            for (key, value) in keys {
-                let new_key = models::NewLocalizationKey {
-                    key: &key,
-                    value: &value,
-                    file_id: file.id,
-                    language: language.name(),
-                };
+        for (file, size, modified) in files {
+            let version = Self::file_version(&file.path());
 
-                diesel::insert_into(schema::localization_key::table)
-                    .values(&new_key)
-                    .on_conflict(schema::localization_key::key)
-                    .do_update()
-                    .set(&new_key)
-                    .execute(connection)?;
+            if version == (size, modified) {
+                continue;
             }
+
+            diesel::update(schema::file::table.find(file.id))
+                .set((
+                    schema::file::size.eq(version.0),
+                    schema::file::modified.eq(version.1),
+                ))
+                .execute(&mut self.connection)?;
+
+            has_localization_changes |= file.content_type == ContentType::Localization.name();
+        }
+
+        Ok(has_localization_changes)
+    }
+
+    // The path as bytes if it can't be stored as text without losing something.
+    fn raw_path(node: &dir::Node) -> Option<Vec<u8>> {
+        (!node.has_utf8_path()).then(|| dir::path_to_bytes(node.full_path()))
+    }
+
+    // Parses the localization files and inserts their keys. The rules for parsing localization
+    // keys are as follows:
+    //
+    // 1. Localization files must be .yml format encoded in UTF-8-BOM. Otherwise, the game ignores
+    // the file.
+    // 2. The filename must end with _l_<language>.
+    // 3. Localization files are processed in reverse alphabetical order (from Z to A), adding a or
+    // 0 at the beginning of the localization file will make sure it is applied last.
+    // 4. File within a 'replace' folder work slightly differently as the localization keys within
+    // are checked specifically and overwrite any other identical localization keys.
+    //
+    // The files are parsed on worker threads, but the results are applied on this thread in the
+    // processing order, so that the same keys win as if the files were parsed one by one. The keys
+    // are collected before they're inserted, so that the ones which get overridden are never
+    // written. The table is expected to be empty.
+    fn parse_and_insert_localization_keys(connection: &mut diesel::SqliteConnection) -> Result<()> {
+        let files = Self::select_localization_files_for_parsing(connection)?;
+        // The value, the revision and the file ID by the key and the language.
+        let mut keys: HashMap<(String, &str), (String, Option<i32>, i64)> = HashMap::new();
+        let mut parse_errors = Vec::new();
+
+        parallel::map_ordered(
+            &files,
+            |file| {
+                // The text of a path which isn't valid UTF-8 is lossy, and the rules above can't
+                // be applied to it reliably.
+                if file.raw_full_path.is_some() {
+                    return (
+                        file.id,
+                        Err(error!("not parsed, because its path isn't valid UTF-8")),
+                    );
+                }
+
+                let path = Path::new(&file.full_path);
+                (file.id, Parser::parse_localization_file(path))
+            },
+            |(file_id, result)| {
+                // A file which can't be parsed doesn't stop the others from being parsed; the
+                // error is recorded instead.
+                let (language, entries) = match result {
+                    Ok(result) => result,
+                    Err(error) => {
+                        parse_errors.push((file_id, error));
+                        return Ok(());
+                    }
+                };
+
+                for entry in entries {
+                    keys.insert(
+                        (entry.key, language.name()),
+                        (entry.value, entry.revision, file_id),
+                    );
+                }
+
+                Ok(())
+            },
+        )?;
+
+        let new_keys: Vec<_> = keys
+            .iter()
+            .map(
+                |((key, language), (value, revision, file_id))| models::NewLocalizationKey {
+                    key,
+                    value,
+                    revision: *revision,
+                    file_id: *file_id,
+                    language,
+                },
+            )
+            .collect();
+
+        for chunk in new_keys.chunks(Self::INSERT_BATCH_SIZE) {
+            diesel::insert_into(schema::localization_key::table)
+                .values(chunk)
+                .execute(connection)?;
+        }
+
+        let new_parse_errors: Vec<_> = parse_errors
+            .iter()
+            .map(|(file_id, error)| models::NewParseError {
+                file_id: *file_id,
+                message: error.description(),
+            })
+            .collect();
+
+        for chunk in new_parse_errors.chunks(Self::INSERT_BATCH_SIZE) {
+            diesel::insert_into(schema::parse_error::table)
+                .values(chunk)
+                .execute(connection)?;
         }
 
         Ok(())
     }
 
+    fn reparse_localization_keys(connection: &mut diesel::SqliteConnection) -> Result<()> {
+        connection.transaction(|connection| {
+            diesel::delete(schema::localization_key::table).execute(connection)?;
+            diesel::delete(schema::parse_error::table).execute(connection)?;
+            Self::parse_and_insert_localization_keys(connection)
+        })
+    }
+
     fn select_localization_files_for_parsing(
         connection: &mut diesel::SqliteConnection,
     ) -> Result<Vec<models::FileIdPath>> {
         // Localization files are processed in reverse alphabetical order (from Z to A), adding
         // a or 0 at the beginning of the localization file will make sure it is applied last.
+        // The layers are processed in the load order, so that mods override the game.
 
         let content_type = models::ContentType {
             name: ContentType::Localization.name().to_owned(),
         };
 
         let files = models::File::belonging_to(&content_type)
-            .order_by(schema::file::file_name.desc())
+            .order_by((schema::file::layer, schema::file::file_name.desc()))
             .select(models::FileIdPath::as_select())
             .get_results(connection)?;
 
         Ok(files)
     }
 
+    fn escape_like_pattern(pattern: &str) -> String {
+        pattern
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    }
+
     // Helper function which returns a Result instead of Option.
     fn path_to_str(path: &Path) -> Result<&str> {
         path.to_str()
             .ok_or_else(|| error!("path `{}` contains invalid UTF-8", path.display()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::collections::BTreeSet;
+
+    // A column as (table, column, SQL type, nullable, part of the primary key).
+    type Column = (String, String, String, bool, bool);
+
+    // Reads the columns declared by the `table!` macros in schema.rs.
+    fn declared_columns() -> BTreeSet<Column> {
+        let mut columns = BTreeSet::new();
+        let mut table: Option<(String, Vec<String>)> = None;
+
+        for line in include_str!("database/schema.rs").lines().map(str::trim) {
+            if let Some((name, primary_key)) = line
+                .strip_suffix(") {")
+                .and_then(|line| line.split_once(" ("))
+            {
+                let primary_key = primary_key.split(", ").map(str::to_owned).collect();
+                table = Some((name.to_owned(), primary_key));
+            } else if line == "}" {
+                table = None;
+            } else if let Some((table, primary_key)) = &table
+                && let Some((column, column_type)) = line
+                    .strip_suffix(',')
+                    .and_then(|line| line.split_once(" -> "))
+            {
+                let (column_type, nullable) = match column_type
+                    .strip_prefix("Nullable<")
+                    .and_then(|column_type| column_type.strip_suffix('>'))
+                {
+                    Some(column_type) => (column_type, true),
+                    None => (column_type, false),
+                };
+                let column_type = match column_type {
+                    "Integer" | "BigInt" => "INTEGER",
+                    "Text" => "TEXT",
+                    "Bool" => "BOOLEAN",
+                    "Binary" => "BLOB",
+                    _ => panic!("unexpected column type `{}`", column_type),
+                };
+
+                columns.insert((
+                    table.clone(),
+                    column.to_owned(),
+                    column_type.to_owned(),
+                    nullable,
+                    primary_key.iter().any(|key| key == column),
+                ));
+            }
+        }
+
+        columns
+    }
+
+    // Reads the columns of the tables the migrations created.
+    fn applied_columns(connection: &mut diesel::SqliteConnection) -> BTreeSet<Column> {
+        #[derive(diesel::QueryableByName)]
+        struct Table {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            name: String,
+        }
+
+        #[derive(diesel::QueryableByName)]
+        struct TableColumn {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            name: String,
+            #[diesel(sql_type = diesel::sql_types::Text, column_name = "type")]
+            column_type: String,
+            #[diesel(sql_type = diesel::sql_types::Integer)]
+            notnull: i32,
+            #[diesel(sql_type = diesel::sql_types::Integer)]
+            pk: i32,
+        }
+
+        let tables = diesel::sql_query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )
+        .load::<Table>(connection)
+        .unwrap();
+
+        let mut columns = BTreeSet::new();
+
+        for table in tables {
+            let table_columns = diesel::sql_query(format!("PRAGMA table_info({})", table.name))
+                .load::<TableColumn>(connection)
+                .unwrap();
+
+            for column in table_columns {
+                columns.insert((
+                    table.name.clone(),
+                    column.name,
+                    column.column_type,
+                    column.notnull == 0,
+                    column.pk > 0,
+                ));
+            }
+        }
+
+        columns
+    }
+
+    // A directory for the roots, along with the path of a database file in it.
+    fn temp_dir(name: &str) -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new(&format!("database-{}", name));
+        let database_file_path = temp_dir.path().join("database.sqlite3");
+        (temp_dir, database_file_path)
+    }
+
+    fn key_value(database: &mut Database, key: &str, language: Language) -> Option<String> {
+        database
+            .get_localization_key(key, Some(language))
+            .unwrap()
+            .into_iter()
+            .next()
+            .map(|(key, _)| key.value)
+    }
+
+    fn migrated_connection() -> diesel::SqliteConnection {
+        let mut connection = diesel::SqliteConnection::establish(":memory:").unwrap();
+        Database::migrate(&mut connection).unwrap();
+        connection
+    }
+
+    #[test]
+    fn schema_matches_migrations() {
+        let mut connection = migrated_connection();
+
+        assert_eq!(declared_columns(), applied_columns(&mut connection));
+    }
+
+    #[test]
+    fn migrations_set_schema_version() {
+        let mut connection = migrated_connection();
+
+        assert_eq!(
+            Database::schema_version(&mut connection).unwrap(),
+            Database::MIGRATIONS.len()
+        );
+
+        // Migrating again does nothing.
+        Database::migrate(&mut connection).unwrap();
+    }
+
+    #[test]
+    fn migrations_fail_on_foreign_key_violations() {
+        let mut connection = diesel::SqliteConnection::establish(":memory:").unwrap();
+        let version = Database::MIGRATIONS.len() - 1;
+
+        for migration in &Database::MIGRATIONS[..version] {
+            connection.batch_execute(migration).unwrap();
+        }
+
+        // A parse error of a file which doesn't exist.
+        connection
+            .batch_execute(&format!(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO parse_error (file_id, message) VALUES (1, 'error');
+                 PRAGMA user_version = {};",
+                version
+            ))
+            .unwrap();
+
+        assert!(Database::migrate(&mut connection).is_err());
+        assert_eq!(Database::schema_version(&mut connection).unwrap(), version);
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let mut connection = migrated_connection();
+        connection
+            .batch_execute(&format!(
+                "PRAGMA user_version = {}",
+                Database::MIGRATIONS.len() + 1
+            ))
+            .unwrap();
+
+        assert!(Database::migrate(&mut connection).is_err());
+    }
+
+    #[test]
+    fn localization_keys_are_created() {
+        let (temp_dir, database_file_path) = temp_dir("create");
+        let root = temp_dir.path().join("game");
+        let english_dir = root.join("localization/english");
+        std::fs::create_dir_all(&english_dir).unwrap();
+        std::fs::write(
+            english_dir.join("a_l_english.yml"),
+            "\u{feff}l_english:\n a_key:0 \"A\"\n",
+        )
+        .unwrap();
+
+        let mut database = Database::new(
+            std::slice::from_ref(&root),
+            &WalkOptions::default(),
+            &database_file_path,
+        )
+        .unwrap();
+
+        database
+            .create_localization_key("new_key", "New", &[Language::English], "a", false)
+            .unwrap();
+        database
+            .create_localization_key("b_key", "B", &[Language::French], "b", false)
+            .unwrap();
+
+        assert_eq!(
+            key_value(&mut database, "new_key", Language::English).as_deref(),
+            Some("New")
+        );
+        assert_eq!(
+            key_value(&mut database, "b_key", Language::French).as_deref(),
+            Some("B")
+        );
+        // The language subfolders are used, since the game has them.
+        assert_eq!(
+            std::fs::read_to_string(root.join("localization/french/b_l_french.yml")).unwrap(),
+            "\u{feff}l_french:\n b_key:0 \"B\"\n"
+        );
+
+        assert!(
+            database
+                .create_localization_key("a_key", "Other", &[Language::English], "a", false)
+                .is_err()
+        );
+        database
+            .create_localization_key("a_key", "Other", &[Language::English], "a", true)
+            .unwrap();
+
+        assert_eq!(
+            key_value(&mut database, "a_key", Language::English).as_deref(),
+            Some("Other")
+        );
+        // The key is changed in place instead of being added a second time.
+        assert_eq!(
+            std::fs::read_to_string(english_dir.join("a_l_english.yml")).unwrap(),
+            "\u{feff}l_english:\n a_key:0 \"Other\"\n new_key:0 \"New\"\n"
+        );
+    }
+
+    #[test]
+    fn overriding_keys_change_the_definition_which_is_used() {
+        let (temp_dir, database_file_path) = temp_dir("override");
+        let game = temp_dir.path().join("game");
+        let mod_root = temp_dir.path().join("mod");
+        let game_file = game.join("localization/english/game_l_english.yml");
+        let game_content = "\u{feff}l_english:\n game_key:0 \"Game\"\n";
+        let mod_file = mod_root.join("localization/english/b_l_english.yml");
+        std::fs::create_dir_all(game_file.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(mod_file.parent().unwrap()).unwrap();
+        std::fs::write(&game_file, game_content).unwrap();
+        std::fs::write(&mod_file, "\u{feff}l_english:\n mod_key:0 \"Mod\"\n").unwrap();
+
+        let mut database = Database::new(
+            &[game.clone(), mod_root.clone()],
+            &WalkOptions::default(),
+            &database_file_path,
+        )
+        .unwrap();
+
+        // The key is changed in the file of the layer which defines it, rather than added to
+        // a_l_english.yml, which b_l_english.yml could take precedence over.
+        database
+            .create_localization_key("mod_key", "Changed", &[Language::English], "a", true)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&mod_file).unwrap(),
+            "\u{feff}l_english:\n mod_key:0 \"Changed\"\n"
+        );
+        assert!(
+            !mod_root
+                .join("localization/english/a_l_english.yml")
+                .exists()
+        );
+
+        // The keys of the game are overridden in the replace folder of the mod.
+        database
+            .create_localization_key("game_key", "Mod", &[Language::English], "a", true)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&game_file).unwrap(), game_content);
+        assert_eq!(
+            std::fs::read_to_string(
+                mod_root.join("localization/english/replace/overrides_l_english.yml")
+            )
+            .unwrap(),
+            "\u{feff}l_english:\n game_key:0 \"Mod\"\n"
+        );
+
+        assert_eq!(
+            key_value(&mut database, "mod_key", Language::English).as_deref(),
+            Some("Changed")
+        );
+        assert_eq!(
+            key_value(&mut database, "game_key", Language::English).as_deref(),
+            Some("Mod")
+        );
+    }
+
+    #[test]
+    fn languages_are_found_by_name() {
+        assert_eq!(Language::from_name("english"), Some(Language::English));
+        assert_eq!(Language::from_name("English"), Some(Language::English));
+        assert_eq!(
+            Language::from_name("Simplified Chinese"),
+            Some(Language::SimplifiedChinese)
+        );
+        assert_eq!(
+            Language::from_name("l_braz_por"),
+            Some(Language::BrazilianPortuguese)
+        );
+        assert_eq!(
+            Language::from_name("braz_por"),
+            Some(Language::BrazilianPortuguese)
+        );
+        assert_eq!(Language::from_name("klingon"), None);
+    }
+
+    #[test]
+    fn built_database_is_opened() {
+        let (temp_dir, database_file_path) = temp_dir("open");
+        let root = temp_dir.path().join("game");
+        std::fs::create_dir_all(root.join("localization")).unwrap();
+        std::fs::write(
+            root.join("localization/a_l_english.yml"),
+            "\u{feff}l_english:\n a_key:1 \"A\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("localization/a_l_german.yml"),
+            "\u{feff}l_german:\n a_key:0 \"Ä\"\n",
+        )
+        .unwrap();
+
+        drop(
+            Database::new(
+                std::slice::from_ref(&root),
+                &WalkOptions::default(),
+                &database_file_path,
+            )
+            .unwrap(),
+        );
+
+        let mut database = Database::open(&database_file_path).unwrap();
+        assert_eq!(database.dir_trees().len(), 1);
+
+        let mut keys: Vec<_> = database
+            .get_localization_key("a_key", None)
+            .unwrap()
+            .into_iter()
+            .map(|(key, relative_path)| {
+                (
+                    key.language,
+                    key.value,
+                    key.revision,
+                    relative_path.replace('\\', "/"),
+                )
+            })
+            .collect();
+        keys.sort();
+
+        assert_eq!(
+            keys,
+            [
+                (
+                    "English".to_owned(),
+                    "A".to_owned(),
+                    Some(1),
+                    "localization/a_l_english.yml".to_owned()
+                ),
+                (
+                    "German".to_owned(),
+                    "Ä".to_owned(),
+                    Some(0),
+                    "localization/a_l_german.yml".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            database
+                .get_localization_key("a_key", Some(Language::German))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(
+            database
+                .get_localization_key("missing", None)
+                .unwrap()
+                .is_empty()
+        );
+        drop(database);
+
+        // A file which wasn't indexed makes the database out of date.
+        std::fs::write(root.join("localization/b_l_english.yml"), "").unwrap();
+        assert!(Database::open(&database_file_path).is_err());
+    }
+
+    #[test]
+    fn updates_only_write_to_the_topmost_layer() {
+        let (temp_dir, database_file_path) = temp_dir("update");
+        let game = temp_dir.path().join("game");
+        let mod_root = temp_dir.path().join("mod");
+        let game_file = game.join("localization/english/game_l_english.yml");
+        let game_content = "\u{feff}l_english:\n a:0 \"A\"\n b:0 \"B\"\n";
+        std::fs::create_dir_all(game_file.parent().unwrap()).unwrap();
+        std::fs::write(&game_file, game_content).unwrap();
+        let mod_file = mod_root.join("localization/english/mod_l_english.yml");
+        std::fs::create_dir_all(mod_file.parent().unwrap()).unwrap();
+        std::fs::write(&mod_file, "\u{feff}l_english:\n c:0 \"C\"\n").unwrap();
+
+        let mut database = Database::new(
+            &[game.clone(), mod_root.clone()],
+            &WalkOptions::default(),
+            &database_file_path,
+        )
+        .unwrap();
+
+        // The keys of the game can't be removed, and nothing is written if one of the updates
+        // fails.
+        let error = database
+            .update_localization_keys(
+                Language::English,
+                &[
+                    LocalizationUpdate::Change {
+                        key: "c".to_owned(),
+                        value: "New C".to_owned(),
+                    },
+                    LocalizationUpdate::Remove {
+                        key: "b".to_owned(),
+                    },
+                ],
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.description(),
+            "localization key `b` is defined by `game` and can't be removed from `mod`"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&mod_file).unwrap(),
+            "\u{feff}l_english:\n c:0 \"C\"\n"
+        );
+
+        database
+            .update_localization_keys(
+                Language::English,
+                &[
+                    LocalizationUpdate::Change {
+                        key: "a".to_owned(),
+                        value: "New A".to_owned(),
+                    },
+                    LocalizationUpdate::Change {
+                        key: "c".to_owned(),
+                        value: "New C".to_owned(),
+                    },
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&game_file).unwrap(), game_content);
+        assert_eq!(
+            std::fs::read_to_string(&mod_file).unwrap(),
+            "\u{feff}l_english:\n c:0 \"New C\"\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(
+                mod_root.join("localization/english/replace/overrides_l_english.yml")
+            )
+            .unwrap(),
+            "\u{feff}l_english:\n a:0 \"New A\"\n"
+        );
+        assert_eq!(
+            key_value(&mut database, "a", Language::English).as_deref(),
+            Some("New A")
+        );
+
+        // The override is in the topmost layer now, so it's changed in place.
+        database
+            .update_localization_keys(
+                Language::English,
+                &[LocalizationUpdate::Change {
+                    key: "a".to_owned(),
+                    value: "Newer A".to_owned(),
+                }],
+            )
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&game_file).unwrap(), game_content);
+        assert_eq!(
+            key_value(&mut database, "a", Language::English).as_deref(),
+            Some("Newer A")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_roots_are_indexed() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let (temp_dir, database_file_path) = temp_dir("non-utf8");
+        let root = temp_dir
+            .path()
+            .join(std::ffi::OsStr::from_bytes(b"mod\xff"));
+        std::fs::create_dir_all(root.join("localization")).unwrap();
+        std::fs::write(
+            root.join("localization/a_l_english.yml"),
+            "\u{feff}l_english:\n a:0 \"A\"\n",
+        )
+        .unwrap();
+
+        drop(
+            Database::new(
+                std::slice::from_ref(&root),
+                &WalkOptions::default(),
+                &database_file_path,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            Database::read_roots(&database_file_path).unwrap(),
+            std::slice::from_ref(&root)
+        );
+
+        let mut database = Database::open(&database_file_path).unwrap();
+        assert_eq!(database.dir_tree().root_path(), root);
+        assert_eq!(database.get_files(None).unwrap().len(), 1);
+    }
+}
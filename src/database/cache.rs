@@ -0,0 +1,210 @@
+use super::Database;
+use super::dir::{self, WalkOptions};
+use crate::core::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// The database files of the indexed workspaces, one per mod stack, so that switching between
+// workspaces doesn't require indexing them again.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+// A database file in the cache.
+pub struct CachedDatabase {
+    pub path: PathBuf,
+    // The root directories of the workspace, or the reason they couldn't be read.
+    pub roots: Result<Vec<PathBuf>>,
+    // The options the directories were walked with; the defaults if they couldn't be read.
+    pub walk_options: WalkOptions,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl Cache {
+    const EXTENSION: &'static str = "sqlite3";
+
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_owned(),
+        }
+    }
+
+    // The database file of a workspace is named after a hash of its canonical root paths, so
+    // that the same directories map to the same file however they were given.
+    pub fn database_file_path(&self, roots: &[PathBuf]) -> Result<PathBuf> {
+        let roots = Self::canonicalize(roots)?;
+        let mut hasher = Fnv1a::default();
+
+        for root in &roots {
+            hasher.write(&dir::path_to_bytes(root));
+            // The separator keeps e.g. `a` + `bc` and `ab` + `c` apart.
+            hasher.write(&[0]);
+        }
+
+        Ok(self
+            .dir
+            .join(format!("{:016x}.{}", hasher.finish(), Self::EXTENSION)))
+    }
+
+    // Opens the cached database of the workspace. The directories are indexed only if there's
+    // no usable database for them yet, or it was built with different walk options.
+    pub fn open(&self, roots: &[PathBuf], walk_options: &WalkOptions) -> Result<Database> {
+        let path = self.database_file_path(roots)?;
+
+        if path.is_file()
+            && let Ok(database) = Database::open(&path)
+            && Self::has_roots(&database, roots)?
+            && database.dir_tree().walk_options() == walk_options
+        {
+            return Ok(database);
+        }
+
+        self.rebuild(roots, walk_options)
+    }
+
+    // Indexes the directories of the workspace again, replacing its cached database.
+    pub fn rebuild(&self, roots: &[PathBuf], walk_options: &WalkOptions) -> Result<Database> {
+        let roots = Self::canonicalize(roots)?;
+        let path = self.database_file_path(&roots)?;
+
+        fs::create_dir_all(&self.dir)?;
+
+        // The old database may have been created by a different version with a different schema,
+        // so it's not reused.
+        Self::remove_database_files(&path)?;
+
+        Database::new(&roots, walk_options, &path)
+    }
+
+    // Lists the cached databases, the most recently built first.
+    pub fn list(&self) -> Result<Vec<CachedDatabase>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut databases = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+
+            if path
+                .extension()
+                .is_none_or(|extension| extension != Self::EXTENSION)
+            {
+                continue;
+            }
+
+            let metadata = fs::metadata(&path)?;
+
+            databases.push(CachedDatabase {
+                roots: Database::read_roots(&path),
+                walk_options: Database::read_walk_options(&path).unwrap_or_default(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
+                path,
+            });
+        }
+
+        databases.sort_by_key(|database| std::cmp::Reverse(database.modified));
+
+        Ok(databases)
+    }
+
+    pub fn delete(&self, database_file_path: &Path) -> Result<()> {
+        if database_file_path.parent() != Some(self.dir.as_path()) {
+            fail!(
+                "`{}` is not a cached database",
+                database_file_path.display()
+            );
+        }
+
+        Self::remove_database_files(database_file_path)
+    }
+
+    // Removes the database file along with the files SQLite keeps next to it.
+    fn remove_database_files(database_file_path: &Path) -> Result<()> {
+        for suffix in ["", "-wal", "-shm", "-journal"] {
+            let mut path = database_file_path.as_os_str().to_owned();
+            path.push(suffix);
+
+            match fs::remove_file(&path) {
+                Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(error.into());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn canonicalize(roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        roots
+            .iter()
+            .map(|root| {
+                root.canonicalize().map_err(|error| {
+                    error!(
+                        "failed to resolve directory `{}`: {}",
+                        root.display(),
+                        error
+                    )
+                })
+            })
+            .collect()
+    }
+
+    // Guards against hash collisions and databases which were renamed by hand.
+    fn has_roots(database: &Database, roots: &[PathBuf]) -> Result<bool> {
+        let roots = Self::canonicalize(roots)?;
+
+        Ok(database
+            .dir_trees()
+            .iter()
+            .map(|dir_tree| dir_tree.root_path())
+            .eq(roots.iter().map(PathBuf::as_path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Language;
+    use crate::testing::TempDir;
+
+    fn value(database: &mut Database, key: &str) -> String {
+        let keys = database
+            .get_localization_key(key, Some(Language::English))
+            .unwrap();
+        keys[0].0.value.clone()
+    }
+
+    #[test]
+    fn changed_files_are_parsed_again_when_reopened() {
+        let temp_dir = TempDir::new("cache");
+        let dir = temp_dir.path();
+        let root = dir.join("game");
+        let file_path = root.join("localization/a_l_english.yml");
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(&file_path, "\u{feff}l_english:\n a:0 \"A\"\n").unwrap();
+
+        let cache = Cache::new(&dir.join("cache"));
+        let roots = [root.clone()];
+        let walk_options = WalkOptions::default();
+
+        let mut database = cache.open(&roots, &walk_options).unwrap();
+        assert_eq!(value(&mut database, "a"), "A");
+        drop(database);
+
+        // Reopening an unchanged workspace reuses the database.
+        let mut database = Database::open(&cache.database_file_path(&roots).unwrap()).unwrap();
+        assert_eq!(value(&mut database, "a"), "A");
+        drop(database);
+
+        fs::write(&file_path, "\u{feff}l_english:\n a:0 \"Changed\"\n").unwrap();
+
+        let mut database = cache.open(&roots, &walk_options).unwrap();
+        assert_eq!(value(&mut database, "a"), "Changed");
+    }
+}
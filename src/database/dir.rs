@@ -1,16 +1,20 @@
+use super::ignore::IgnoreRules;
+use super::parallel;
+use super::vfs::{self, EntryKind, FileSystem};
 use crate::core::*;
 use crate::database::ContentType;
-use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Directory {
     full_path: PathBuf,
     relative_path: PathBuf,
     dir_name: PathBuf,
 
     content_type: ContentType,
-    id: u32,
+    id: u64,
 
     children: Vec<Node>,
 }
@@ -32,7 +36,7 @@ impl Directory {
         self.content_type
     }
 
-    pub fn id(&self) -> u32 {
+    pub fn id(&self) -> u64 {
         self.id
     }
 
@@ -41,14 +45,14 @@ impl Directory {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct File {
     full_path: PathBuf,
     relative_path: PathBuf,
     file_name: PathBuf,
 
     content_type: ContentType,
-    id: u32,
+    id: u64,
 }
 
 impl File {
@@ -68,12 +72,12 @@ impl File {
         self.content_type
     }
 
-    pub fn id(&self) -> u32 {
+    pub fn id(&self) -> u64 {
         self.id
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Node {
     Directory(Directory),
     File(File),
@@ -108,29 +112,162 @@ impl Node {
         }
     }
 
-    pub fn id(&self) -> u32 {
+    pub fn id(&self) -> u64 {
         match self {
             Self::Directory(dir) => dir.id(),
             Self::File(file) => file.id(),
         }
     }
+
+    // Derives the ID of a node from the layer, the kind of the node and its relative path, so that
+    // a node keeps its ID when the directories are indexed again. The IDs of directories and
+    // files are kept apart by the kind. The hash is cut down to 63 bits to fit into an SQLite
+    // integer. `used` are the IDs already given out; on the unlikely collision the next free ID
+    // is taken.
+    fn allocate_id(used: &mut HashSet<u64>, layer: u32, is_dir: bool, relative_path: &Path) -> u64 {
+        const MASK: u64 = u64::MAX >> 1;
+
+        let mut hasher = Fnv1a::default();
+        hasher.write(&layer.to_le_bytes());
+        hasher.write(if is_dir { b"d" } else { b"f" });
+
+        // The path is normalized to `/` as the separator, so that the IDs don't depend on the
+        // platform. The IDs are stored, so the bytes of the components have to be stable.
+        for component in relative_path.components() {
+            let component = component.as_os_str();
+
+            hasher.write(b"/");
+            match component.to_str() {
+                Some(component) => hasher.write(component.as_bytes()),
+                None => hasher.write(&path_to_bytes(Path::new(component))),
+            }
+        }
+
+        let mut id = hasher.finish() & MASK;
+
+        while !used.insert(id) {
+            id = id.wrapping_add(1) & MASK;
+        }
+
+        id
+    }
+
+    // Whether the path is valid UTF-8. Other paths are indexed and shown in a lossy form, but
+    // their content isn't parsed.
+    pub fn has_utf8_path(&self) -> bool {
+        self.full_path().to_str().is_some()
+    }
+
+    // Whether the game could read the node. Hidden entries (e.g. .git) and files with extensions
+    // the games don't use (e.g. README.md) are left out.
+    pub fn is_game_file(&self) -> bool {
+        const EXTENSIONS: &[&str] = &[
+            "txt", "yml", "csv", "lua", "mod", "asset", "gui", "gfx", "dds", "tga", "png", "bmp",
+            "shader", "fxh", "mesh", "anim", "cur", "ani", "wav", "ogg", "fnt", "ttf", "otf",
+            "map", "json", "settings",
+        ];
+
+        if self.file_name().to_string_lossy().starts_with('.') {
+            return false;
+        }
+
+        match self {
+            Self::Directory(_) => true,
+            Self::File(file) => file.full_path().extension().is_some_and(|extension| {
+                EXTENSIONS
+                    .iter()
+                    .any(|value| extension.eq_ignore_ascii_case(value))
+            }),
+        }
+    }
+}
+
+// Paths which aren't valid UTF-8 can't be stored as text, so they're stored as bytes. Unlike
+// `OsStr::as_encoded_bytes`, the encoding is stable across versions.
+#[cfg(unix)]
+pub fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+// The UTF-16 code units in little-endian order, since a path may contain unpaired surrogates.
+#[cfg(windows)]
+pub fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+
+    path.as_os_str()
+        .encode_wide()
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+#[cfg(windows)]
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::windows::ffi::OsStringExt;
+
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+
+    PathBuf::from(std::ffi::OsString::from_wide(&wide))
+}
+
+// How the directories are walked when a dir tree is built.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct WalkOptions {
+    // Whether symbolic links are followed. Links which would form a cycle are always skipped.
+    pub follow_symlinks: bool,
+    // Gitignore-style patterns of the entries to leave out, on top of the default ones and the
+    // ones in the .pdxexplorerignore file of the root.
+    pub ignore_patterns: Vec<String>,
 }
 
-#[derive(Debug)]
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: true,
+            ignore_patterns: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct DirTree {
     root: Node,
+    layer: u32,
+    // The IDs of the nodes of this tree and the trees below it.
+    ids: HashSet<u64>,
+    options: WalkOptions,
+    // Entries which couldn't be read or were skipped for another reason than the ignore rules.
+    warnings: Vec<String>,
 }
 
 impl DirTree {
-    pub fn new(root: &Path) -> Result<Self> {
-        if !root.is_dir() {
-            fail!(
-                "root path `{}` doesn't point to a directory",
-                root.display()
-            );
-        }
+    // The root is a directory or a zip archive.
+    pub fn new(root: &Path, options: &WalkOptions) -> Result<Self> {
+        DirTreeBuilder::new(root, 0, HashSet::new(), options.clone())?.build()
+    }
 
-        DirTreeBuilder::new(root).build()
+    // Builds the tree of a mod which is loaded on top of the given tree, walking it the same way.
+    // The IDs of its nodes are unique across all layers.
+    pub fn new_layer(root: &Path, below: &DirTree) -> Result<Self> {
+        DirTreeBuilder::new(
+            root,
+            below.layer + 1,
+            below.ids.clone(),
+            below.options.clone(),
+        )?
+        .build()
     }
 
     pub fn root(&self) -> &Node {
@@ -140,31 +277,412 @@ impl DirTree {
     pub fn root_path(&self) -> &Path {
         self.root.full_path()
     }
+
+    pub fn walk_options(&self) -> &WalkOptions {
+        &self.options
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    // Position of the tree in the load order; the game is 0.
+    pub fn layer(&self) -> u32 {
+        self.layer
+    }
+
+    // Name of the game or the mod, from the `name` in the descriptor.mod file of mods, or the name
+    // of the root directory or archive.
+    pub fn layer_name(&self) -> String {
+        self.descriptor_value("name").unwrap_or_else(|| {
+            let root = self.root_path();
+            let name = if vfs::is_archive(root) {
+                root.file_stem()
+            } else {
+                root.file_name()
+            };
+
+            name.unwrap_or(root.as_os_str())
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    // The Steam Workshop ID of the mod, from the `remote_file_id` in its descriptor.mod file. Mods
+    // downloaded from the Workshop sit in folders named after it, e.g.
+    // `steamapps/workshop/content/<app ID>/<Workshop ID>`, so that's used as well.
+    pub fn workshop_id(&self) -> Option<u64> {
+        if let Some(id) = self
+            .descriptor_value("remote_file_id")
+            .and_then(|id| id.parse().ok())
+        {
+            return Some(id);
+        }
+
+        let root = self.root_path();
+
+        if !root.parent()?.parent()?.ends_with("workshop/content") {
+            return None;
+        }
+
+        root.file_name()?.to_str()?.parse().ok()
+    }
+
+    fn descriptor_value(&self, key: &str) -> Option<String> {
+        let descriptor = vfs::read_to_string(&self.root_path().join("descriptor.mod")).ok()?;
+
+        descriptor
+            .lines()
+            .find_map(|line| {
+                let (line_key, value) = line.split_once('=')?;
+                (line_key.trim() == key).then(|| value.trim().trim_matches('"').to_owned())
+            })
+            .filter(|value| !value.is_empty())
+    }
+
+    // Returns all the files in the tree in depth-first order.
+    pub fn files(&self) -> Vec<&File> {
+        fn collect<'a>(node: &'a Node, files: &mut Vec<&'a File>) {
+            match node {
+                Node::Directory(dir) => {
+                    for child in dir.children() {
+                        collect(child, files);
+                    }
+                }
+                Node::File(file) => files.push(file),
+            }
+        }
+
+        let mut files = Vec::new();
+        collect(&self.root, &mut files);
+        files
+    }
+
+    // The given path must be relative to the root of the dir tree.
+    pub fn find_node(&self, relative_path: &Path) -> Option<&Node> {
+        let mut node = &self.root;
+
+        for component in relative_path.components() {
+            let Node::Directory(dir) = node else {
+                return None;
+            };
+
+            node = dir
+                .children()
+                .iter()
+                .find(|child| child.file_name() == component.as_os_str())?;
+        }
+
+        Some(node)
+    }
+
+    // Adds a file node (and any missing parent directories) for a file which was created on disk
+    // after the tree was built. Returns the relative path of the topmost node that was created.
+    // The given path must be relative to the root of the dir tree.
+    pub fn insert_file(&mut self, relative_path: &Path) -> Result<PathBuf> {
+        if self.find_node(relative_path).is_some() {
+            fail!("`{}` is already in the dir tree", relative_path.display());
+        }
+
+        let (Some(parent), Some(file_name)) = (relative_path.parent(), relative_path.file_name())
+        else {
+            fail!("cannot insert `{}` as a file", relative_path.display());
+        };
+
+        let root_path = self.root_path().to_owned();
+        let mut dir = match &mut self.root {
+            Node::Directory(dir) => dir,
+            Node::File(_) => fail!("root of the dir tree is not a directory"),
+        };
+        let mut topmost_created = None;
+
+        for component in parent.components() {
+            let position = dir
+                .children
+                .iter()
+                .position(|child| child.file_name() == component.as_os_str());
+
+            let position = match position {
+                Some(position) => position,
+                None => {
+                    let relative_path = dir.relative_path.join(component);
+                    topmost_created.get_or_insert_with(|| relative_path.clone());
+
+                    dir.children.push(Node::Directory(Directory {
+                        full_path: root_path.join(&relative_path),
+                        dir_name: PathBuf::from(component.as_os_str()),
+                        content_type: DirTreeBuilder::get_content_type_from_relative_path(
+                            &relative_path,
+                        )?,
+                        id: Node::allocate_id(&mut self.ids, self.layer, true, &relative_path),
+                        relative_path,
+                        children: Vec::new(),
+                    }));
+
+                    dir.children.len() - 1
+                }
+            };
+
+            dir = match &mut dir.children[position] {
+                Node::Directory(child) => child,
+                Node::File(file) => fail!(
+                    "cannot insert `{}`; `{}` is a file",
+                    relative_path.display(),
+                    file.relative_path().display()
+                ),
+            };
+        }
+
+        dir.children.push(Node::File(File {
+            full_path: root_path.join(relative_path),
+            relative_path: relative_path.to_owned(),
+            file_name: PathBuf::from(file_name),
+            content_type: DirTreeBuilder::get_content_type_from_relative_path(relative_path)?,
+            id: Node::allocate_id(&mut self.ids, self.layer, false, relative_path),
+        }));
+
+        Ok(topmost_created.unwrap_or_else(|| relative_path.to_owned()))
+    }
 }
 
 // This is meant to be a private builder for use by this module only.
 struct DirTreeBuilder<'a> {
     root_path: &'a Path,
-    dir_ids: std::ops::RangeFrom<u32>,
-    file_ids: std::ops::RangeFrom<u32>,
+    fs: Arc<dyn FileSystem>,
+    layer: u32,
+    ids: HashSet<u64>,
+    options: WalkOptions,
+    // The entries of every directory under the root, read up front.
+    listings: HashMap<PathBuf, Vec<DirEntry>>,
+    warnings: Vec<String>,
+}
+
+// An entry of a directory listing.
+struct DirEntry {
+    path: PathBuf,
+    is_dir: bool,
+    // The path with all symbolic links resolved, for directories.
+    real_path: Option<PathBuf>,
+}
+
+// A directory which is yet to be read.
+struct PendingDir {
+    path: PathBuf,
+    ancestry: Arc<Ancestry>,
+}
+
+// The real paths of a directory and the directories it's in, as they were reached by the walk.
+// A symbolic link to any of them would form a cycle.
+struct Ancestry {
+    real_path: PathBuf,
+    parent: Option<Arc<Ancestry>>,
+}
+
+impl Ancestry {
+    fn contains(&self, real_path: &Path) -> bool {
+        let mut ancestry = Some(self);
+
+        while let Some(current) = ancestry {
+            if current.real_path == real_path {
+                return true;
+            }
+
+            ancestry = current.parent.as_deref();
+        }
+
+        false
+    }
 }
 
 impl<'a> DirTreeBuilder<'a> {
-    fn new(root: &'a Path) -> Self {
-        Self {
+    fn new(root: &'a Path, layer: u32, ids: HashSet<u64>, options: WalkOptions) -> Result<Self> {
+        Ok(Self {
             root_path: root,
-            dir_ids: 0..,
-            file_ids: 0..,
-        }
+            fs: vfs::mount(root)?,
+            layer,
+            ids,
+            options,
+            listings: HashMap::new(),
+            warnings: Vec::new(),
+        })
     }
 
+    // The directories are read in parallel, while the nodes are created in a single pass
+    // afterwards. The children are sorted by name, so that the tree (and the IDs given out on
+    // collisions) doesn't depend on the order in which the file system lists the entries or the
+    // threads finish reading them.
     fn build(mut self) -> Result<DirTree> {
+        self.read_dirs()?;
+
         Ok(DirTree {
-            root: self.create_node(self.root_path.to_owned())?,
+            root: self.create_node(self.root_path.to_owned(), true)?,
+            layer: self.layer,
+            ids: self.ids,
+            options: self.options,
+            warnings: self.warnings,
         })
     }
 
-    fn create_node(&mut self, path: PathBuf) -> Result<Node> {
+    // Reads the directories level by level; the directories of a level are read in parallel.
+    fn read_dirs(&mut self) -> Result<()> {
+        let ignore_rules = IgnoreRules::for_root(self.root_path, &self.options.ignore_patterns)?;
+        let root = PendingDir {
+            path: self.root_path.to_owned(),
+            ancestry: Arc::new(Ancestry {
+                real_path: self.root_path.canonicalize()?,
+                parent: None,
+            }),
+        };
+        let mut level = vec![root];
+
+        while !level.is_empty() {
+            let mut next_level = Vec::new();
+
+            parallel::map_ordered(
+                &level,
+                |dir| {
+                    let mut warnings = Vec::new();
+                    let entries = Self::read_dir(
+                        self.fs.as_ref(),
+                        self.root_path,
+                        &self.options,
+                        &ignore_rules,
+                        dir,
+                        &mut warnings,
+                    );
+
+                    (dir.path.clone(), dir.ancestry.clone(), entries, warnings)
+                },
+                |(path, ancestry, entries, warnings)| {
+                    next_level.extend(entries.iter().filter_map(|entry| {
+                        Some(PendingDir {
+                            path: entry.path.clone(),
+                            ancestry: Arc::new(Ancestry {
+                                real_path: entry.real_path.clone()?,
+                                parent: Some(ancestry.clone()),
+                            }),
+                        })
+                    }));
+                    self.listings.insert(path, entries);
+                    self.warnings.extend(warnings);
+
+                    Ok(())
+                },
+            )?;
+
+            level = next_level;
+        }
+
+        Ok(())
+    }
+
+    // A directory or an entry which can't be read is left out with a warning, rather than failing
+    // the whole tree.
+    fn read_dir(
+        fs: &dyn FileSystem,
+        root_path: &Path,
+        options: &WalkOptions,
+        ignore_rules: &IgnoreRules,
+        dir: &PendingDir,
+        warnings: &mut Vec<String>,
+    ) -> Vec<DirEntry> {
+        let read_dir = match fs.read_dir(&dir.path) {
+            Ok(read_dir) => read_dir,
+            Err(error) => {
+                warnings.push(format!(
+                    "failed to read directory `{}`: {}",
+                    dir.path.display(),
+                    error
+                ));
+                return Vec::new();
+            }
+        };
+
+        let mut entries = Vec::new();
+
+        for entry in read_dir {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    warnings.push(format!(
+                        "failed to read an entry of `{}`: {}",
+                        dir.path.display(),
+                        error
+                    ));
+                    continue;
+                }
+            };
+
+            let path = entry.path;
+            let is_symlink = entry.kind == EntryKind::Symlink;
+
+            let (is_dir, real_path) = if is_symlink {
+                if !options.follow_symlinks {
+                    continue;
+                }
+
+                match path.canonicalize() {
+                    Ok(real_path) => (real_path.is_dir(), Some(real_path)),
+                    Err(error) => {
+                        warnings.push(format!(
+                            "skipped symbolic link `{}`: {}",
+                            path.display(),
+                            error
+                        ));
+                        continue;
+                    }
+                }
+            } else {
+                let is_dir = entry.kind == EntryKind::Dir;
+                let real_path = is_dir
+                    .then(|| path.file_name())
+                    .flatten()
+                    .map(|name| dir.ancestry.real_path.join(name));
+                (is_dir, real_path)
+            };
+
+            if ignore_rules.is_ignored(&Self::normalize(root_path, &path), is_dir) {
+                continue;
+            }
+
+            if is_dir
+                && is_symlink
+                && let Some(real_path) = &real_path
+                && dir.ancestry.contains(real_path)
+            {
+                warnings.push(format!(
+                    "skipped symbolic link `{}`, which points to `{}` and would form a cycle",
+                    path.display(),
+                    real_path.display()
+                ));
+                continue;
+            }
+
+            entries.push(DirEntry {
+                path,
+                is_dir,
+                real_path: real_path.filter(|_| is_dir),
+            });
+        }
+
+        entries.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+
+        entries
+    }
+
+    // The path relative to the root with `/` as the separator, which the ignore rules are matched
+    // against.
+    fn normalize(root_path: &Path, path: &Path) -> String {
+        let relative_path = path.strip_prefix(root_path).unwrap_or(path);
+
+        relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn create_node(&mut self, path: PathBuf, is_dir: bool) -> Result<Node> {
         let relative_path = path.strip_prefix(self.root_path)?.to_path_buf();
         let file_name =
             PathBuf::from(path.file_name().ok_or_else(|| {
@@ -172,8 +690,9 @@ impl<'a> DirTreeBuilder<'a> {
             })?);
         let content_type = Self::get_content_type_from_relative_path(&relative_path)?;
 
-        if path.is_dir() {
-            let id = self.dir_ids.next().unwrap();
+        let id = Node::allocate_id(&mut self.ids, self.layer, is_dir, &relative_path);
+
+        if is_dir {
             let children = self.create_children_nodes(&path)?;
 
             Ok(Node::Directory(Directory {
@@ -187,8 +706,6 @@ impl<'a> DirTreeBuilder<'a> {
                 children,
             }))
         } else {
-            let id = self.file_ids.next().unwrap();
-
             Ok(Node::File(File {
                 full_path: path,
                 relative_path,
@@ -201,13 +718,11 @@ impl<'a> DirTreeBuilder<'a> {
     }
 
     fn create_children_nodes(&mut self, path: &Path) -> Result<Vec<Node>> {
-        let mut children = Vec::new();
+        let entries = self.listings.remove(path).unwrap_or_default();
+        let mut children = Vec::with_capacity(entries.len());
 
-        for child in fs::read_dir(path)? {
-            let child = child?;
-            let child_path = child.path();
-            let child_node = self.create_node(child_path)?;
-            children.push(child_node);
+        for entry in entries {
+            children.push(self.create_node(entry.path, entry.is_dir)?);
         }
 
         children.shrink_to_fit();
@@ -223,3 +738,114 @@ impl<'a> DirTreeBuilder<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    #[test]
+    fn ids_depend_only_on_layer_kind_and_path() {
+        let path = Path::new("localization/english/a_l_english.yml");
+        let id = Node::allocate_id(&mut HashSet::new(), 0, false, path);
+
+        assert_eq!(Node::allocate_id(&mut HashSet::new(), 0, false, path), id);
+        assert_ne!(Node::allocate_id(&mut HashSet::new(), 1, false, path), id);
+        assert_ne!(Node::allocate_id(&mut HashSet::new(), 0, true, path), id);
+        assert!(id <= i64::MAX as u64);
+        // The IDs are stored, so they mustn't change between versions.
+        assert_eq!(id, 8822991909650438632);
+    }
+
+    #[test]
+    fn colliding_ids_take_the_next_free_one() {
+        let path = Path::new("common");
+        let mut used = HashSet::new();
+        let first = Node::allocate_id(&mut used, 0, true, path);
+        let second = Node::allocate_id(&mut used, 0, true, path);
+
+        assert_eq!(second, (first + 1) & (u64::MAX >> 1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths_round_trip_through_bytes() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(std::ffi::OsStr::from_bytes(
+            b"localization/b\xff_l_english.yml",
+        ));
+
+        assert!(path.to_str().is_none());
+        assert_eq!(path_from_bytes(&path_to_bytes(path)), path);
+    }
+
+    fn relative_paths(dir_tree: &DirTree) -> Vec<String> {
+        let mut paths: Vec<_> = dir_tree
+            .files()
+            .iter()
+            .map(|file| file.relative_path().to_string_lossy().replace('\\', "/"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn ignored_entries_are_left_out() {
+        let temp_dir = TempDir::new("dir-ignore");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("common")).unwrap();
+        fs::write(root.join(".git/HEAD"), "").unwrap();
+        fs::write(root.join("common/a.txt"), "").unwrap();
+        fs::write(root.join("common/a.bak"), "").unwrap();
+        fs::write(root.join("common/b.txt"), "").unwrap();
+        fs::write(root.join(IgnoreRules::FILE_NAME), "b.txt\n").unwrap();
+
+        let options = WalkOptions {
+            ignore_patterns: vec!["*.bak".to_owned()],
+            ..Default::default()
+        };
+        let dir_tree = DirTree::new(root, &options).unwrap();
+
+        assert_eq!(
+            relative_paths(&dir_tree),
+            [IgnoreRules::FILE_NAME, "common/a.txt"]
+        );
+        assert!(dir_tree.warnings().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_cycles_are_skipped_with_a_warning() {
+        let temp_dir = TempDir::new("dir-symlinks");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("common/inner")).unwrap();
+        fs::write(root.join("common/inner/a.txt"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("common"), root.join("common/inner/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("common/inner"), root.join("linked")).unwrap();
+
+        let dir_tree = DirTree::new(root, &WalkOptions::default()).unwrap();
+
+        // The links are followed until one leads back to a directory the walk came through.
+        assert_eq!(
+            relative_paths(&dir_tree),
+            [
+                "common/inner/a.txt",
+                "linked/a.txt",
+                "linked/loop/inner/a.txt"
+            ]
+        );
+        assert_eq!(dir_tree.warnings().len(), 2);
+
+        let options = WalkOptions {
+            follow_symlinks: false,
+            ..Default::default()
+        };
+        let dir_tree = DirTree::new(root, &options).unwrap();
+
+        assert_eq!(relative_paths(&dir_tree), ["common/inner/a.txt"]);
+        assert!(dir_tree.warnings().is_empty());
+    }
+}
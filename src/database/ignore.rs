@@ -0,0 +1,173 @@
+use super::vfs;
+use crate::core::*;
+use crate::glob::Pattern;
+use std::io;
+use std::path::Path;
+
+// Gitignore-style rules for the entries which are left out of the dir tree:
+//
+// * blank lines and lines starting with `#` are skipped
+// * a pattern without a `/` matches the name of an entry at any depth, e.g. `*.swp`
+// * a pattern with a `/` matches the path relative to the root, e.g. `/logs` or `gfx/**/*.psd`
+// * a trailing `/` only matches directories, e.g. `.git/`
+// * a leading `!` includes entries again which an earlier pattern left out
+//
+// The last matching rule wins. The entries of a directory which is left out are never looked at,
+// so they can't be included again.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    // Whether the pattern is matched against the relative path rather than the name.
+    anchored: bool,
+}
+
+impl IgnoreRules {
+    // The file in the root directory the rules are read from.
+    pub const FILE_NAME: &'static str = ".pdxexplorerignore";
+
+    // Files of version control systems and editors, and the folders the games write their output
+    // to when a documents folder is opened.
+    const DEFAULT_PATTERNS: &'static [&'static str] = &[
+        ".git/",
+        ".svn/",
+        ".hg/",
+        ".idea/",
+        ".vscode/",
+        "*.swp",
+        "*.swo",
+        "*~",
+        ".DS_Store",
+        "Thumbs.db",
+        "desktop.ini",
+        "/screenshots/",
+        "/logs/",
+        "/crashes/",
+    ];
+
+    // The default rules, the given patterns and the rules from the ignore file in the root, in
+    // this order.
+    pub fn for_root(root: &Path, patterns: &[String]) -> Result<Self> {
+        let mut rules = Self::default();
+
+        for pattern in Self::DEFAULT_PATTERNS {
+            rules.add(pattern);
+        }
+
+        for pattern in patterns {
+            rules.add(pattern);
+        }
+
+        // The root may be an archive, so the file is read through the VFS.
+        let path = root.join(Self::FILE_NAME);
+        let content = match vfs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => fail!("failed to read `{}`: {}", path.display(), error),
+        };
+
+        for line in content.lines() {
+            rules.add(line);
+        }
+
+        Ok(rules)
+    }
+
+    pub fn add(&mut self, line: &str) {
+        let line = line.trim_end();
+
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+
+        if line.is_empty() {
+            return;
+        }
+
+        self.rules.push(Rule {
+            pattern: Pattern::new(line),
+            negated,
+            dir_only,
+            anchored,
+        });
+    }
+
+    // The path must be relative to the root, with `/` as the separator.
+    pub fn is_ignored(&self, relative_path: &str, is_dir: bool) -> bool {
+        let name = relative_path
+            .rsplit_once('/')
+            .map_or(relative_path, |(_, name)| name);
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && rule
+                        .pattern
+                        .matches(if rule.anchored { relative_path } else { name })
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(lines: &[&str]) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+
+        for line in lines {
+            rules.add(line);
+        }
+
+        rules
+    }
+
+    #[test]
+    fn patterns_without_a_slash_match_names_at_any_depth() {
+        let rules = rules(&["*.swp", "# a comment", ""]);
+
+        assert!(rules.is_ignored("a.swp", false));
+        assert!(rules.is_ignored("common/b.swp", false));
+        assert!(!rules.is_ignored("common/b.txt", false));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_match_relative_paths() {
+        let rules = rules(&["/logs/", "gfx/**/*.psd"]);
+
+        assert!(rules.is_ignored("logs", true));
+        assert!(!rules.is_ignored("logs", false));
+        assert!(!rules.is_ignored("mod/logs", true));
+        assert!(rules.is_ignored("gfx/a.psd", false));
+        assert!(rules.is_ignored("gfx/interface/a.psd", false));
+        assert!(!rules.is_ignored("common/a.psd", false));
+    }
+
+    #[test]
+    fn the_last_matching_rule_wins() {
+        let rules = rules(&["*.txt", "!keep.txt"]);
+
+        assert!(rules.is_ignored("a.txt", false));
+        assert!(!rules.is_ignored("common/keep.txt", false));
+    }
+}
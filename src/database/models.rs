@@ -1,6 +1,9 @@
-// This file must be kept up to date with the init.sql and schema.rs files.
+// This file must be kept up to date with the migrations in sql/migrations and the schema.rs
+// file.
 
 use diesel::prelude::*;
+use std::borrow::Cow;
+use std::path::PathBuf;
 
 #[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq)]
 #[diesel(
@@ -18,6 +21,39 @@ pub struct NewLanguage<'a> {
     pub name: &'a str,
 }
 
+#[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq, serde::Serialize)]
+#[diesel(
+    table_name = super::schema::layer,
+    primary_key(id),
+    check_for_backend(diesel::sqlite::Sqlite),
+)]
+pub struct Layer {
+    pub id: i32,
+    pub name: String,
+    pub root_path: String,
+    // The root path if it isn't valid UTF-8; `root_path` holds a lossy form then.
+    #[serde(skip)]
+    pub raw_root_path: Option<Vec<u8>>,
+}
+
+impl Layer {
+    pub fn root_path(&self) -> PathBuf {
+        match &self.raw_root_path {
+            Some(raw_root_path) => super::dir::path_from_bytes(raw_root_path),
+            None => PathBuf::from(&self.root_path),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = super::schema::layer)]
+pub struct NewLayer<'a> {
+    pub id: i32,
+    pub name: &'a str,
+    pub root_path: Cow<'a, str>,
+    pub raw_root_path: Option<Vec<u8>>,
+}
+
 #[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq)]
 #[diesel(
     table_name = super::schema::content_type,
@@ -42,24 +78,31 @@ pub struct NewContentType<'a> {
     check_for_backend(diesel::sqlite::Sqlite),
 )]
 pub struct Directory {
-    pub id: i32,
+    pub id: i64,
     pub full_path: String,
     pub relative_path: String,
     pub dir_name: String,
     pub content_type: String,
+    pub layer: i32,
+    // The full path if it isn't valid UTF-8; the text columns hold a lossy form then.
+    pub raw_full_path: Option<Vec<u8>>,
 }
 
 #[derive(Insertable, AsChangeset)]
 #[diesel(table_name = super::schema::directory)]
 pub struct NewDirectory<'a> {
-    pub id: i32,
-    pub full_path: &'a str,
-    pub relative_path: &'a str,
-    pub dir_name: &'a str,
+    pub id: i64,
+    pub full_path: Cow<'a, str>,
+    pub relative_path: Cow<'a, str>,
+    pub dir_name: Cow<'a, str>,
     pub content_type: &'a str,
+    pub layer: i32,
+    pub raw_full_path: Option<Vec<u8>>,
 }
 
-#[derive(Queryable, Identifiable, Selectable, Associations, Debug, Clone, PartialEq)]
+#[derive(
+    Queryable, Identifiable, Selectable, Associations, Debug, Clone, PartialEq, serde::Serialize,
+)]
 #[diesel(
     table_name = super::schema::file,
     primary_key(id),
@@ -67,11 +110,29 @@ pub struct NewDirectory<'a> {
     check_for_backend(diesel::sqlite::Sqlite),
 )]
 pub struct File {
-    pub id: i32,
+    pub id: i64,
     pub full_path: String,
     pub relative_path: String,
     pub file_name: String,
     pub content_type: String,
+    pub layer: i32,
+    // The full path if it isn't valid UTF-8; the text columns hold a lossy form then.
+    #[serde(skip)]
+    pub raw_full_path: Option<Vec<u8>>,
+}
+
+impl File {
+    pub fn path(&self) -> PathBuf {
+        match &self.raw_full_path {
+            Some(raw_full_path) => super::dir::path_from_bytes(raw_full_path),
+            None => PathBuf::from(&self.full_path),
+        }
+    }
+
+    // Whether the path is stored as text only in a lossy form.
+    pub fn has_raw_path(&self) -> bool {
+        self.raw_full_path.is_some()
+    }
 }
 
 #[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq)]
@@ -81,24 +142,31 @@ pub struct File {
     check_for_backend(diesel::sqlite::Sqlite),
 )]
 pub struct FileIdPath {
-    pub id: i32,
+    pub id: i64,
     pub full_path: String,
+    pub raw_full_path: Option<Vec<u8>>,
 }
 
 #[derive(Insertable, AsChangeset)]
 #[diesel(table_name = super::schema::file)]
 pub struct NewFile<'a> {
-    pub id: i32,
-    pub full_path: &'a str,
-    pub relative_path: &'a str,
-    pub file_name: &'a str,
+    pub id: i64,
+    pub full_path: Cow<'a, str>,
+    pub relative_path: Cow<'a, str>,
+    pub file_name: Cow<'a, str>,
     pub content_type: &'a str,
+    pub layer: i32,
+    pub raw_full_path: Option<Vec<u8>>,
+    pub size: Option<i64>,
+    pub modified: Option<i64>,
 }
 
-#[derive(Queryable, Identifiable, Selectable, Associations, Debug, Clone, PartialEq)]
+#[derive(
+    Queryable, Identifiable, Selectable, Associations, Debug, Clone, PartialEq, serde::Serialize,
+)]
 #[diesel(
     table_name = super::schema::localization_key,
-    primary_key(key),
+    primary_key(key, language),
     belongs_to(File, foreign_key = file_id),
     belongs_to(Language, foreign_key = language),
     check_for_backend(diesel::sqlite::Sqlite),
@@ -106,11 +174,12 @@ pub struct NewFile<'a> {
 pub struct LocalizationKey {
     pub key: String,
     pub value: String,
-    pub file_id: i32,
+    pub revision: Option<i32>,
+    pub file_id: i64,
     pub language: String,
 }
 
-#[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq)]
+#[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq, serde::Serialize)]
 #[diesel(
     table_name = super::schema::localization_key,
     primary_key(key),
@@ -122,10 +191,57 @@ pub struct LocalizationKeyKeyValue {
 }
 
 #[derive(Insertable, AsChangeset)]
-#[diesel(table_name = super::schema::localization_key)]
+#[diesel(
+    table_name = super::schema::localization_key,
+    treat_none_as_null = true,
+    treat_none_as_default_value = false,
+)]
 pub struct NewLocalizationKey<'a> {
     pub key: &'a str,
     pub value: &'a str,
-    pub file_id: i32,
+    pub revision: Option<i32>,
+    pub file_id: i64,
     pub language: &'a str,
 }
+
+#[derive(
+    Queryable, Identifiable, Selectable, Associations, Debug, Clone, PartialEq, serde::Serialize,
+)]
+#[diesel(
+    table_name = super::schema::parse_error,
+    primary_key(id),
+    belongs_to(File, foreign_key = file_id),
+    check_for_backend(diesel::sqlite::Sqlite),
+)]
+pub struct ParseError {
+    pub id: i32,
+    pub file_id: i64,
+    pub message: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = super::schema::parse_error)]
+pub struct NewParseError<'a> {
+    pub file_id: i64,
+    pub message: &'a str,
+}
+
+#[derive(Queryable, Identifiable, Selectable, Debug, Clone, PartialEq)]
+#[diesel(
+    table_name = super::schema::walk_options,
+    primary_key(id),
+    check_for_backend(diesel::sqlite::Sqlite),
+)]
+pub struct WalkOptions {
+    pub id: i32,
+    pub follow_symlinks: bool,
+    pub ignore_patterns: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = super::schema::walk_options)]
+pub struct NewWalkOptions<'a> {
+    pub id: i32,
+    pub follow_symlinks: bool,
+    pub ignore_patterns: &'a str,
+}
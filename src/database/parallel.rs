@@ -0,0 +1,132 @@
+use crate::core::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+// Maps the items on worker threads and hands the results to `consume` on the calling thread in the
+// order of the items, so that the outcome is the same as if the items were processed one by one.
+// Stops at the first error returned by `consume`.
+pub fn map_ordered<T, R>(
+    items: &[T],
+    map: impl Fn(&T) -> R + Sync,
+    mut consume: impl FnMut(R) -> Result<()>,
+) -> Result<()>
+where
+    T: Sync,
+    R: Send,
+{
+    let workers = thread::available_parallelism()
+        .map_or(1, |workers| workers.get())
+        .min(items.len());
+
+    if workers <= 1 {
+        for item in items {
+            consume(map(item))?;
+        }
+
+        return Ok(());
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::sync_channel(workers * 4);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            let map = &map;
+
+            scope.spawn(move || {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(idx) else {
+                        break;
+                    };
+
+                    // The receiver is only gone if consuming failed.
+                    if sender.send((idx, map(item))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        drop(sender);
+
+        consume_in_order(receiver, &mut consume)
+    })
+}
+
+// The results arrive in the order the workers finish them, so the ones which come early are held
+// back until the results before them are consumed.
+fn consume_in_order<R>(
+    receiver: mpsc::Receiver<(usize, R)>,
+    consume: &mut impl FnMut(R) -> Result<()>,
+) -> Result<()> {
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+
+    for (idx, result) in receiver {
+        pending.insert(idx, result);
+
+        while let Some(result) = pending.remove(&next) {
+            consume(result)?;
+            next += 1;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_are_consumed_in_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        let mut results = Vec::new();
+
+        map_ordered(
+            &items,
+            |item| {
+                // Makes the workers finish out of order.
+                thread::sleep(std::time::Duration::from_micros(item % 7 * 50));
+                item * 2
+            },
+            |result| {
+                results.push(result);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn consuming_stops_at_the_first_error() {
+        let items: Vec<u64> = (0..1000).collect();
+        let mut consumed = 0;
+
+        let result = map_ordered(
+            &items,
+            |item| *item,
+            |item| {
+                if item == 10 {
+                    fail!("failed at {}", item);
+                }
+
+                consumed += 1;
+                Ok(())
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(consumed, 10);
+    }
+}
@@ -1,16 +1,24 @@
 use crate::core::*;
 use crate::database::Language;
-use std::fs;
+use crate::database::vfs;
 use std::path::Path;
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizationEntry {
+    pub key: String,
+    pub revision: Option<i32>,
+    pub value: String,
+}
+
 #[derive(Debug, Default)]
 pub struct Parser {}
 
 impl Parser {
+    const BYTE_ORDER_MARK: &'static str = "\u{feff}";
     const LOCALIZATION_FILE_COMMENT_DELIMITER: char = '#';
 
     // TODO: This function shouldn't take in a path (it should take in file contents or a reader).
-    pub fn parse_localization_file(path: &Path) -> Result<(Language, Vec<(String, String)>)> {
+    pub fn parse_localization_file(path: &Path) -> Result<(Language, Vec<LocalizationEntry>)> {
         // This is how a localization file looks like:
         //
         // l_english:
@@ -19,12 +27,18 @@ impl Parser {
         //
         // A localization file must be encoded in the UTF-8 with BOM format.
 
-        let file_content = fs::read_to_string(path)?;
+        let file_content = vfs::read_to_string(path)?;
+
+        if file_content.is_empty() {
+            return Ok(Default::default());
+        }
 
-        // Strip BOM (first three bytes).
-        // TODO: Should do this in a better, cleaner way.
-        let Some(file_content) = file_content.get(3..) else {
-            return Ok(Default::default()); // Empty file, no BOM.
+        let Some(file_content) = Self::strip_byte_order_mark(&file_content) else {
+            return Err(Error::with_file_reference(
+                path,
+                1,
+                "the file doesn't start with a UTF-8 byte order mark",
+            ));
         };
 
         // Empty lines and lines which contain only comments are filtered out.
@@ -33,12 +47,7 @@ impl Parser {
                 .lines()
                 .enumerate()
                 .filter_map(|(line_number, line)| {
-                    // Strip the comments.
-                    let line = match line.split_once(Self::LOCALIZATION_FILE_COMMENT_DELIMITER) {
-                        Some((before_comment, _comment)) => before_comment,
-                        None => line,
-                    }
-                    .trim();
+                    let line = Self::strip_comment(line).trim();
 
                     if !line.is_empty() {
                         Some((line_number, line))
@@ -81,11 +90,32 @@ impl Parser {
         Ok((language, keys))
     }
 
+    // Returns the content after the byte order mark, or None if it doesn't start with one.
+    pub(crate) fn strip_byte_order_mark(content: &str) -> Option<&str> {
+        content.strip_prefix(Self::BYTE_ORDER_MARK)
+    }
+
+    // Comments start with a # outside a quoted value, so that values like "#bold text#!" are
+    // kept whole.
+    pub(crate) fn strip_comment(line: &str) -> &str {
+        let mut in_quotes = false;
+
+        for (idx, c) in line.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                Self::LOCALIZATION_FILE_COMMENT_DELIMITER if !in_quotes => return &line[..idx],
+                _ => {}
+            }
+        }
+
+        line
+    }
+
     fn parse_localization_file_line(
         line_number: usize,
         line: &str,
         path: &Path,
-    ) -> Result<(String, String)> {
+    ) -> Result<LocalizationEntry> {
         let make_error = || {
             Error::with_file_reference(
                 path,
@@ -106,9 +136,14 @@ impl Parser {
             return Err(make_error());
         };
 
-        // Skip the revision number and whitespace at the beginning.
-        let after_colon =
-            after_colon.trim_start_matches(|c: char| c.is_ascii_digit() || c.is_whitespace());
+        // Extract the revision number, which may be preceded by whitespace, and skip the
+        // whitespace after it.
+        let after_colon = after_colon.trim_start();
+        let revision_end = after_colon
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after_colon.len());
+        let revision = after_colon[..revision_end].parse().ok();
+        let after_colon = after_colon[revision_end..].trim_start();
 
         // Make sure the first and the last character are quotes.
         let mut after_colon_chars = after_colon.chars();
@@ -120,8 +155,67 @@ impl Parser {
             return Err(make_error());
         }
 
-        let key = before_colon.to_owned();
-        let value: String = after_colon_chars.collect();
-        Ok((key, value))
+        Ok(LocalizationEntry {
+            key: before_colon.to_owned(),
+            revision,
+            value: after_colon_chars.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_start_outside_quotes() {
+        assert_eq!(Parser::strip_comment(" a:0 \"A\" # B"), " a:0 \"A\" ");
+        assert_eq!(
+            Parser::strip_comment(" a:0 \"#bold A#!\" # B"),
+            " a:0 \"#bold A#!\" "
+        );
+        assert_eq!(Parser::strip_comment("# l_english:"), "");
+        assert_eq!(Parser::strip_comment(" a:0 \"A\""), " a:0 \"A\"");
+    }
+
+    #[test]
+    fn byte_order_mark_is_required() {
+        assert_eq!(
+            Parser::strip_byte_order_mark("\u{feff}l_english:"),
+            Some("l_english:")
+        );
+        assert_eq!(Parser::strip_byte_order_mark("l_english:"), None);
+    }
+
+    fn parse_line(line: &str) -> Result<LocalizationEntry> {
+        Parser::parse_localization_file_line(0, line, Path::new("a_l_english.yml"))
+    }
+
+    fn entry(key: &str, revision: Option<i32>, value: &str) -> LocalizationEntry {
+        LocalizationEntry {
+            key: key.to_owned(),
+            revision,
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn lines_are_parsed_with_and_without_revisions() {
+        assert_eq!(
+            parse_line("key:0 \"A\"").unwrap(),
+            entry("key", Some(0), "A")
+        );
+        assert_eq!(
+            parse_line("key: 0 \"A\"").unwrap(),
+            entry("key", Some(0), "A")
+        );
+        assert_eq!(
+            parse_line("key:12\t\"A\"").unwrap(),
+            entry("key", Some(12), "A")
+        );
+        assert_eq!(parse_line("key: \"A\"").unwrap(), entry("key", None, "A"));
+        assert_eq!(parse_line("key:\"\"").unwrap(), entry("key", None, ""));
+        assert!(parse_line("key: 0").is_err());
+        assert!(parse_line("key \"A\"").is_err());
     }
 }
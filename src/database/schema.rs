@@ -1,4 +1,5 @@
-// This file must be kept up to date with the init.sql and models.rs files.
+// This file must be kept up to date with the migrations in sql/migrations and the models.rs
+// file. The tests check it against the schema the migrations create.
 
 diesel::table! {
     language (name) {
@@ -13,34 +14,52 @@ diesel::table! {
 }
 
 diesel::table! {
-    directory (id) {
+    layer (id) {
         id -> Integer,
+        name -> Text,
+        root_path -> Text,
+        raw_root_path -> Nullable<Binary>,
+    }
+}
+
+diesel::table! {
+    directory (id) {
+        id -> BigInt,
         full_path -> Text,
         relative_path -> Text,
         dir_name -> Text,
         content_type -> Text,
+        layer -> Integer,
+        raw_full_path -> Nullable<Binary>,
     }
 }
 
 diesel::joinable!(directory -> content_type (content_type));
+diesel::joinable!(directory -> layer (layer));
 
 diesel::table! {
     file (id) {
-        id -> Integer,
+        id -> BigInt,
         full_path -> Text,
         relative_path -> Text,
         file_name -> Text,
         content_type -> Text,
+        layer -> Integer,
+        raw_full_path -> Nullable<Binary>,
+        size -> Nullable<BigInt>,
+        modified -> Nullable<BigInt>,
     }
 }
 
 diesel::joinable!(file -> content_type (content_type));
+diesel::joinable!(file -> layer (layer));
 
 diesel::table! {
-    localization_key (key) {
+    localization_key (key, language) {
         key -> Text,
         value -> Text,
-        file_id -> Integer,
+        revision -> Nullable<Integer>,
+        file_id -> BigInt,
         language -> Text,
     }
 }
@@ -48,10 +67,31 @@ diesel::table! {
 diesel::joinable!(localization_key -> file (file_id));
 diesel::joinable!(localization_key -> language (language));
 
+diesel::table! {
+    parse_error (id) {
+        id -> Integer,
+        file_id -> BigInt,
+        message -> Text,
+    }
+}
+
+diesel::joinable!(parse_error -> file (file_id));
+
+diesel::table! {
+    walk_options (id) {
+        id -> Integer,
+        follow_symlinks -> Bool,
+        ignore_patterns -> Text,
+    }
+}
+
 diesel::allow_tables_to_appear_in_same_query!(
     language,
+    layer,
     content_type,
     directory,
     file,
-    localization_key
+    localization_key,
+    parse_error,
+    walk_options
 );
@@ -0,0 +1,11 @@
+-- Deletes everything which was indexed, keeping the schema. The tables are cleared in the reverse
+-- order of their dependencies.
+
+DELETE FROM parse_error;
+DELETE FROM localization_key;
+DELETE FROM file;
+DELETE FROM directory;
+DELETE FROM layer;
+DELETE FROM content_type;
+DELETE FROM language;
+DELETE FROM walk_options;
@@ -0,0 +1,72 @@
+-- The initial schema. Databases from before the schema was versioned have the tables without
+-- a version, so they're dropped first.
+
+DROP TABLE IF EXISTS language;
+DROP TABLE IF EXISTS layer;
+DROP TABLE IF EXISTS content_type;
+DROP TABLE IF EXISTS directory;
+DROP TABLE IF EXISTS file;
+DROP TABLE IF EXISTS localization_key;
+DROP TABLE IF EXISTS parse_error;
+
+CREATE TABLE IF NOT EXISTS language
+(
+    name TEXT PRIMARY KEY NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS content_type
+(
+    name TEXT PRIMARY KEY NOT NULL
+);
+
+-- The game and the mods on top of it, in the load order.
+CREATE TABLE IF NOT EXISTS layer
+(
+    id        INTEGER PRIMARY KEY NOT NULL,
+    name      TEXT                NOT NULL,
+    root_path TEXT                NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS directory
+(
+    id            INTEGER PRIMARY KEY NOT NULL,
+    full_path     TEXT                NOT NULL,
+    relative_path TEXT                NOT NULL,
+    dir_name      TEXT                NOT NULL,
+    content_type  TEXT                NOT NULL,
+    layer         INTEGER             NOT NULL,
+    FOREIGN KEY (content_type) REFERENCES content_type (name) ON DELETE CASCADE,
+    FOREIGN KEY (layer) REFERENCES layer (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS file
+(
+    id            INTEGER PRIMARY KEY NOT NULL,
+    full_path     TEXT                NOT NULL,
+    relative_path TEXT                NOT NULL,
+    file_name     TEXT                NOT NULL,
+    content_type  TEXT                NOT NULL,
+    layer         INTEGER             NOT NULL,
+    FOREIGN KEY (content_type) REFERENCES content_type (name) ON DELETE CASCADE,
+    FOREIGN KEY (layer) REFERENCES layer (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS localization_key
+(
+    key      TEXT    NOT NULL,
+    value    TEXT    NOT NULL,
+    revision INTEGER,
+    file_id  INTEGER NOT NULL,
+    language TEXT    NOT NULL,
+    PRIMARY KEY (key, language),
+    FOREIGN KEY (file_id) REFERENCES file (id) ON DELETE CASCADE,
+    FOREIGN KEY (language) REFERENCES language (name) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS parse_error
+(
+    id      INTEGER PRIMARY KEY NOT NULL,
+    file_id INTEGER             NOT NULL,
+    message TEXT                NOT NULL,
+    FOREIGN KEY (file_id) REFERENCES file (id) ON DELETE CASCADE
+)
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- How the directories of the layers were walked, so that an opened database is checked against
+-- the same files. There's at most one row; databases which were indexed before the walk could be
+-- configured don't have one and were walked with the defaults.
+
+CREATE TABLE IF NOT EXISTS walk_options
+(
+    id              INTEGER PRIMARY KEY NOT NULL,
+    follow_symlinks BOOLEAN             NOT NULL,
+    -- One pattern per line.
+    ignore_patterns TEXT                NOT NULL
+);
@@ -0,0 +1,5 @@
+-- Paths which aren't valid UTF-8 are stored losslessly as bytes next to their text columns, which
+-- hold a lossy form for display. The bytes are NULL for all other paths.
+
+ALTER TABLE directory ADD COLUMN raw_full_path BLOB;
+ALTER TABLE file ADD COLUMN raw_full_path BLOB;
@@ -0,0 +1,6 @@
+-- The size and the modification time (in nanoseconds since the Unix epoch) of the files when they
+-- were indexed, so that an opened database notices the files which changed since. Files indexed
+-- before they were recorded have NULL and count as changed.
+
+ALTER TABLE file ADD COLUMN size INTEGER;
+ALTER TABLE file ADD COLUMN modified INTEGER;
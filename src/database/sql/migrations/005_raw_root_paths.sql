@@ -0,0 +1,4 @@
+-- Root paths which aren't valid UTF-8 are stored losslessly as bytes, like the paths of the
+-- directories and files. The bytes are NULL for all other roots.
+
+ALTER TABLE layer ADD COLUMN raw_root_path BLOB;
@@ -0,0 +1,389 @@
+use crate::core::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+// The file systems the dir trees are built from. A layer is either a directory on disk or a zip
+// archive, which is read without extracting it.
+//
+// The entries of an archive are addressed by the path of the archive joined with their path
+// within it, e.g. `mods/my_mod.zip/common/on_actions/00_on_actions.txt`. Such paths can be stored
+// and opened like the paths on disk, as long as they're opened through this module.
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    // Lists the entries of a directory. An entry which can't be read doesn't fail the others.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<Entry>>>;
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>>;
+}
+
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    // Only directories on disk have symbolic links; whether they're followed is up to the walk.
+    Symlink,
+}
+
+// A directory on disk.
+#[derive(Debug)]
+pub struct OsFs;
+
+impl FileSystem for OsFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<Entry>>> {
+        Ok(fs::read_dir(path)?
+            .map(|entry| {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                let kind = if file_type.is_symlink() {
+                    EntryKind::Symlink
+                } else if file_type.is_dir() {
+                    EntryKind::Dir
+                } else {
+                    EntryKind::File
+                };
+
+                Ok(Entry {
+                    path: entry.path(),
+                    kind,
+                })
+            })
+            .collect())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+}
+
+// A zip archive. Mods are often packed with their directory at the top of the archive, so if
+// everything is in one directory, that directory is treated as the root.
+pub struct ZipFs {
+    archive_path: PathBuf,
+    // The directory within the archive the paths are relative to, with a trailing `/`, if any.
+    prefix: String,
+    // The entries of the directories by their path relative to the root; the root is "".
+    dirs: HashMap<PathBuf, BTreeMap<PathBuf, EntryKind>>,
+    // The indices of the files in the archive by their path relative to the root.
+    files: HashMap<PathBuf, usize>,
+    archive: Mutex<zip::ZipArchive<BufReader<fs::File>>>,
+}
+
+impl fmt::Debug for ZipFs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipFs")
+            .field("archive_path", &self.archive_path)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ZipFs {
+    pub fn new(archive_path: &Path) -> Result<Self> {
+        let file = fs::File::open(archive_path)?;
+        let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|error| {
+            error!(
+                "failed to read archive `{}`: {}",
+                archive_path.display(),
+                error
+            )
+        })?;
+
+        // The names are validated by the archive, so that an entry can't point outside of it.
+        let mut names = Vec::new();
+
+        for idx in 0..archive.len() {
+            let Ok(entry) = archive.by_index_raw(idx) else {
+                continue;
+            };
+            let Some(name) = entry.enclosed_name() else {
+                continue;
+            };
+            let name = name
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            if !name.is_empty() {
+                names.push((name, entry.is_dir(), idx));
+            }
+        }
+
+        let prefix = Self::common_dir(&names);
+        let mut dirs: HashMap<PathBuf, BTreeMap<PathBuf, EntryKind>> = HashMap::new();
+        let mut files = HashMap::new();
+
+        dirs.insert(PathBuf::new(), BTreeMap::new());
+
+        for (name, is_dir, idx) in &names {
+            let Some(name) = name.strip_prefix(&prefix) else {
+                continue;
+            };
+
+            if name.is_empty() {
+                continue;
+            }
+
+            let path = PathBuf::from(name);
+
+            if *is_dir {
+                dirs.entry(path.clone()).or_default();
+            } else {
+                files.insert(path.clone(), *idx);
+            }
+
+            // Archives don't always have entries for the directories, so they're added for the
+            // ancestors of every entry.
+            let mut child = (
+                path,
+                if *is_dir {
+                    EntryKind::Dir
+                } else {
+                    EntryKind::File
+                },
+            );
+
+            while let Some(parent) = child.0.parent() {
+                let parent = parent.to_owned();
+                let children = dirs.entry(parent.clone()).or_default();
+                let is_new = children.insert(child.0, child.1).is_none();
+
+                if !is_new || parent.as_os_str().is_empty() {
+                    break;
+                }
+
+                child = (parent, EntryKind::Dir);
+            }
+        }
+
+        Ok(Self {
+            archive_path: archive_path.to_owned(),
+            prefix,
+            dirs,
+            files,
+            archive: Mutex::new(archive),
+        })
+    }
+
+    // The directory all the entries are in, e.g. `my_mod/`, or "" if there's none.
+    fn common_dir(names: &[(String, bool, usize)]) -> String {
+        let mut top_level = names
+            .iter()
+            .map(|(name, is_dir, _)| match name.split_once('/') {
+                Some((top, _)) => (top, true),
+                None => (name.as_str(), *is_dir),
+            });
+
+        let Some((first, true)) = top_level.next() else {
+            return String::new();
+        };
+
+        if top_level.all(|(top, is_dir)| is_dir && top == first) {
+            format!("{}/", first)
+        } else {
+            String::new()
+        }
+    }
+
+    fn relative_path<'a>(&self, path: &'a Path) -> io::Result<&'a Path> {
+        path.strip_prefix(&self.archive_path).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "`{}` is not in archive `{}`",
+                    path.display(),
+                    self.archive_path.display()
+                ),
+            )
+        })
+    }
+}
+
+impl FileSystem for ZipFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<Entry>>> {
+        let relative_path = self.relative_path(path)?;
+        let children = self
+            .dirs
+            .get(relative_path)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+
+        Ok(children
+            .iter()
+            .map(|(child, kind)| {
+                Ok(Entry {
+                    path: self.archive_path.join(child),
+                    kind: *kind,
+                })
+            })
+            .collect())
+    }
+
+    // The file is decompressed into memory, since the entries of an archive can't be seeked.
+    fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+        let relative_path = self.relative_path(path)?;
+        let idx = *self
+            .files
+            .get(relative_path)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+
+        let mut archive = self
+            .archive
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let mut entry = archive.by_index(idx).map_err(io::Error::other)?;
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes)?;
+
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+        && path.is_file()
+}
+
+// The file system of a layer with the given root, which is a directory or an archive.
+pub fn mount(root: &Path) -> Result<Arc<dyn FileSystem>> {
+    if root.is_dir() {
+        return Ok(Arc::new(OsFs));
+    }
+
+    if is_archive(root) {
+        return Ok(open_archive(root)?);
+    }
+
+    fail!(
+        "root path `{}` doesn't point to a directory or a zip archive",
+        root.display()
+    );
+}
+
+// The archive the path points into, if any.
+pub fn archive_of(path: &Path) -> Option<&Path> {
+    path.ancestors().skip(1).find(|path| is_archive(path))
+}
+
+// Opens a file on disk or in an archive.
+pub fn open(path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+    if let Some(archive_path) = archive_of(path) {
+        let archive = open_archive(archive_path).map_err(io::Error::other)?;
+
+        return archive.open(path);
+    }
+
+    OsFs.open(path)
+}
+
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open(path)?.read_to_end(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    String::from_utf8(read(path)?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+// The size and the modification time of a file on disk or in an archive. The entries of an archive
+// have the ones of the archive, so that they all count as changed when it changes.
+pub fn metadata(path: &Path) -> io::Result<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(archive_of(path).unwrap_or(path))?;
+
+    Ok((metadata.len(), metadata.modified().ok()))
+}
+
+// Reading the index of an archive is slow, so the opened archives are kept until they change.
+fn open_archive(archive_path: &Path) -> Result<Arc<ZipFs>> {
+    type Archives = HashMap<PathBuf, (Option<SystemTime>, u64, Arc<ZipFs>)>;
+
+    static ARCHIVES: OnceLock<Mutex<Archives>> = OnceLock::new();
+
+    let metadata = fs::metadata(archive_path)?;
+    let version = (metadata.modified().ok(), metadata.len());
+    let mut archives = ARCHIVES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+
+    if let Some((modified, len, archive)) = archives.get(archive_path)
+        && (*modified, *len) == version
+    {
+        return Ok(archive.clone());
+    }
+
+    let archive = Arc::new(ZipFs::new(archive_path)?);
+    archives.insert(
+        archive_path.to_owned(),
+        (version.0, version.1, archive.clone()),
+    );
+
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::io::Write;
+
+    #[test]
+    fn archives_are_read_like_directories() {
+        let temp_dir = TempDir::new("vfs");
+        let archive_path = temp_dir.path().join("my_mod.zip");
+
+        // Everything is in the `my_mod` directory, which becomes the root.
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer
+            .start_file("my_mod/localization/a_l_english.yml", options)
+            .unwrap();
+        writer.write_all(b"l_english:").unwrap();
+        writer.start_file("my_mod/descriptor.mod", options).unwrap();
+        writer.finish().unwrap();
+
+        let archive = mount(&archive_path).unwrap();
+        let entries: Vec<_> = archive
+            .read_dir(&archive_path)
+            .unwrap()
+            .into_iter()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path, entry.kind)
+            })
+            .collect();
+
+        assert_eq!(
+            entries,
+            [
+                (archive_path.join("descriptor.mod"), EntryKind::File),
+                (archive_path.join("localization"), EntryKind::Dir),
+            ]
+        );
+        assert_eq!(
+            read_to_string(&archive_path.join("localization/a_l_english.yml")).unwrap(),
+            "l_english:"
+        );
+        assert_eq!(
+            read(&archive_path.join("missing.txt")).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
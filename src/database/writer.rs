@@ -0,0 +1,215 @@
+use crate::core::*;
+use crate::database::Language;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct Writer {}
+
+impl Writer {
+    const BYTE_ORDER_MARK: &'static str = "\u{feff}";
+
+    // Creates a new localization file with the BOM and the language specifier header. Fails if the
+    // file already exists, so that it's never overwritten by accident.
+    pub fn create_localization_file(
+        path: &Path,
+        language: Language,
+        keys: &[(&str, &str)],
+    ) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut content = format!("{}{}:\n", Self::BYTE_ORDER_MARK, language.specifier());
+
+        for (key, value) in keys {
+            content.push_str(&Self::format_localization_key_line(key, value));
+        }
+
+        let mut file = fs::File::create_new(path)
+            .map_err(|error| error!("failed to create `{}`: {}", path.display(), error))?;
+        file.write_all(content.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn append_localization_keys(path: &Path, keys: &[(&str, &str)]) -> Result<()> {
+        let file_content = fs::read_to_string(path)?;
+        let line_ending = if file_content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let mut content = String::new();
+
+        // Make sure the new keys don't end up on the last line of the existing content.
+        if !file_content.is_empty() && !file_content.ends_with('\n') {
+            content.push_str(line_ending);
+        }
+
+        for (key, value) in keys {
+            let line = Self::format_localization_key_line(key, value);
+            content.push_str(line.trim_end_matches('\n'));
+            content.push_str(line_ending);
+        }
+
+        let mut file = fs::OpenOptions::new().append(true).open(path)?;
+        file.write_all(content.as_bytes())?;
+
+        Ok(())
+    }
+
+    // Changes the values of the given keys (or removes the keys which map to None) in place. Only
+    // the quoted values are replaced; the indentation, revision numbers, comments, line endings and
+    // the BOM are left as they are.
+    pub fn rewrite_localization_keys(
+        path: &Path,
+        changes: &HashMap<&str, Option<&str>>,
+    ) -> Result<()> {
+        let file_content = fs::read_to_string(path)?;
+        let mut content = String::with_capacity(file_content.len());
+
+        for line in file_content.split_inclusive('\n') {
+            let key = line
+                .trim_start_matches(Self::BYTE_ORDER_MARK)
+                .split_once(':')
+                .map(|(key, _)| key.trim());
+
+            match key.and_then(|key| changes.get(key)) {
+                None => content.push_str(line),
+                Some(None) => {
+                    // Keep the BOM if the removed key happened to be on the first line.
+                    if line.starts_with(Self::BYTE_ORDER_MARK) {
+                        content.push_str(Self::BYTE_ORDER_MARK);
+                    }
+                }
+                Some(Some(value)) => content.push_str(&Self::replace_value(line, value)),
+            }
+        }
+
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    // The file name has to end with _l_<language> or the game ignores the file.
+    pub fn localization_file_name(name: &str, language: Language) -> String {
+        format!("{}_{}.yml", name, language.specifier())
+    }
+
+    fn format_localization_key_line(key: &str, value: &str) -> String {
+        format!(" {}:0 \"{}\"\n", key, Self::escape_value(value))
+    }
+
+    // Values have to fit on a single line; line breaks are written as escape sequences.
+    fn escape_value(value: &str) -> String {
+        value.replace("\r\n", "\\n").replace('\n', "\\n")
+    }
+
+    // Replaces the text between the first quote after the colon and the last quote before the
+    // comment (if there is one).
+    fn replace_value(line: &str, value: &str) -> String {
+        let Some(colon_idx) = line.find(':') else {
+            return line.to_owned();
+        };
+
+        let mut in_quotes = false;
+        let mut value_start = None;
+        let mut value_end = None;
+
+        for (idx, c) in line[colon_idx..].char_indices() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    value_start.get_or_insert(colon_idx + idx + 1);
+                    value_end = Some(colon_idx + idx);
+                }
+                '#' if !in_quotes => break,
+                _ => {}
+            }
+        }
+
+        match (value_start, value_end) {
+            (Some(start), Some(end)) if start <= end => format!(
+                "{}{}{}",
+                &line[..start],
+                Self::escape_value(value),
+                &line[end..]
+            ),
+            _ => line.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn localization_files_are_created_and_appended_to() {
+        let temp_dir = TempDir::new("writer");
+        let path = temp_dir
+            .path()
+            .join(Writer::localization_file_name("new", Language::German));
+        assert!(path.ends_with("new_l_german.yml"));
+
+        Writer::create_localization_file(&path, Language::German, &[("a", "line\nbreak")]).unwrap();
+        assert!(Writer::create_localization_file(&path, Language::German, &[]).is_err());
+
+        Writer::append_localization_keys(&path, &[("b", "B")]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}l_german:\n a:0 \"line\\nbreak\"\n b:0 \"B\"\n"
+        );
+    }
+
+    #[test]
+    fn appended_keys_keep_line_endings() {
+        let temp_dir = TempDir::new("writer");
+        let path = temp_dir.path().join("crlf_l_english.yml");
+        fs::write(&path, "\u{feff}l_english:\r\n a:0 \"A\"").unwrap();
+
+        Writer::append_localization_keys(&path, &[("b", "B")]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}l_english:\r\n a:0 \"A\"\r\n b:0 \"B\"\r\n"
+        );
+    }
+
+    #[test]
+    fn rewriting_keeps_byte_order_mark_and_line_endings() {
+        let temp_dir = TempDir::new("writer");
+        let path = temp_dir.path().join("rewrite_l_english.yml");
+        fs::write(
+            &path,
+            "\u{feff}l_english:\r\n a:0 \"A\" # Comment\r\n  b:1 \"B\"\r\n c:0 \"C\"",
+        )
+        .unwrap();
+
+        let changes = HashMap::from([("a", Some("New\nline")), ("b", None), ("c", Some("\"C\""))]);
+        Writer::rewrite_localization_keys(&path, &changes).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}l_english:\r\n a:0 \"New\\nline\" # Comment\r\n c:0 \"\"C\"\""
+        );
+    }
+
+    #[test]
+    fn values_are_replaced_between_the_outer_quotes() {
+        assert_eq!(
+            Writer::replace_value(" a:0 \"say \"hi\"\" # \"quoted\" comment\r\n", "bye"),
+            " a:0 \"bye\" # \"quoted\" comment\r\n"
+        );
+        assert_eq!(
+            Writer::replace_value(" a: \"#bold A#!\"\n", "B"),
+            " a: \"B\"\n"
+        );
+        // Lines without a value are left alone.
+        assert_eq!(Writer::replace_value(" a:0\n", "B"), " a:0\n");
+    }
+}
@@ -0,0 +1,353 @@
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::script::{self, Block, Location, ScriptFile, Value};
+use std::collections::HashMap;
+
+// The decisions and the missions of the game and the mods:
+//
+// * the decisions in `decisions` (EU4, HOI4) or `common/decisions` (CK3, HOI4), either at the top
+//   level or in groups like `country_decisions = { ... }` or the categories of HOI4
+// * the missions in `missions` (EU4), in series like `my_series = { potential = { ... } ... }`
+//
+// An entry replaces the entry with the same name which was read before it, so the entries of a
+// mod override those of the game.
+#[derive(Debug, Default)]
+pub struct DecisionIndex {
+    entries: Vec<Entry>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    Decision,
+    Mission,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub name: String,
+    // The block the entry is in, e.g. `country_decisions` or the mission series.
+    pub group: Option<String>,
+    pub location: Location,
+    // The definitions which this one replaces, from the files read before it or the replaced
+    // files of the layers below.
+    pub overridden: Vec<Location>,
+    pub block: Block,
+}
+
+impl EntryKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Decision => "decision",
+            Self::Mission => "mission",
+        }
+    }
+}
+
+impl Entry {
+    // The blocks of the entry which are shown, in this order.
+    const SECTIONS: &'static [&'static str] = &[
+        "potential",
+        "is_shown",
+        "allow",
+        "is_valid",
+        "trigger",
+        "effect",
+        "complete_effect",
+        "ai_will_do",
+    ];
+
+    // The keys the name could be localized with, in the order they're looked up. CK3 names the
+    // keys explicitly; EU4 uses `<name>_title`.
+    pub fn title_keys(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self
+            .block
+            .get_scalar("title")
+            .map(str::to_owned)
+            .into_iter()
+            .collect();
+        keys.push(format!("{}_title", self.name));
+        keys.push(self.name.clone());
+        keys
+    }
+
+    pub fn desc_keys(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self
+            .block
+            .get_scalar("desc")
+            .map(str::to_owned)
+            .into_iter()
+            .collect();
+        keys.push(format!("{}_desc", self.name));
+        keys
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = (&'static str, &Block)> {
+        Self::SECTIONS
+            .iter()
+            .filter_map(|&key| Some((key, self.block.get_block(key)?)))
+    }
+}
+
+impl DecisionIndex {
+    // The older games keep the decisions in the top-level folder.
+    const DECISIONS_FOLDERS: &'static [&'static str] = &["decisions", "common/decisions"];
+    const MISSIONS_FOLDER: &'static str = "missions";
+    // HOI4 keeps the categories of the decisions next to them.
+    const CATEGORIES_FOLDER: &'static str = "common/decisions/categories";
+
+    // The keys only decisions have; a block without any is a group of decisions.
+    const DECISION_KEYS: &'static [&'static str] = &[
+        "potential",
+        "allow",
+        "is_shown",
+        "is_valid",
+        "visible",
+        "available",
+        "effect",
+        "complete_effect",
+    ];
+    const MISSION_KEYS: &'static [&'static str] =
+        &["icon", "trigger", "effect", "required_missions", "position"];
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        let mut index = Self::default();
+        // The entries of the replaced files, which are matched up with the entries which replace
+        // them once everything is read.
+        let mut replaced = Vec::new();
+        let mut entry_indices = HashMap::new();
+
+        for (folders, kind) in [
+            (Self::DECISIONS_FOLDERS, EntryKind::Decision),
+            (&[Self::MISSIONS_FOLDER][..], EntryKind::Mission),
+        ] {
+            for folder in folders {
+                let (files, warnings) = script::load_folder_with_replaced(dir_trees, folder)?;
+                index.warnings.extend(warnings);
+
+                for file in &files {
+                    if file.relative_path.starts_with(Self::CATEGORIES_FOLDER) {
+                        continue;
+                    }
+
+                    let mut entries = Vec::new();
+
+                    match kind {
+                        EntryKind::Decision => {
+                            Self::read_decisions(file, &file.block, None, &mut entries)
+                        }
+                        EntryKind::Mission => Self::read_missions(file, &mut entries),
+                    }
+
+                    if file.is_replaced {
+                        replaced.extend(entries);
+                        continue;
+                    }
+
+                    for mut entry in entries {
+                        match entry_indices.get(&(kind, entry.name.clone())) {
+                            Some(&idx) => {
+                                let other: &mut Entry = &mut index.entries[idx];
+                                entry.overridden.append(&mut other.overridden);
+                                entry.overridden.push(other.location.clone());
+                                *other = entry;
+                            }
+                            None => {
+                                entry_indices
+                                    .insert((kind, entry.name.clone()), index.entries.len());
+                                index.entries.push(entry);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for entry in replaced {
+            if let Some(&idx) = entry_indices.get(&(entry.kind, entry.name)) {
+                index.entries[idx].overridden.push(entry.location);
+            }
+        }
+
+        for entry in &mut index.entries {
+            entry.overridden.sort_by_key(|location| location.layer);
+        }
+
+        Ok(index)
+    }
+
+    // The entries in the order they're read.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    // The decisions in the block; the top level of a file may contain groups of decisions.
+    fn read_decisions(
+        file: &ScriptFile,
+        block: &Block,
+        group: Option<&str>,
+        entries: &mut Vec<Entry>,
+    ) {
+        for (key, _, value) in block.fields() {
+            let Value::Block(decision) = value else {
+                continue;
+            };
+
+            if !decision
+                .fields()
+                .any(|(key, _, _)| Self::DECISION_KEYS.contains(&key.text.as_str()))
+            {
+                if group.is_none() {
+                    Self::read_decisions(file, decision, Some(&key.text), entries);
+                }
+
+                continue;
+            }
+
+            entries.push(Entry {
+                kind: EntryKind::Decision,
+                name: key.text.clone(),
+                group: group.map(str::to_owned),
+                location: file.location(value.line()),
+                overridden: Vec::new(),
+                block: decision.clone(),
+            });
+        }
+    }
+
+    fn read_missions(file: &ScriptFile, entries: &mut Vec<Entry>) {
+        for (series_key, _, series) in file.block.fields() {
+            let Value::Block(series) = series else {
+                continue;
+            };
+
+            for (key, _, value) in series.fields() {
+                let Value::Block(mission) = value else {
+                    continue;
+                };
+
+                if key.text.starts_with("potential")
+                    || !mission
+                        .fields()
+                        .any(|(key, _, _)| Self::MISSION_KEYS.contains(&key.text.as_str()))
+                {
+                    continue;
+                }
+
+                entries.push(Entry {
+                    kind: EntryKind::Mission,
+                    name: key.text.clone(),
+                    group: Some(series_key.text.clone()),
+                    location: file.location(value.line()),
+                    overridden: Vec::new(),
+                    block: mission.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    #[test]
+    fn entries_of_mods_override_the_game() {
+        let temp_dir = TempDir::new("decisions");
+        let root = temp_dir.path();
+        let game = root.join("game");
+        let mod_ = root.join("mod");
+        fs::create_dir_all(game.join("decisions")).unwrap();
+        fs::create_dir_all(game.join("missions")).unwrap();
+        fs::create_dir_all(mod_.join("decisions")).unwrap();
+        fs::write(
+            game.join("decisions/a.txt"),
+            "country_decisions = {\n\
+             \tfirst = { potential = { } effect = { } }\n\
+             \tsecond = { allow = { } }\n\
+             }\n",
+        )
+        .unwrap();
+        fs::write(
+            game.join("missions/m.txt"),
+            "my_series = {\n\
+             \tpotential = { tag = FRA }\n\
+             \tmy_mission = { icon = x trigger = { } effect = { } }\n\
+             }\n",
+        )
+        .unwrap();
+        // Replaces `a.txt` of the game without `second`, which is overridden in `b.txt`.
+        fs::write(
+            mod_.join("decisions/a.txt"),
+            "country_decisions = { first = { effect = { } } }\n",
+        )
+        .unwrap();
+        fs::write(
+            mod_.join("decisions/b.txt"),
+            "second = { title = custom_title allow = { } }\n",
+        )
+        .unwrap();
+
+        let game = DirTree::new(&game, &WalkOptions::default()).unwrap();
+        let mod_ = DirTree::new_layer(&mod_, &game).unwrap();
+        let index = DecisionIndex::new(&[game, mod_]).unwrap();
+
+        let entries: Vec<_> = index
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.kind,
+                    entry.name.as_str(),
+                    entry.group.as_deref(),
+                    entry.location.layer,
+                    entry
+                        .overridden
+                        .iter()
+                        .map(|location| location.layer)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            entries,
+            [
+                (
+                    EntryKind::Decision,
+                    "first",
+                    Some("country_decisions"),
+                    1,
+                    vec![0]
+                ),
+                (EntryKind::Decision, "second", None, 1, vec![0]),
+                (
+                    EntryKind::Mission,
+                    "my_mission",
+                    Some("my_series"),
+                    0,
+                    vec![]
+                ),
+            ]
+        );
+        assert_eq!(
+            index.entries()[1].title_keys(),
+            ["custom_title", "second_title", "second"]
+        );
+        assert_eq!(
+            index.entries()[2]
+                .sections()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            ["trigger", "effect"]
+        );
+    }
+}
@@ -0,0 +1,630 @@
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::database::vfs;
+use crate::script::{self, Block, Location, SyntaxError, Value};
+use std::collections::HashMap;
+
+// The defines in `common/defines`, the constants the games are tuned with. EU4 and HOI4 set them
+// in Lua files, either in tables or one by one:
+//
+// NDefines = {
+//     NGame = { START_DATE = "1444.11.11", END_DATE = "1821.1.2" },
+// }
+// NDefines.NGame.END_DATE = "1900.1.1"
+//
+// CK3 and Vic3 use script files with blocks like `NGame = { START_DATE = "867.1.1" }`. The files
+// are run in the order of their relative paths, so a later assignment to a define replaces the
+// value of an earlier one, and a mod can change a define without replacing the whole file.
+#[derive(Debug, Default)]
+pub struct DefineIndex {
+    defines: Vec<Define>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Define {
+    // The full name, e.g. `NDefines.NGame.START_DATE`.
+    pub name: String,
+    // The name without the last part, e.g. `NDefines.NGame`.
+    pub namespace: String,
+    // The values the define is set to, in the order they're set.
+    pub assignments: Vec<Assignment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    // The value as it's written, e.g. `0.5` or `"1444.11.11"`.
+    pub value: String,
+    pub location: Location,
+    // Whether the file is replaced by a file of a later layer, so the games don't read it.
+    pub is_replaced: bool,
+}
+
+impl Define {
+    // The last assignment the games read, which the define has the value of.
+    pub fn assignment(&self) -> &Assignment {
+        self.assignments
+            .iter()
+            .rev()
+            .find(|assignment| !assignment.is_replaced)
+            .expect("defines only set in replaced files are left out")
+    }
+
+    // The last assignment of the game itself, which may be replaced by a mod.
+    pub fn vanilla_assignment(&self) -> Option<&Assignment> {
+        self.assignments
+            .iter()
+            .rev()
+            .find(|assignment| assignment.location.layer == 0)
+    }
+
+    // The last part of the name, e.g. `START_DATE`.
+    pub fn key(&self) -> &str {
+        self.name
+            .strip_prefix(&self.namespace)
+            .map_or(&self.name, |key| key.trim_start_matches('.'))
+    }
+
+    // Whether a mod sets the define to another value than the game, or adds it.
+    pub fn is_changed(&self) -> bool {
+        match self.vanilla_assignment() {
+            Some(vanilla) => !values_equal(&vanilla.value, &self.assignment().value),
+            None => true,
+        }
+    }
+}
+
+impl DefineIndex {
+    const FOLDER: &'static str = "common/defines";
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        let mut index = Self::default();
+        let mut define_indices: HashMap<String, usize> = HashMap::new();
+
+        let mut files = script::folder_files(dir_trees, Self::FOLDER, "lua", true);
+        files.extend(script::folder_files(dir_trees, Self::FOLDER, "txt", true));
+        // The replaced versions of a file stay right before the version replacing them.
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (relative_path, full_path, layer, is_replaced) in files {
+            let bytes = match vfs::read(&full_path) {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    index.warnings.push(format!(
+                        "failed to read `{}`: {}",
+                        full_path.display(),
+                        error
+                    ));
+                    continue;
+                }
+            };
+            let text = script::decode(&bytes);
+
+            let is_lua = relative_path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("lua"));
+            let (values, errors) = if is_lua {
+                parse_lua(&text)
+            } else {
+                let (block, errors) = script::parse(&text);
+                let mut values = Vec::new();
+                flatten_block(&mut Vec::new(), &block, &mut values);
+                (values, errors)
+            };
+
+            if !is_replaced {
+                for error in errors {
+                    index.warnings.push(format!(
+                        "{}:{}: {}",
+                        relative_path.display(),
+                        error.line,
+                        error.message
+                    ));
+                }
+            }
+
+            for (path, value, line) in values {
+                let assignment = Assignment {
+                    value,
+                    location: Location {
+                        relative_path: relative_path.clone(),
+                        full_path: full_path.clone(),
+                        line,
+                        layer,
+                    },
+                    is_replaced,
+                };
+                let name = path.join(".");
+
+                match define_indices.get(&name) {
+                    Some(&idx) => index.defines[idx].assignments.push(assignment),
+                    None => {
+                        define_indices.insert(name.clone(), index.defines.len());
+                        index.defines.push(Define {
+                            name,
+                            namespace: path[..path.len() - 1].join("."),
+                            assignments: vec![assignment],
+                        });
+                    }
+                }
+            }
+        }
+
+        // The games never see the defines which are only set in replaced files.
+        index.defines.retain(|define| {
+            define
+                .assignments
+                .iter()
+                .any(|assignment| !assignment.is_replaced)
+        });
+
+        Ok(index)
+    }
+
+    // The defines in the order they're first set.
+    pub fn defines(&self) -> &[Define] {
+        &self.defines
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+// The defines read from a file as `(path, value, line)`, e.g. `(["NGame", "START_DATE"], ...)`.
+type Values = Vec<(Vec<String>, String, usize)>;
+
+// Compares numbers by their value, so that `0.5` equals `0.50`.
+fn values_equal(a: &str, b: &str) -> bool {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// The defines in the block; blocks of named values are namespaces.
+fn flatten_block(path: &mut Vec<String>, block: &Block, values: &mut Values) {
+    for (key, _, value) in block.fields() {
+        path.push(key.text.clone());
+
+        match value {
+            Value::Block(nested)
+                if !nested.items.is_empty()
+                    && nested.items.iter().all(|item| item.key.is_some()) =>
+            {
+                flatten_block(path, nested, values);
+            }
+            value => values.push((path.clone(), value.to_script(), value.line())),
+        }
+
+        path.pop();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LuaToken<'a> {
+    Name(&'a str),
+    // Numbers, strings, `true`, `false` and `nil`, as they're written.
+    Literal(&'a str),
+    Equals,
+    Dot,
+    Open,
+    Close,
+    // `,` or `;`.
+    Separator,
+}
+
+enum LuaValue {
+    Literal(String, usize),
+    // The fields of a table, which are either named or positional.
+    Table(Vec<(Option<String>, LuaValue)>, usize),
+}
+
+impl LuaValue {
+    fn line(&self) -> usize {
+        match self {
+            Self::Literal(_, line) | Self::Table(_, line) => *line,
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            Self::Literal(text, _) => text.clone(),
+            Self::Table(fields, _) if fields.is_empty() => "{ }".to_owned(),
+            Self::Table(fields, _) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, value)| match name {
+                        Some(name) => format!("{} = {}", name, value.to_text()),
+                        None => value.to_text(),
+                    })
+                    .collect();
+
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }
+    }
+
+    // Tables of named values are namespaces; everything else is the value of a define.
+    fn flatten(self, path: &mut Vec<String>, values: &mut Values) {
+        match self {
+            Self::Table(fields, _)
+                if !fields.is_empty() && fields.iter().all(|(name, _)| name.is_some()) =>
+            {
+                for (name, value) in fields {
+                    path.push(name.unwrap_or_default());
+                    value.flatten(path, values);
+                    path.pop();
+                }
+            }
+            value => values.push((path.clone(), value.to_text(), value.line())),
+        }
+    }
+}
+
+// Reads the assignments of a defines file, the part of Lua these files are written in.
+fn parse_lua(text: &str) -> (Values, Vec<SyntaxError>) {
+    let (tokens, mut errors) = tokenize_lua(text);
+    let mut parser = LuaParser {
+        tokens,
+        position: 0,
+        errors: Vec::new(),
+    };
+    let mut values = Vec::new();
+
+    while parser.position < parser.tokens.len() {
+        if parser.eat(&LuaToken::Separator) {
+            continue;
+        }
+
+        let start = parser.position;
+
+        match parser.statement() {
+            Some((mut path, value)) => value.flatten(&mut path, &mut values),
+            // Skips what couldn't be read, and at least a token so that it gets on.
+            None => parser.position = parser.position.max(start + 1),
+        }
+    }
+
+    errors.append(&mut parser.errors);
+    errors.sort_by_key(|error| error.line);
+
+    (values, errors)
+}
+
+fn tokenize_lua(text: &str) -> (Vec<(LuaToken<'_>, usize)>, Vec<SyntaxError>) {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    let mut position = 0;
+    let mut line = 1;
+
+    while let Some(&byte) = bytes.get(position) {
+        let start = position;
+        position += 1;
+
+        let token = match byte {
+            b'\n' => {
+                line += 1;
+                continue;
+            }
+            byte if byte.is_ascii_whitespace() => continue,
+            b'-' if bytes.get(position) == Some(&b'-') => {
+                // `--[[ ... ]]` comments span lines; `-- ...` comments end with the line.
+                let (end_marker, skipped) = if text[position + 1..].starts_with("[[") {
+                    ("]]", 3)
+                } else {
+                    ("\n", 1)
+                };
+                let comment_start = position + skipped;
+                let end = text[comment_start..]
+                    .find(end_marker)
+                    .map_or(text.len(), |end| comment_start + end);
+
+                line += text[start..end].matches('\n').count();
+                position = if end_marker == "]]" { end + 2 } else { end };
+                continue;
+            }
+            b'=' => LuaToken::Equals,
+            b'.' if !bytes.get(position).is_some_and(u8::is_ascii_digit) => LuaToken::Dot,
+            b'{' => LuaToken::Open,
+            b'}' => LuaToken::Close,
+            b',' | b';' => LuaToken::Separator,
+            b'"' | b'\'' => {
+                let mut escaped = false;
+
+                loop {
+                    match bytes.get(position) {
+                        None | Some(b'\n') => {
+                            errors.push(SyntaxError {
+                                line,
+                                message: "unterminated string".to_owned(),
+                            });
+                            break;
+                        }
+                        Some(b'\\') if !escaped => escaped = true,
+                        Some(&quote) if quote == byte && !escaped => {
+                            position += 1;
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+
+                    position += 1;
+                }
+
+                LuaToken::Literal(&text[start..position])
+            }
+            b'-' | b'.' | b'0'..=b'9' => {
+                while bytes.get(position).is_some_and(|&byte| {
+                    byte.is_ascii_alphanumeric()
+                        || byte == b'.'
+                        || (matches!(byte, b'-' | b'+')
+                            && matches!(bytes[position - 1], b'e' | b'E'))
+                }) {
+                    position += 1;
+                }
+
+                LuaToken::Literal(&text[start..position])
+            }
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                while bytes
+                    .get(position)
+                    .is_some_and(|&byte| byte.is_ascii_alphanumeric() || byte == b'_')
+                {
+                    position += 1;
+                }
+
+                match &text[start..position] {
+                    word @ ("true" | "false" | "nil") => LuaToken::Literal(word),
+                    word => LuaToken::Name(word),
+                }
+            }
+            _ => {
+                // Skips the whole character, which may be more than a byte.
+                let char = text[start..].chars().next().unwrap_or_default();
+                position = start + char.len_utf8();
+                errors.push(SyntaxError {
+                    line,
+                    message: format!("unexpected `{}`", char),
+                });
+                continue;
+            }
+        };
+
+        tokens.push((token, line));
+    }
+
+    (tokens, errors)
+}
+
+struct LuaParser<'a> {
+    tokens: Vec<(LuaToken<'a>, usize)>,
+    position: usize,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> LuaParser<'a> {
+    fn peek(&self) -> Option<&LuaToken<'a>> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |&(_, line)| line)
+    }
+
+    fn eat(&mut self, token: &LuaToken) -> bool {
+        let is_next = self.peek() == Some(token);
+
+        if is_next {
+            self.position += 1;
+        }
+
+        is_next
+    }
+
+    fn error(&mut self, message: &str) {
+        self.errors.push(SyntaxError {
+            line: self.line(),
+            message: message.to_owned(),
+        });
+    }
+
+    // `a.b.c = value`
+    fn statement(&mut self) -> Option<(Vec<String>, LuaValue)> {
+        let mut path = Vec::new();
+
+        loop {
+            let Some(&LuaToken::Name(name)) = self.peek() else {
+                self.error("expected a name");
+                return None;
+            };
+
+            path.push(name.to_owned());
+            self.position += 1;
+
+            if !self.eat(&LuaToken::Dot) {
+                break;
+            }
+        }
+
+        if !self.eat(&LuaToken::Equals) {
+            self.error("expected `=`");
+            return None;
+        }
+
+        Some((path, self.value()?))
+    }
+
+    fn value(&mut self) -> Option<LuaValue> {
+        let line = self.line();
+
+        match self.peek().cloned() {
+            Some(LuaToken::Literal(text)) => {
+                self.position += 1;
+                Some(LuaValue::Literal(text.to_owned(), line))
+            }
+            // A reference to another value, e.g. `NDefines.NGame.START_DATE`.
+            Some(LuaToken::Name(_)) => {
+                let mut text = String::new();
+
+                while let Some(&LuaToken::Name(name)) = self.peek() {
+                    text.push_str(name);
+                    self.position += 1;
+
+                    if !self.eat(&LuaToken::Dot) {
+                        break;
+                    }
+
+                    text.push('.');
+                }
+
+                Some(LuaValue::Literal(text, line))
+            }
+            Some(LuaToken::Open) => {
+                self.position += 1;
+                let mut fields = Vec::new();
+
+                loop {
+                    match self.peek() {
+                        Some(LuaToken::Close) => {
+                            self.position += 1;
+                            break;
+                        }
+                        Some(LuaToken::Separator) => {
+                            self.position += 1;
+                            continue;
+                        }
+                        None => {
+                            self.error("unclosed table");
+                            break;
+                        }
+                        _ => {}
+                    }
+
+                    let name = match (self.peek(), self.tokens.get(self.position + 1)) {
+                        (Some(&LuaToken::Name(name)), Some((LuaToken::Equals, _))) => {
+                            self.position += 2;
+                            Some(name.to_owned())
+                        }
+                        _ => None,
+                    };
+
+                    match self.value() {
+                        Some(value) => fields.push((name, value)),
+                        // Skips the token which isn't a value.
+                        None => self.position += 1,
+                    }
+                }
+
+                Some(LuaValue::Table(fields, line))
+            }
+            _ => {
+                self.error("expected a value");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn lua_defines_are_flattened() {
+        let (values, errors) = parse_lua(
+            "-- The defines of the game\n\
+             NDefines = {\n\
+             \tNGame = {\n\
+             \t\tSTART_DATE = \"1444.11.11\", -- a comment\n\
+             \t\tWEIGHTS = { 1, 2.5, -3 },\n\
+             \t},\n\
+             \t--[[ NOld = {\n\
+             \t}, ]]\n\
+             \tNAI = { ENABLED = true; BIAS = -1e-2 },\n\
+             }\n\
+             NDefines.NGame.END_DATE = '1821.1.2'\n",
+        );
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            values
+                .iter()
+                .map(|(path, value, line)| (path.join("."), value.as_str(), *line))
+                .collect::<Vec<_>>(),
+            [
+                ("NDefines.NGame.START_DATE".to_owned(), "\"1444.11.11\"", 4),
+                ("NDefines.NGame.WEIGHTS".to_owned(), "{ 1, 2.5, -3 }", 5),
+                ("NDefines.NAI.ENABLED".to_owned(), "true", 9),
+                ("NDefines.NAI.BIAS".to_owned(), "-1e-2", 9),
+                ("NDefines.NGame.END_DATE".to_owned(), "'1821.1.2'", 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn mods_override_defines() {
+        let temp_dir = TempDir::new("defines");
+        let root = temp_dir.path();
+        let game = root.join("game");
+        let mod_ = root.join("mod");
+        fs::create_dir_all(game.join("common/defines")).unwrap();
+        fs::create_dir_all(mod_.join("common/defines")).unwrap();
+        fs::write(
+            game.join("common/defines/00_defines.lua"),
+            "NDefines = { NGame = { A = 1, B = 2, C = 0.5 } }\n",
+        )
+        .unwrap();
+        // Replaces the file of the game without `B`.
+        fs::write(
+            mod_.join("common/defines/00_defines.lua"),
+            "NDefines = { NGame = { A = 1, C = 0.50 } }\n",
+        )
+        .unwrap();
+        fs::write(
+            mod_.join("common/defines/01_mod.lua"),
+            "NDefines.NGame.A = 10\nNDefines.NGame.D = 4\n",
+        )
+        .unwrap();
+
+        let game = DirTree::new(&game, &WalkOptions::default()).unwrap();
+        let mod_ = DirTree::new_layer(&mod_, &game).unwrap();
+        let index = DefineIndex::new(&[game, mod_]).unwrap();
+
+        let defines: Vec<_> = index
+            .defines()
+            .iter()
+            .map(|define| {
+                (
+                    define.key(),
+                    define.assignment().value.as_str(),
+                    define.assignment().location.relative_path.clone(),
+                    define
+                        .vanilla_assignment()
+                        .map(|assignment| assignment.value.as_str()),
+                    define.is_changed(),
+                )
+            })
+            .collect();
+        let path = |name| PathBuf::from("common/defines").join(name);
+
+        assert_eq!(
+            defines,
+            [
+                ("A", "10", path("01_mod.lua"), Some("1"), true),
+                ("C", "0.50", path("00_defines.lua"), Some("0.5"), false),
+                ("D", "4", path("01_mod.lua"), None, true),
+            ]
+        );
+        assert_eq!(index.defines()[0].namespace, "NDefines.NGame");
+        assert!(index.warnings().is_empty());
+    }
+}
@@ -0,0 +1,588 @@
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::script::{self, Block, Location, ScriptFile, Value};
+use std::collections::{HashMap, HashSet};
+
+// The named things the script files define in the `common` folder, e.g. the modifiers in
+// `common/event_modifiers`, and the places in the scripts which refer to them by name, e.g.
+//
+// add_country_modifier = { name = my_modifier duration = 365 }
+//
+// Scripted triggers and effects are called by their name as a key instead, with the parameters in
+// a block or with `yes` if there aren't any:
+//
+// my_effect = { AMOUNT = 10 }
+//
+// A definition replaces the definition of the same kind with the same name which was read before
+// it, so the definitions of a mod override those of the game. Things are looked up by name only,
+// so a reference to a name which is defined as more than one kind of thing refers to all of them.
+#[derive(Debug, Default)]
+pub struct DefinitionIndex {
+    definitions: Vec<Definition>,
+    // The indices of the definitions by name.
+    definition_indices: HashMap<String, Vec<usize>>,
+    // The names of the definitions which are referred to by keys.
+    key_names: HashSet<String>,
+    references: HashMap<String, Vec<Reference>>,
+    // Calls of scripted triggers and effects which aren't defined.
+    undefined_calls: Vec<Reference>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DefinitionKind {
+    StaticModifier,
+    EventModifier,
+    TriggeredModifier,
+    OpinionModifier,
+    Modifier,
+    Idea,
+    ScriptedTrigger,
+    ScriptedEffect,
+    ScriptValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub kind: DefinitionKind,
+    pub name: String,
+    pub location: Location,
+    // The names of the `$PARAM$`s the definition substitutes.
+    pub parameters: Vec<String>,
+}
+
+impl Definition {
+    // The key the games localize the name of a modifier with.
+    pub fn localization_key(&self) -> String {
+        format!("modifier_{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub name: String,
+    pub location: Location,
+    // The key the name is used with, e.g. `add_country_modifier` or `has_idea`, or the key of the
+    // block a scripted trigger or effect is called in.
+    pub context: String,
+    // The parameters passed to a scripted trigger or effect, e.g. `("AMOUNT", "10")`.
+    pub arguments: Vec<(String, String)>,
+}
+
+impl DefinitionKind {
+    pub const MODIFIERS: &'static [Self] = &[
+        Self::StaticModifier,
+        Self::EventModifier,
+        Self::TriggeredModifier,
+        Self::OpinionModifier,
+        Self::Modifier,
+        Self::Idea,
+    ];
+    pub const SCRIPTED: &'static [Self] = &[
+        Self::ScriptedTrigger,
+        Self::ScriptedEffect,
+        Self::ScriptValue,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::StaticModifier => "static modifier",
+            Self::EventModifier => "event modifier",
+            Self::TriggeredModifier => "triggered modifier",
+            Self::OpinionModifier => "opinion modifier",
+            Self::Modifier => "modifier",
+            Self::Idea => "idea",
+            Self::ScriptedTrigger => "scripted trigger",
+            Self::ScriptedEffect => "scripted effect",
+            Self::ScriptValue => "script value",
+        }
+    }
+
+    // Whether the things are referred to by keys, e.g. `my_trigger = yes` or, for script values,
+    // `my_value > 10`.
+    fn is_referred_to_by_key(&self) -> bool {
+        matches!(
+            self,
+            Self::ScriptedTrigger | Self::ScriptedEffect | Self::ScriptValue
+        )
+    }
+
+    fn folder(&self) -> &'static str {
+        match self {
+            Self::StaticModifier => "common/static_modifiers",
+            Self::EventModifier => "common/event_modifiers",
+            Self::TriggeredModifier => "common/triggered_modifiers",
+            Self::OpinionModifier => "common/opinion_modifiers",
+            Self::Modifier => "common/modifiers",
+            Self::Idea => "common/ideas",
+            Self::ScriptedTrigger => "common/scripted_triggers",
+            Self::ScriptedEffect => "common/scripted_effects",
+            Self::ScriptValue => "common/script_values",
+        }
+    }
+}
+
+impl DefinitionIndex {
+    // The folders which are searched for references.
+    const SCRIPT_FOLDERS: &'static [&'static str] =
+        &["common", "events", "decisions", "missions", "history"];
+    // Keys which only say that the value is a name, e.g. `name = my_modifier`; the key of the
+    // enclosing block is more telling.
+    const NAME_KEYS: &'static [&'static str] = &["name", "modifier", "id", "key"];
+    // The keys of an EU4 idea group which aren't ideas.
+    const IDEA_GROUP_KEYS: &'static [&'static str] = &[
+        "start",
+        "bonus",
+        "trigger",
+        "free",
+        "ai_will_do",
+        "category",
+        "important",
+    ];
+
+    pub fn new(dir_trees: &[DirTree], kinds: &[DefinitionKind]) -> Result<Self> {
+        let mut index = Self::default();
+        let mut kind_indices = HashMap::new();
+
+        for kind in kinds {
+            let (files, warnings) = script::load_folder(dir_trees, kind.folder())?;
+            index.warnings.extend(warnings);
+
+            for file in &files {
+                let mut definitions = Vec::new();
+                Self::read_definitions(*kind, file, &mut definitions);
+
+                for definition in definitions {
+                    match kind_indices.get(&(definition.kind, definition.name.clone())) {
+                        Some(&idx) => index.definitions[idx] = definition,
+                        None => {
+                            kind_indices.insert(
+                                (definition.kind, definition.name.clone()),
+                                index.definitions.len(),
+                            );
+                            index.definitions.push(definition);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (idx, definition) in index.definitions.iter().enumerate() {
+            index
+                .definition_indices
+                .entry(definition.name.clone())
+                .or_default()
+                .push(idx);
+
+            if definition.kind.is_referred_to_by_key() {
+                index.key_names.insert(definition.name.clone());
+            }
+        }
+
+        let finds_undefined_calls = kinds.iter().any(DefinitionKind::is_referred_to_by_key);
+
+        for folder in Self::SCRIPT_FOLDERS {
+            // The warnings of the definition folders were reported already.
+            let (files, _) = script::load_folder(dir_trees, folder)?;
+
+            for file in &files {
+                // The top-level keys of these files are the definitions themselves.
+                let is_definition_file = kinds
+                    .iter()
+                    .any(|kind| file.relative_path.starts_with(kind.folder()));
+
+                index.add_references(
+                    file,
+                    &file.block,
+                    "",
+                    is_definition_file,
+                    finds_undefined_calls,
+                );
+            }
+        }
+
+        Ok(index)
+    }
+
+    // The definitions in the order of the kinds, and in the order they're read within a kind.
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+
+    pub fn definitions_of(&self, name: &str) -> impl Iterator<Item = &Definition> {
+        self.definition_indices
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|&idx| &self.definitions[idx])
+    }
+
+    pub fn references(&self, name: &str) -> &[Reference] {
+        self.references.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    // The calls which look like calls of scripted triggers or effects, with parameters in
+    // capitals, but which don't call any that's defined. Calls with `yes` or `no` are left out,
+    // since they can't be told apart from the built-in triggers and effects, like `always = yes`.
+    pub fn undefined_calls(&self) -> &[Reference] {
+        &self.undefined_calls
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    fn read_definitions(
+        kind: DefinitionKind,
+        file: &ScriptFile,
+        definitions: &mut Vec<Definition>,
+    ) {
+        let mut add = |name: &str, value: &Value| {
+            definitions.push(Definition {
+                kind,
+                name: name.to_owned(),
+                location: file.location(value.line()),
+                parameters: parameters(&value.to_script()),
+            });
+        };
+
+        for (key, _, value) in file.block.fields() {
+            let Value::Block(block) = value else {
+                // Variables like `@value = 10`.
+                continue;
+            };
+
+            if kind != DefinitionKind::Idea {
+                add(&key.text, value);
+                continue;
+            }
+
+            // HOI4 has categories of ideas in an `ideas` block; EU4 has idea groups with the
+            // ideas and a few settings.
+            let groups: Vec<_> = if key.text == "ideas" {
+                block
+                    .fields()
+                    .filter_map(|(_, _, value)| value.as_block())
+                    .collect()
+            } else {
+                vec![block]
+            };
+
+            for group in groups {
+                for (key, _, value) in group.fields() {
+                    if value.as_block().is_some()
+                        && !Self::IDEA_GROUP_KEYS.contains(&key.text.as_str())
+                    {
+                        add(&key.text, value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_references(
+        &mut self,
+        file: &ScriptFile,
+        block: &Block,
+        block_key: &str,
+        is_definition_block: bool,
+        finds_undefined_calls: bool,
+    ) {
+        for item in &block.items {
+            let key = item.key.as_ref().map_or("", |(key, _)| key.text.as_str());
+
+            if let Some((key_scalar, _)) = &item.key
+                && !is_definition_block
+            {
+                if self.key_names.contains(key) {
+                    let arguments = item
+                        .value
+                        .as_block()
+                        .map(|block| {
+                            block
+                                .fields()
+                                .map(|(key, _, value)| (key.text.clone(), value.to_script()))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    self.references
+                        .entry(key.to_owned())
+                        .or_default()
+                        .push(Reference {
+                            name: key.to_owned(),
+                            location: file.location(key_scalar.line),
+                            context: block_key.to_owned(),
+                            arguments,
+                        });
+                } else if finds_undefined_calls
+                    && !self.definition_indices.contains_key(key)
+                    && !key.contains('$')
+                    && item.value.as_block().is_some_and(is_call_block)
+                {
+                    self.undefined_calls.push(Reference {
+                        name: key.to_owned(),
+                        location: file.location(key_scalar.line),
+                        context: block_key.to_owned(),
+                        arguments: Vec::new(),
+                    });
+                }
+            }
+
+            match &item.value {
+                Value::Scalar(scalar) => {
+                    if !self.definition_indices.contains_key(&scalar.text) {
+                        continue;
+                    }
+
+                    let context = if key.is_empty() || Self::NAME_KEYS.contains(&key) {
+                        block_key
+                    } else {
+                        key
+                    };
+
+                    self.references
+                        .entry(scalar.text.clone())
+                        .or_default()
+                        .push(Reference {
+                            name: scalar.text.clone(),
+                            location: file.location(scalar.line),
+                            context: context.to_owned(),
+                            arguments: Vec::new(),
+                        });
+                }
+                Value::Block(nested) => {
+                    let key = if key.is_empty() { block_key } else { key };
+                    self.add_references(file, nested, key, false, finds_undefined_calls);
+                }
+            }
+        }
+    }
+}
+
+// The names of the parameters in the script, e.g. `AMOUNT` in `add_gold = $AMOUNT$`. The newer
+// games allow defaults like `$AMOUNT|10$`.
+fn parameters(script: &str) -> Vec<String> {
+    let mut parameters: Vec<String> = Vec::new();
+
+    for part in script.split('$').skip(1).step_by(2) {
+        let name = part.split('|').next().unwrap_or_default();
+
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_')
+            && !parameters.iter().any(|parameter| parameter == name)
+        {
+            parameters.push(name.to_owned());
+        }
+    }
+
+    parameters
+}
+
+// Whether the block looks like the parameters of a call, e.g. `{ AMOUNT = 10 }`.
+fn is_call_block(block: &Block) -> bool {
+    block.tag.is_none()
+        && !block.items.is_empty()
+        && block.items.iter().all(|item| {
+            item.key.as_ref().is_some_and(|(key, _)| {
+                key.text.chars().any(|char| char.is_ascii_uppercase())
+                    && key.text.chars().all(|char| {
+                        char.is_ascii_uppercase() || char.is_ascii_digit() || char == '_'
+                    })
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    #[test]
+    fn definitions_and_references_are_indexed() {
+        let temp_dir = TempDir::new("definitions");
+        let root = temp_dir.path();
+        let game = root.join("game");
+        let mod_ = root.join("mod");
+        fs::create_dir_all(game.join("common/event_modifiers")).unwrap();
+        fs::create_dir_all(game.join("common/ideas")).unwrap();
+        fs::create_dir_all(game.join("events")).unwrap();
+        fs::create_dir_all(mod_.join("common/event_modifiers")).unwrap();
+        fs::write(
+            game.join("common/event_modifiers/a.txt"),
+            "@bonus = 0.1\n\
+             first = { tax_income = @bonus }\n\
+             second = { stability_cost_modifier = -0.1 }\n",
+        )
+        .unwrap();
+        fs::write(
+            game.join("common/ideas/b.txt"),
+            "my_ideas = {\n\
+             \tstart = { tax_income = 1 }\n\
+             \tmy_idea = { tax_income = 1 }\n\
+             \tai_will_do = { factor = 1 }\n\
+             }\n\
+             ideas = { country = { national_spirit = { } } }\n",
+        )
+        .unwrap();
+        fs::write(
+            game.join("events/c.txt"),
+            "country_event = {\n\
+             \tid = test.1\n\
+             \ttrigger = { has_country_modifier = first }\n\
+             \timmediate = { add_country_modifier = { name = second duration = 365 } }\n\
+             }\n",
+        )
+        .unwrap();
+        fs::write(
+            mod_.join("common/event_modifiers/d.txt"),
+            "second = { stability_cost_modifier = -0.2 }\n",
+        )
+        .unwrap();
+
+        let game = DirTree::new(&game, &WalkOptions::default()).unwrap();
+        let mod_ = DirTree::new_layer(&mod_, &game).unwrap();
+        let index = DefinitionIndex::new(&[game, mod_], DefinitionKind::MODIFIERS).unwrap();
+
+        let definitions: Vec<_> = index
+            .definitions()
+            .iter()
+            .map(|definition| {
+                (
+                    definition.kind,
+                    definition.name.as_str(),
+                    definition.location.layer,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            definitions,
+            [
+                (DefinitionKind::EventModifier, "first", 0),
+                (DefinitionKind::EventModifier, "second", 1),
+                (DefinitionKind::Idea, "my_idea", 0),
+                (DefinitionKind::Idea, "national_spirit", 0),
+            ]
+        );
+
+        let references = |name| {
+            index
+                .references(name)
+                .iter()
+                .map(|reference| (reference.context.clone(), reference.location.line))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            references("first"),
+            [("has_country_modifier".to_owned(), 3)]
+        );
+        assert_eq!(
+            references("second"),
+            [("add_country_modifier".to_owned(), 4)]
+        );
+        assert_eq!(references("my_idea"), []);
+        assert_eq!(index.definitions()[1].localization_key(), "modifier_second");
+    }
+
+    #[test]
+    fn scripted_items_are_called_by_key() {
+        let temp_dir = TempDir::new("scripted");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("common/scripted_effects")).unwrap();
+        fs::create_dir_all(root.join("common/scripted_triggers")).unwrap();
+        fs::create_dir_all(root.join("events")).unwrap();
+        fs::write(
+            root.join("common/scripted_effects/a.txt"),
+            "my_effect = { add_gold = $AMOUNT$ add_prestige = $AMOUNT|5$ }\n\
+             unused_effect = { }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("common/scripted_triggers/b.txt"),
+            "my_trigger = { always = yes }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("events/c.txt"),
+            "country_event = {\n\
+             \ttrigger = { my_trigger = yes }\n\
+             \timmediate = {\n\
+             \t\tmy_effect = { AMOUNT = 10 }\n\
+             \t\tmissing_effect = { AMOUNT = 10 }\n\
+             \t\tFRA = { add_gold = 1 }\n\
+             \t}\n\
+             \tis_triggered_only = yes\n\
+             \toption = {\n\
+             \t\tif = { limit = { missing_trigger = yes } }\n\
+             \t\tmissing_flag_effect = no\n\
+             \t}\n\
+             }\n",
+        )
+        .unwrap();
+
+        let dir_tree = DirTree::new(root, &WalkOptions::default()).unwrap();
+        let index = DefinitionIndex::new(&[dir_tree], DefinitionKind::SCRIPTED).unwrap();
+
+        let definitions: Vec<_> = index
+            .definitions()
+            .iter()
+            .map(|definition| {
+                (
+                    definition.kind,
+                    definition.name.as_str(),
+                    definition.parameters.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            definitions,
+            [
+                (DefinitionKind::ScriptedTrigger, "my_trigger", vec![]),
+                (
+                    DefinitionKind::ScriptedEffect,
+                    "my_effect",
+                    vec!["AMOUNT".to_owned()]
+                ),
+                (DefinitionKind::ScriptedEffect, "unused_effect", vec![]),
+            ]
+        );
+
+        let calls = |name| {
+            index
+                .references(name)
+                .iter()
+                .map(|reference| {
+                    (
+                        reference.context.as_str(),
+                        reference.location.line,
+                        reference.arguments.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(calls("my_trigger"), [("trigger", 2, vec![])]);
+        assert_eq!(
+            calls("my_effect"),
+            [("immediate", 4, vec![("AMOUNT".to_owned(), "10".to_owned())])]
+        );
+        assert_eq!(calls("unused_effect"), []);
+        assert_eq!(
+            index
+                .undefined_calls()
+                .iter()
+                .map(|reference| (reference.name.as_str(), reference.location.line))
+                .collect::<Vec<_>>(),
+            // Neither `always = yes`, which is built in, nor `missing_trigger = yes` is reported,
+            // since they can't be told apart.
+            [("missing_effect", 5)]
+        );
+    }
+}
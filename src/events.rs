@@ -0,0 +1,577 @@
+pub mod graph;
+
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::decisions::{DecisionIndex, EntryKind};
+use crate::script::{self, Block, Location, ScriptFile, Value};
+use std::collections::{BTreeSet, HashMap};
+
+// The events of the game and the mods, from the files in the `events` folder, and the on_actions
+// and decisions which fire them, from the files in `common/on_actions` and the decision index.
+//
+// The games write events in two styles. The older games (EU4, HOI4, Stellaris) use blocks named
+// after the scope of the event with the ID inside:
+//
+// country_event = { id = flavor_fra.1 ... }
+//
+// CK3 and later use the ID as the key and the scope as the `type`:
+//
+// flavor_fra.0001 = { type = character_event ... }
+#[derive(Debug, Default)]
+pub struct EventIndex {
+    events: Vec<Event>,
+    event_indices: HashMap<String, usize>,
+    on_actions: Vec<OnAction>,
+    decisions: Vec<Decision>,
+    // The events, on_actions and decisions which fire each event, by the ID of the fired event.
+    callers: HashMap<String, Vec<Caller>>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub id: String,
+    // The part of the ID before the `.`, if any.
+    pub namespace: Option<String>,
+    // The scope of the event, e.g. `country_event`.
+    pub kind: String,
+    pub location: Location,
+    // The localization keys of the title and the description. Events can pick them by triggers,
+    // so there may be more than one.
+    pub titles: Vec<String>,
+    pub descs: Vec<String>,
+    pub hidden: bool,
+    pub is_triggered_only: bool,
+    pub fire_only_once: bool,
+    pub trigger: Option<Block>,
+    pub mean_time_to_happen: Option<Block>,
+    pub immediate: Option<Block>,
+    pub options: Vec<EventOption>,
+    pub after: Option<Block>,
+    // The events fired by this one.
+    pub calls: Vec<EventCall>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventOption {
+    // The localization key of the option's text.
+    pub name: Option<String>,
+    pub block: Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct OnAction {
+    pub name: String,
+    pub location: Location,
+    pub calls: Vec<EventCall>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub name: String,
+    pub location: Location,
+    pub calls: Vec<EventCall>,
+}
+
+// An event fired from a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventCall {
+    pub event_id: String,
+    // The key the event is fired with, e.g. `country_event` or `random_events`.
+    pub context: String,
+    pub line: usize,
+    // The index of the option of the event the call is in, if any.
+    pub option: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caller {
+    Event(String),
+    OnAction(String),
+    Decision(String),
+}
+
+impl EventIndex {
+    const EVENTS_FOLDER: &'static str = "events";
+    const ON_ACTIONS_FOLDER: &'static str = "common/on_actions";
+    // The keys of the blocks which are conditions rather than effects.
+    const TRIGGER_KEYS: &'static [&'static str] = &[
+        "trigger",
+        "mean_time_to_happen",
+        "potential",
+        "allow",
+        "is_shown",
+        "is_valid",
+        "visible",
+        "available",
+        "ai_will_do",
+    ];
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        let mut index = Self::default();
+
+        let (files, warnings) = script::load_folder(dir_trees, Self::EVENTS_FOLDER)?;
+        index.warnings.extend(warnings);
+
+        for file in &files {
+            index.add_events(file);
+        }
+
+        let (files, warnings) = script::load_folder(dir_trees, Self::ON_ACTIONS_FOLDER)?;
+        index.warnings.extend(warnings);
+
+        for file in &files {
+            index.add_on_actions(file, &file.block);
+        }
+
+        let decisions = DecisionIndex::new(dir_trees)?;
+        index.warnings.extend_from_slice(decisions.warnings());
+
+        for entry in decisions.entries() {
+            if entry.kind != EntryKind::Decision {
+                continue;
+            }
+
+            let mut calls = Vec::new();
+
+            for (key, _, value) in entry.block.fields() {
+                Self::collect_effect_calls(&key.text, value, &mut calls);
+            }
+
+            index.decisions.push(Decision {
+                name: entry.name.clone(),
+                location: entry.location.clone(),
+                calls,
+            });
+        }
+
+        index
+            .events
+            .sort_by(|a, b| Self::sort_key(&a.id).cmp(&Self::sort_key(&b.id)));
+        index.event_indices = index
+            .events
+            .iter()
+            .enumerate()
+            .map(|(idx, event)| (event.id.clone(), idx))
+            .collect();
+
+        for event in &index.events {
+            for call in &event.calls {
+                index
+                    .callers
+                    .entry(call.event_id.clone())
+                    .or_default()
+                    .push(Caller::Event(event.id.clone()));
+            }
+        }
+
+        for on_action in &index.on_actions {
+            for call in &on_action.calls {
+                index
+                    .callers
+                    .entry(call.event_id.clone())
+                    .or_default()
+                    .push(Caller::OnAction(on_action.name.clone()));
+            }
+        }
+
+        for decision in &index.decisions {
+            for call in &decision.calls {
+                index
+                    .callers
+                    .entry(call.event_id.clone())
+                    .or_default()
+                    .push(Caller::Decision(decision.name.clone()));
+            }
+        }
+
+        for callers in index.callers.values_mut() {
+            callers.dedup();
+        }
+
+        Ok(index)
+    }
+
+    // The events sorted by namespace and number.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn namespaces(&self) -> BTreeSet<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| event.namespace.as_deref())
+            .collect()
+    }
+
+    pub fn event(&self, id: &str) -> Option<&Event> {
+        self.event_indices.get(id).map(|&idx| &self.events[idx])
+    }
+
+    pub fn on_actions(&self) -> &[OnAction] {
+        &self.on_actions
+    }
+
+    pub fn decisions(&self) -> &[Decision] {
+        &self.decisions
+    }
+
+    pub fn callers(&self, event_id: &str) -> &[Caller] {
+        self.callers.get(event_id).map_or(&[], Vec::as_slice)
+    }
+
+    // The files which couldn't be read or parsed, and the events which are defined twice.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    // Sorts `ns.2` before `ns.10`.
+    fn sort_key(id: &str) -> (&str, Option<u64>, &str) {
+        match id.rsplit_once('.') {
+            Some((namespace, number)) => (namespace, number.parse().ok(), number),
+            None => ("", id.parse().ok(), id),
+        }
+    }
+
+    // Whether the key defines or calls an event, e.g. `country_event`. The games accept any case.
+    fn is_event_key(key: &str) -> bool {
+        const SUFFIX: &str = "_event";
+
+        key.eq_ignore_ascii_case("event")
+            || key
+                .len()
+                .checked_sub(SUFFIX.len())
+                .and_then(|start| key.get(start..))
+                .is_some_and(|end| end.eq_ignore_ascii_case(SUFFIX))
+    }
+
+    fn add_events(&mut self, file: &ScriptFile) {
+        for (key, _, value) in file.block.fields() {
+            let Value::Block(block) = value else {
+                continue;
+            };
+
+            let key = key.text.as_str();
+            let (id, kind) = if Self::is_event_key(key) {
+                let Some(id) = block.get_scalar("id") else {
+                    self.warnings.push(format!(
+                        "{}:{}: the event doesn't have an ID",
+                        file.relative_path.display(),
+                        value.line()
+                    ));
+                    continue;
+                };
+
+                (id, key)
+            } else if key.contains('.') {
+                (key, block.get_scalar("type").unwrap_or("event"))
+            } else {
+                // Variables, scripted triggers and effects local to the file, etc.
+                continue;
+            };
+
+            let event = Self::parse_event(id, kind, block, file.location(value.line()));
+
+            match self.event_indices.get(&event.id).copied() {
+                Some(idx) => {
+                    let other = &self.events[idx].location;
+                    self.warnings.push(format!(
+                        "{}:{}: event `{}` is also defined in {}:{}, which is replaced",
+                        event.location.relative_path.display(),
+                        event.location.line,
+                        event.id,
+                        other.relative_path.display(),
+                        other.line
+                    ));
+                    self.events[idx] = event;
+                }
+                None => {
+                    self.event_indices
+                        .insert(event.id.clone(), self.events.len());
+                    self.events.push(event);
+                }
+            }
+        }
+    }
+
+    fn parse_event(id: &str, kind: &str, block: &Block, location: Location) -> Event {
+        let mut calls = Vec::new();
+        let mut option_idx = 0;
+
+        for (key, _, value) in block.fields() {
+            if key.text.eq_ignore_ascii_case("id") {
+                continue;
+            }
+
+            let start = calls.len();
+            Self::collect_effect_calls(&key.text, value, &mut calls);
+
+            if key.text.eq_ignore_ascii_case("option") && value.as_block().is_some() {
+                for call in &mut calls[start..] {
+                    call.option = Some(option_idx);
+                }
+
+                option_idx += 1;
+            }
+        }
+
+        Event {
+            id: id.to_owned(),
+            namespace: id
+                .split_once('.')
+                .map(|(namespace, _)| namespace.to_owned()),
+            kind: kind.to_owned(),
+            location,
+            titles: Self::localization_keys(block, "title"),
+            descs: Self::localization_keys(block, "desc"),
+            hidden: block.is_yes("hidden"),
+            is_triggered_only: block.is_yes("is_triggered_only"),
+            fire_only_once: block.is_yes("fire_only_once"),
+            trigger: block.get_block("trigger").cloned(),
+            mean_time_to_happen: block.get_block("mean_time_to_happen").cloned(),
+            immediate: block.get_block("immediate").cloned(),
+            options: block
+                .get_all("option")
+                .filter_map(Value::as_block)
+                .map(|option| EventOption {
+                    name: option.get_scalar("name").map(str::to_owned),
+                    block: option.clone(),
+                })
+                .collect(),
+            after: block.get_block("after").cloned(),
+            calls,
+        }
+    }
+
+    // The keys of `title = key` and of the variants picked by triggers, e.g.
+    // `desc = { trigger = { ... } desc = key }` or `desc = { first_valid = { desc = key } }`.
+    fn localization_keys(block: &Block, key: &str) -> Vec<String> {
+        fn collect(value: &Value, key: &str, keys: &mut Vec<String>) {
+            match value {
+                Value::Scalar(scalar) => {
+                    if !keys.contains(&scalar.text) {
+                        keys.push(scalar.text.clone());
+                    }
+                }
+                Value::Block(block) => {
+                    for (item_key, _, value) in block.fields() {
+                        if item_key.text.eq_ignore_ascii_case(key)
+                            || (value.as_block().is_some()
+                                && !item_key.text.eq_ignore_ascii_case("trigger"))
+                        {
+                            collect(value, key, keys);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut keys = Vec::new();
+
+        for value in block.get_all(key) {
+            collect(value, key, &mut keys);
+        }
+
+        keys
+    }
+
+    // Finds the events fired by the item and the blocks in it:
+    //
+    // * `country_event = { id = ns.1 days = 3 }` or `country_event = ns.1`, and `trigger_event`
+    // * `events = { ns.1 ns.2 }` in on_actions
+    // * `random_events = { 100 = ns.1 50 = ns.2 }` in on_actions, where `0` is no event
+    fn collect_calls(key: &str, value: &Value, calls: &mut Vec<EventCall>) {
+        let mut push = |event_id: &str, line| {
+            calls.push(EventCall {
+                event_id: event_id.to_owned(),
+                context: key.to_owned(),
+                line,
+                option: None,
+            })
+        };
+
+        if Self::is_event_key(key) {
+            match value {
+                Value::Scalar(scalar) => push(&scalar.text, scalar.line),
+                Value::Block(block) => {
+                    if let Some(id) = block.get("id") {
+                        push(id.as_scalar().unwrap_or_default(), id.line());
+                    }
+                }
+            }
+
+            return;
+        }
+
+        let Value::Block(block) = value else {
+            return;
+        };
+
+        match key {
+            "events" => {
+                for value in block.values() {
+                    if let Value::Scalar(scalar) = value {
+                        push(&scalar.text, scalar.line);
+                    }
+                }
+            }
+            "random_events" => {
+                for (weight, _, value) in block.fields() {
+                    if let Value::Scalar(scalar) = value
+                        && weight.text.parse::<f64>().is_ok()
+                        && scalar.text != "0"
+                    {
+                        push(&scalar.text, scalar.line);
+                    }
+                }
+            }
+            _ => {
+                for item in &block.items {
+                    let key = item.key.as_ref().map_or("", |(key, _)| key.text.as_str());
+                    Self::collect_calls(key, &item.value, calls);
+                }
+            }
+        }
+    }
+
+    // Like `collect_calls`, but skips the conditions, which only decide whether the effects happen.
+    fn collect_effect_calls(key: &str, value: &Value, calls: &mut Vec<EventCall>) {
+        if !Self::TRIGGER_KEYS
+            .iter()
+            .any(|trigger_key| key.eq_ignore_ascii_case(trigger_key))
+        {
+            Self::collect_calls(key, value, calls);
+        }
+    }
+
+    fn add_on_actions(&mut self, file: &ScriptFile, block: &Block) {
+        for (key, _, value) in block.fields() {
+            let Value::Block(on_action) = value else {
+                continue;
+            };
+
+            // HOI4 wraps the on_actions in an `on_actions` block.
+            if key.text == "on_actions" {
+                self.add_on_actions(file, on_action);
+                continue;
+            }
+
+            let mut calls = Vec::new();
+
+            for item in &on_action.items {
+                let key = item.key.as_ref().map_or("", |(key, _)| key.text.as_str());
+                Self::collect_effect_calls(key, &item.value, &mut calls);
+            }
+
+            // An on_action can be extended in several files; each of them is listed.
+            self.on_actions.push(OnAction {
+                name: key.text.clone(),
+                location: file.location(value.line()),
+                calls,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    #[test]
+    fn events_and_their_callers_are_indexed() {
+        let temp_dir = TempDir::new("events");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("events")).unwrap();
+        fs::create_dir_all(root.join("common/on_actions")).unwrap();
+        fs::create_dir_all(root.join("decisions")).unwrap();
+        fs::write(
+            root.join("events/test.txt"),
+            "namespace = test\n\
+             country_event = {\n\
+             \tid = test.10\n\
+             \ttitle = test.10.t\n\
+             \tdesc = { trigger = { tag = FRA } desc = test.10.d.fra }\n\
+             \tdesc = test.10.d\n\
+             \tis_triggered_only = yes\n\
+             \toption = { name = test.10.a country_event = { id = test.2 days = 3 } }\n\
+             }\n\
+             test.2 = { type = character_event hidden = yes }\n\
+             Province_Event = { id = test.3 }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("common/on_actions/00_on_actions.txt"),
+            "on_startup = { events = { test.10 } random_events = { 100 = test.2 100 = 0 } }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("decisions/test.txt"),
+            "country_decisions = {\n\
+             \ttest_decision = {\n\
+             \t\tallow = { has_country_flag = ready }\n\
+             \t\teffect = { country_event = test.10 Character_Event = test.3 }\n\
+             \t}\n\
+             }\n",
+        )
+        .unwrap();
+
+        let dir_tree = DirTree::new(root, &WalkOptions::default()).unwrap();
+        let index = EventIndex::new(std::slice::from_ref(&dir_tree)).unwrap();
+
+        assert_eq!(index.warnings(), [] as [String; 0]);
+        assert_eq!(
+            index
+                .events()
+                .iter()
+                .map(|event| (event.id.as_str(), event.kind.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("test.2", "character_event"),
+                ("test.3", "Province_Event"),
+                ("test.10", "country_event")
+            ]
+        );
+
+        let event = index.event("test.10").unwrap();
+        assert_eq!(event.namespace.as_deref(), Some("test"));
+        assert_eq!(event.location.line, 2);
+        assert_eq!(event.titles, ["test.10.t"]);
+        assert_eq!(event.descs, ["test.10.d.fra", "test.10.d"]);
+        assert!(event.is_triggered_only);
+        assert_eq!(event.options[0].name.as_deref(), Some("test.10.a"));
+        assert_eq!(
+            event.calls,
+            [EventCall {
+                event_id: "test.2".to_owned(),
+                context: "country_event".to_owned(),
+                line: 8,
+                option: Some(0),
+            }]
+        );
+
+        assert!(index.event("test.2").unwrap().hidden);
+        assert_eq!(
+            index.callers("test.2"),
+            [
+                Caller::Event("test.10".to_owned()),
+                Caller::OnAction("on_startup".to_owned())
+            ]
+        );
+        assert_eq!(
+            index.callers("test.10"),
+            [
+                Caller::OnAction("on_startup".to_owned()),
+                Caller::Decision("test_decision".to_owned())
+            ]
+        );
+        // The games accept the keys in any case.
+        assert_eq!(
+            index.callers("test.3"),
+            [Caller::Decision("test_decision".to_owned())]
+        );
+        assert_eq!(index.decisions()[0].location.line, 2);
+    }
+}
@@ -0,0 +1,365 @@
+use super::EventIndex;
+use crate::script::Location;
+use std::collections::{HashMap, HashSet};
+
+// The events of a namespace, the events, on_actions and decisions which fire them and the events
+// they fire, laid out in columns from left to right along the chains, e.g.
+//
+// on_startup -> quest.1 -> quest.2 -> quest.3
+//
+// Cycles are common (events which fire themselves again later), so the edges which close a cycle
+// are left out when the columns are computed; they're still part of the graph.
+#[derive(Debug, Default)]
+pub struct EventGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Event,
+    OnAction,
+    Decision,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub kind: NodeKind,
+    pub name: String,
+    // Events which are fired but not defined don't have a location.
+    pub location: Option<Location>,
+    // Whether the node is outside the namespace and only there because it's connected to it.
+    pub is_external: bool,
+    pub column: usize,
+    pub row: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    // An event or a decision fires the event from its effects.
+    Fires,
+    // An option of the event fires the event.
+    OptionFires,
+    // The on_action fires the event.
+    OnAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+impl EventGraph {
+    // The graph of the events in the namespace, or of all the events.
+    pub fn new(index: &EventIndex, namespace: Option<&str>) -> Self {
+        let mut builder = Builder {
+            index,
+            graph: Self::default(),
+            node_indices: HashMap::new(),
+            edges: HashSet::new(),
+        };
+
+        let in_namespace = |id: &str| {
+            namespace.is_none_or(|namespace| {
+                index.event(id).and_then(|event| event.namespace.as_deref()) == Some(namespace)
+            })
+        };
+
+        for event in index.events() {
+            if !in_namespace(&event.id) {
+                continue;
+            }
+
+            let from = builder.event_node(&event.id, false);
+
+            for call in &event.calls {
+                let to = builder.event_node(&call.event_id, !in_namespace(&call.event_id));
+                let kind = if call.option.is_some() {
+                    EdgeKind::OptionFires
+                } else {
+                    EdgeKind::Fires
+                };
+
+                builder.edge(from, to, kind);
+            }
+        }
+
+        // The events outside the namespace which fire the events in it.
+        for event in index.events() {
+            if in_namespace(&event.id) {
+                continue;
+            }
+
+            for call in &event.calls {
+                if in_namespace(&call.event_id) && index.event(&call.event_id).is_some() {
+                    let from = builder.event_node(&event.id, true);
+                    let to = builder.event_node(&call.event_id, false);
+                    let kind = if call.option.is_some() {
+                        EdgeKind::OptionFires
+                    } else {
+                        EdgeKind::Fires
+                    };
+
+                    builder.edge(from, to, kind);
+                }
+            }
+        }
+
+        for on_action in index.on_actions() {
+            for call in &on_action.calls {
+                if in_namespace(&call.event_id) && index.event(&call.event_id).is_some() {
+                    let from = builder.node(
+                        NodeKind::OnAction,
+                        &on_action.name,
+                        Some(&on_action.location),
+                        false,
+                    );
+                    let to = builder.event_node(&call.event_id, false);
+
+                    builder.edge(from, to, EdgeKind::OnAction);
+                }
+            }
+        }
+
+        for decision in index.decisions() {
+            for call in &decision.calls {
+                if in_namespace(&call.event_id) && index.event(&call.event_id).is_some() {
+                    let from = builder.node(
+                        NodeKind::Decision,
+                        &decision.name,
+                        Some(&decision.location),
+                        false,
+                    );
+                    let to = builder.event_node(&call.event_id, false);
+
+                    builder.edge(from, to, EdgeKind::Fires);
+                }
+            }
+        }
+
+        let mut graph = builder.graph;
+        graph.layout();
+        graph
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| node.column + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| node.row + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Puts every node one column right of the rightmost node which leads to it, and orders the
+    // nodes of a column by the rows of the nodes leading to them, so that the edges cross less.
+    fn layout(&mut self) {
+        let node_count = self.nodes.len();
+        let mut outgoing = vec![Vec::new(); node_count];
+        let mut incoming = vec![Vec::new(); node_count];
+
+        for (idx, edge) in self.edges.iter().enumerate() {
+            if edge.from != edge.to {
+                outgoing[edge.from].push(idx);
+            }
+        }
+
+        // Finds the edges which close cycles with a depth-first search.
+        let mut is_back_edge = vec![false; self.edges.len()];
+        let mut state = vec![0u8; node_count]; // 0: unvisited, 1: on the stack, 2: done
+
+        for start in 0..node_count {
+            if state[start] != 0 {
+                continue;
+            }
+
+            let mut stack = vec![(start, 0)];
+            state[start] = 1;
+
+            while let Some((node, next_edge)) = stack.last_mut() {
+                let node = *node;
+
+                let Some(&edge_idx) = outgoing[node].get(*next_edge) else {
+                    state[node] = 2;
+                    stack.pop();
+                    continue;
+                };
+
+                *next_edge += 1;
+
+                let to = self.edges[edge_idx].to;
+
+                match state[to] {
+                    0 => {
+                        state[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    1 => is_back_edge[edge_idx] = true,
+                    _ => {}
+                }
+            }
+        }
+
+        for (idx, edge) in self.edges.iter().enumerate() {
+            if edge.from != edge.to && !is_back_edge[idx] {
+                incoming[edge.to].push(edge.from);
+            }
+        }
+
+        // Longest paths in topological order.
+        let mut remaining: Vec<_> = incoming.iter().map(Vec::len).collect();
+        let mut order: Vec<_> = (0..node_count).filter(|&idx| remaining[idx] == 0).collect();
+        let mut next = 0;
+
+        while let Some(&node) = order.get(next) {
+            next += 1;
+
+            for &edge_idx in &outgoing[node] {
+                if is_back_edge[edge_idx] {
+                    continue;
+                }
+
+                let to = self.edges[edge_idx].to;
+                self.nodes[to].column = self.nodes[to].column.max(self.nodes[node].column + 1);
+                remaining[to] -= 1;
+
+                if remaining[to] == 0 {
+                    order.push(to);
+                }
+            }
+        }
+
+        let mut columns = vec![Vec::new(); self.column_count()];
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            columns[node.column].push(idx);
+        }
+
+        for column in columns {
+            let mut keyed: Vec<_> = column
+                .into_iter()
+                .map(|idx| {
+                    let rows: Vec<_> = incoming[idx]
+                        .iter()
+                        .map(|&from| self.nodes[from].row as f32)
+                        .collect();
+                    let barycenter = if rows.is_empty() {
+                        f32::MAX
+                    } else {
+                        rows.iter().sum::<f32>() / rows.len() as f32
+                    };
+
+                    (barycenter, idx)
+                })
+                .collect();
+
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+            for (row, (_, idx)) in keyed.into_iter().enumerate() {
+                self.nodes[idx].row = row;
+            }
+        }
+    }
+}
+
+struct Builder<'a> {
+    index: &'a EventIndex,
+    graph: EventGraph,
+    node_indices: HashMap<(NodeKind, String), usize>,
+    edges: HashSet<GraphEdge>,
+}
+
+impl Builder<'_> {
+    fn event_node(&mut self, id: &str, is_external: bool) -> usize {
+        let location = self.index.event(id).map(|event| &event.location);
+        self.node(NodeKind::Event, id, location, is_external)
+    }
+
+    fn node(
+        &mut self,
+        kind: NodeKind,
+        name: &str,
+        location: Option<&Location>,
+        is_external: bool,
+    ) -> usize {
+        let nodes = &mut self.graph.nodes;
+
+        *self
+            .node_indices
+            .entry((kind, name.to_owned()))
+            .or_insert_with(|| {
+                nodes.push(GraphNode {
+                    kind,
+                    name: name.to_owned(),
+                    location: location.cloned(),
+                    is_external,
+                    column: 0,
+                    row: 0,
+                });
+                nodes.len() - 1
+            })
+    }
+
+    fn edge(&mut self, from: usize, to: usize, kind: EdgeKind) {
+        let edge = GraphEdge { from, to, kind };
+
+        if self.edges.insert(edge) {
+            self.graph.edges.push(edge);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_are_laid_out_from_left_to_right() {
+        let node = |kind, name: &str| GraphNode {
+            kind,
+            name: name.to_owned(),
+            location: None,
+            is_external: false,
+            column: 0,
+            row: 0,
+        };
+        let edge = |from, to, kind| GraphEdge { from, to, kind };
+
+        // on_startup -> a -> b -> c -> a, and a -> c
+        let mut graph = EventGraph {
+            nodes: vec![
+                node(NodeKind::Event, "a"),
+                node(NodeKind::Event, "b"),
+                node(NodeKind::Event, "c"),
+                node(NodeKind::OnAction, "on_startup"),
+            ],
+            edges: vec![
+                edge(0, 1, EdgeKind::OptionFires),
+                edge(1, 2, EdgeKind::Fires),
+                edge(2, 0, EdgeKind::Fires),
+                edge(0, 2, EdgeKind::Fires),
+                edge(3, 0, EdgeKind::OnAction),
+            ],
+        };
+        graph.layout();
+
+        assert_eq!(
+            graph
+                .nodes
+                .iter()
+                .map(|node| (node.name.as_str(), node.column))
+                .collect::<Vec<_>>(),
+            [("a", 1), ("b", 2), ("c", 3), ("on_startup", 0)]
+        );
+        assert_eq!((graph.column_count(), graph.row_count()), (4, 1));
+    }
+}
@@ -1,7 +1,80 @@
+mod decision_view;
+mod define_view;
+mod event_graph;
+mod event_view;
+mod file_view;
+mod highlight;
+mod history_view;
+mod localization_table;
+mod modifier_view;
+mod scripted_view;
+mod session;
+mod tree_view;
+mod workspace;
+
 use crate::core::*;
-use crate::database::{self, dir};
+use crate::database::{self, cache::Cache, cache::CachedDatabase};
+use crate::export::{Export, ExportFormat};
+use crate::import::{ImportPreview, ImportedKey};
 use eframe::egui;
-use std::path::{Path, PathBuf};
+use localization_table::LocalizationTable;
+use session::{Session, Sessions};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tree_view::{TreeStats, TreeView};
+use workspace::{Tab, Workspace, WorkspaceContext};
+
+#[derive(Default)]
+struct NewKeyDialog {
+    key: String,
+    value: String,
+    languages: Vec<database::Language>,
+    file_name: String,
+    allow_override: bool,
+
+    file_names: Vec<String>,
+    error: Option<Error>,
+}
+
+struct ImportDialog {
+    path: PathBuf,
+    language: database::Language,
+    source_language: database::Language,
+    include_conflicts: bool,
+
+    preview: Option<ImportPreview>,
+    error: Option<Error>,
+}
+
+#[derive(Default)]
+struct ExportDialog {
+    languages: Vec<database::Language>,
+    filter: String,
+    format: ExportFormat,
+
+    error: Option<Error>,
+}
+
+#[derive(Default)]
+struct SaveSessionDialog {
+    name: String,
+
+    error: Option<Error>,
+}
+
+// Edits the walk options of the current session; they're applied by indexing the directories
+// again.
+struct IndexSettingsDialog {
+    follow_symlinks: bool,
+    // One pattern per line.
+    ignore_patterns: String,
+}
+
+// Lists the cached databases of all workspaces.
+struct CacheWindow {
+    databases: Result<Vec<CachedDatabase>>,
+}
 
 #[derive(Default, serde::Deserialize, serde::Serialize)]
 #[serde(default)]
@@ -11,41 +84,134 @@ pub struct Explorer {
     #[serde(skip)]
     error: Option<Error>,
     #[serde(skip)]
-    cached_localization_keys: Option<Vec<database::models::LocalizationKeyKeyValue>>,
+    localization_table: Option<LocalizationTable>,
+    #[serde(skip)]
+    tree_view: TreeView,
+    #[serde(skip)]
+    workspace: Workspace,
 
-    selected_language: database::Language,
-    persistent_string: String,
+    #[serde(skip)]
+    new_key_dialog: Option<NewKeyDialog>,
+    #[serde(skip)]
+    export_dialog: Option<ExportDialog>,
+    #[serde(skip)]
+    import_dialog: Option<ImportDialog>,
+    #[serde(skip)]
+    save_session_dialog: Option<SaveSessionDialog>,
+    #[serde(skip)]
+    index_settings_dialog: Option<IndexSettingsDialog>,
+    #[serde(skip)]
+    cache_window: Option<CacheWindow>,
+    #[serde(skip)]
+    search_query: String,
+    // Whether to ask if the last session should be reopened.
+    #[serde(skip)]
+    reopen_prompt: bool,
+
+    // The current session, which is reopened at startup.
+    session: Session,
+    // Name of the current session if it was saved or opened as a named session.
+    session_name: Option<String>,
+    sessions: Sessions,
 }
 
 impl Explorer {
     pub const APP_ID: &'static str = "pdx-explorer";
-    const DATABASE_FILE_NAME: &'static str = "db.sqlite3";
+    const CACHE_DIR_NAME: &'static str = "databases";
 
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // Try to restore the app state from previous session.
-        if let Some(storage) = cc.storage {
+        let mut explorer: Self = if let Some(storage) = cc.storage {
             eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
         } else {
             Default::default()
+        };
+
+        if !explorer.session.roots.is_empty() {
+            if explorer.sessions.reopen_last_session {
+                explorer.open_database();
+            } else {
+                explorer.reopen_prompt = true;
+            }
         }
+
+        explorer
     }
 
     fn storage_dir() -> Option<PathBuf> {
         eframe::storage_dir(Self::APP_ID)
     }
 
-    fn database_file_path() -> Option<PathBuf> {
-        Some(Self::storage_dir()?.join(Self::DATABASE_FILE_NAME))
+    fn cache() -> Option<Cache> {
+        Some(Cache::new(&Self::storage_dir()?.join(Self::CACHE_DIR_NAME)))
+    }
+
+    // Starts a new session with the given game and mods.
+    fn open_mod_stack(&mut self, roots: Vec<PathBuf>) {
+        self.store_session();
+        self.session = Session {
+            roots,
+            walk_options: self.session.walk_options.clone(),
+            selected_language: self.session.selected_language,
+            ..Default::default()
+        };
+        self.session_name = None;
+        self.open_database();
+    }
+
+    fn open_session(&mut self, name: &str) {
+        let Some(session) = self.sessions.named.get(name).cloned() else {
+            return;
+        };
+
+        self.store_session();
+        self.session = session;
+        self.session_name = Some(name.to_owned());
+        self.open_database();
+    }
+
+    // Keeps the named session up to date with the changes made since it was opened.
+    fn store_session(&mut self) {
+        if let Some(name) = &self.session_name {
+            self.sessions
+                .named
+                .insert(name.clone(), self.session.clone());
+        }
+    }
+
+    // Opens the database of the current session, indexing the directories if they're not cached.
+    fn open_database(&mut self) {
+        self.load_database(false);
     }
 
-    fn set_directory(&mut self, path: &Path) {
-        let Some(db_path) = Self::database_file_path() else {
+    // Indexes the directories of the current session again.
+    fn rebuild_database(&mut self) {
+        self.load_database(true);
+    }
+
+    fn close_database(&mut self) {
+        self.database = None;
+        self.localization_table = None;
+        self.tree_view = TreeView::default();
+        self.workspace.invalidate();
+    }
+
+    fn load_database(&mut self, rebuild: bool) {
+        self.close_database();
+
+        let Some(cache) = Self::cache() else {
             self.error
-                .replace(error!("failed to obtain a path to the database file"));
+                .replace(error!("failed to obtain a path to the database directory"));
             return;
         };
 
-        let db = match database::Database::new(path, &db_path) {
+        let db = if rebuild {
+            cache.rebuild(&self.session.roots, &self.session.walk_options)
+        } else {
+            cache.open(&self.session.roots, &self.session.walk_options)
+        };
+
+        let db = match db {
             Ok(db) => db,
             Err(error) => {
                 self.error.replace(error);
@@ -53,25 +219,60 @@ impl Explorer {
             }
         };
 
+        self.error = None;
         self.database.replace(db);
+        self.sessions.add_recent(&self.session.roots);
+        self.refresh_cache_window();
+        self.reload();
+    }
 
-        if let Err(error) = self.load_localization_keys() {
+    // Rebuilds everything which is derived from the database, after it was opened or changed.
+    fn reload(&mut self) {
+        self.workspace.invalidate();
+
+        if let Err(error) = self
+            .load_tree_view()
+            .and_then(|()| self.load_localization_keys())
+        {
             self.error.replace(error);
         }
     }
 
+    fn load_tree_view(&mut self) -> Result<()> {
+        let Some(db) = &mut self.database else {
+            fail!("cannot load the directory tree; database is not opened");
+        };
+
+        let mut parse_errors: HashMap<u64, Vec<String>> = HashMap::new();
+
+        for (error, _) in db.get_parse_errors()? {
+            parse_errors
+                .entry(error.file_id as u64)
+                .or_default()
+                .push(error.message);
+        }
+
+        let stats = TreeStats {
+            localization_key_counts: db.get_localization_key_counts()?,
+            parse_errors,
+        };
+
+        self.tree_view.set_dir_trees(db.dir_trees(), stats);
+        Ok(())
+    }
+
     fn load_localization_keys(&mut self) -> Result<()> {
         let Some(db) = &mut self.database else {
             fail!("cannot load localization keys; database is not opened");
         };
 
-        match db.get_localization_keys_for_language(self.selected_language) {
+        match db.get_localization_keys_for_language(self.session.selected_language) {
             Ok(keys) => {
-                self.cached_localization_keys = Some(keys);
+                self.localization_table = Some(LocalizationTable::new(keys));
                 Ok(())
             }
             Err(error) => {
-                self.cached_localization_keys = None;
+                self.localization_table = None;
                 Err(error)
             }
         }
@@ -97,6 +298,14 @@ impl Explorer {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.central_panel_content(ui);
         });
+
+        self.new_key_window(ctx);
+        self.export_window(ctx);
+        self.import_window(ctx);
+        self.save_session_window(ctx);
+        self.index_settings_window(ctx);
+        self.cache_window(ctx);
+        self.reopen_window(ctx);
     }
 
     fn top_panel_content(&mut self, ui: &mut egui::Ui) {
@@ -105,120 +314,987 @@ impl Explorer {
 
             ui.separator();
 
-            if ui.button("Open Game/Mod Directory").clicked()
-                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            ui.menu_button("Workspace", |ui| {
+                self.workspace_menu(ui);
+            });
+
+            if let Some(db) = &mut self.database {
+                if ui.button("New Key").clicked() {
+                    let file_names = db.localization_file_names().unwrap_or_default();
+
+                    self.new_key_dialog.replace(NewKeyDialog {
+                        languages: vec![self.session.selected_language],
+                        file_name: file_names.first().cloned().unwrap_or_default(),
+                        file_names,
+                        ..Default::default()
+                    });
+                }
+
+                if ui.button("Export").clicked() {
+                    self.export_dialog.replace(ExportDialog {
+                        languages: vec![self.session.selected_language],
+                        filter: self.session.localization_filter.clone(),
+                        ..Default::default()
+                    });
+                }
+
+                if ui.button("Import").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter(
+                            "Spreadsheet",
+                            &ExportFormat::values()
+                                .iter()
+                                .map(ExportFormat::extension)
+                                .collect::<Vec<_>>(),
+                        )
+                        .pick_file()
+                {
+                    self.import_dialog.replace(ImportDialog {
+                        path,
+                        language: self.session.selected_language,
+                        source_language: database::Language::English,
+                        include_conflicts: false,
+                        preview: None,
+                        error: None,
+                    });
+                }
+
+                ui.separator();
+
+                if ui.button("Localization").clicked() {
+                    self.session.tabs.open(Tab::Localization);
+                }
+
+                if ui.button("Events").clicked() {
+                    self.session.tabs.open(Tab::Events);
+                }
+
+                if ui.button("Event Graph").clicked() {
+                    self.session.tabs.open(Tab::EventGraph);
+                }
+
+                if ui.button("Decisions").clicked() {
+                    self.session.tabs.open(Tab::Decisions);
+                }
+
+                if ui.button("Modifiers").clicked() {
+                    self.session.tabs.open(Tab::Modifiers);
+                }
+
+                if ui.button("Scripted").clicked() {
+                    self.session.tabs.open(Tab::Scripted);
+                }
+
+                if ui.button("Defines").clicked() {
+                    self.session.tabs.open(Tab::Defines);
+                }
+
+                if ui.button("History").clicked() {
+                    self.session.tabs.open(Tab::History);
+                }
+
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query)
+                        .hint_text("Search files and keys")
+                        .desired_width(200.0),
+                );
+
+                if search.lost_focus()
+                    && ui.input(|input| input.key_pressed(egui::Key::Enter))
+                    && !self.search_query.trim().is_empty()
+                {
+                    self.session.tabs.open(Tab::Search {
+                        query: self.search_query.trim().to_owned(),
+                    });
+                }
+
+                ui.separator();
+
+                let title = match &self.session_name {
+                    Some(name) => format!("Session: {}", name),
+                    None => format!("Session: {}", Session::title(&self.session.roots)),
+                };
+                let roots: Vec<_> = db
+                    .dir_trees()
+                    .iter()
+                    .map(|dir_tree| {
+                        let layer = format!(
+                            "{} ({})",
+                            dir_tree.layer_name(),
+                            dir_tree.root_path().display()
+                        );
+
+                        match dir_tree.workshop_id() {
+                            Some(id) => format!("{} [Workshop {}]", layer, id),
+                            None => layer,
+                        }
+                    })
+                    .collect();
+
+                ui.label(title).on_hover_text(roots.join("\n"));
+            }
+        });
+    }
+
+    fn workspace_menu(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Open Game/Mod Directory...").clicked() {
+            ui.close();
+
+            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                self.open_mod_stack(vec![path]);
+            }
+        }
+
+        if self.database.is_some()
+            && ui
+                .button("Add Mod Directory...")
+                .on_hover_text("Load a mod on top of the open directories.")
+                .clicked()
+        {
+            ui.close();
+
+            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                self.session.roots.push(path);
+                self.open_database();
+            }
+        }
+
+        if self.database.is_some()
+            && ui
+                .button("Add Mod Archive...")
+                .on_hover_text(
+                    "Load a zipped mod on top of the open directories without extracting it.",
+                )
+                .clicked()
+        {
+            ui.close();
+
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Zip archive", &["zip"])
+                .pick_file()
             {
-                self.set_directory(&path);
+                self.session.roots.push(path);
+                self.open_database();
             }
+        }
 
-            if let Some(db) = &self.database {
-                ui.label(format!(
-                    "Selected path: {}",
-                    db.dir_tree().root_path().display()
-                ));
+        if self.database.is_some()
+            && ui
+                .button("Rebuild Index")
+                .on_hover_text("Index the open directories again, e.g. after they were changed.")
+                .clicked()
+        {
+            ui.close();
+            self.rebuild_database();
+        }
+
+        if ui
+            .button("Index Settings...")
+            .on_hover_text("Choose which files are indexed.")
+            .clicked()
+        {
+            ui.close();
+            self.index_settings_dialog.replace(IndexSettingsDialog {
+                follow_symlinks: self.session.walk_options.follow_symlinks,
+                ignore_patterns: self.session.walk_options.ignore_patterns.join("\n"),
+            });
+        }
+
+        if ui.button("Manage Indexes...").clicked() {
+            ui.close();
+            self.cache_window = Some(CacheWindow {
+                databases: Ok(Vec::new()),
+            });
+            self.refresh_cache_window();
+        }
+
+        ui.separator();
+
+        ui.menu_button("Recent", |ui| {
+            if self.sessions.recent.is_empty() {
+                ui.weak("Nothing opened yet");
+            }
+
+            let mut clicked = None;
+
+            for roots in &self.sessions.recent {
+                let paths: Vec<_> = roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect();
+
+                if ui
+                    .button(Session::title(roots))
+                    .on_hover_text(paths.join("\n"))
+                    .clicked()
+                {
+                    clicked = Some(roots.clone());
+                }
+            }
+
+            if let Some(roots) = clicked {
+                ui.close();
+                self.open_mod_stack(roots);
             }
         });
-    }
 
-    fn bottom_panel_content(&mut self, ui: &mut egui::Ui) {
-        if let Some(error) = &self.error {
-            ui.colored_label(
-                egui::Color32::YELLOW,
-                format!("Error: {}.", error.description()),
-            );
+        ui.menu_button("Sessions", |ui| {
+            if self.sessions.named.is_empty() {
+                ui.weak("No saved sessions");
+            }
+
+            let mut opened = None;
+            let mut deleted = None;
+
+            for (name, session) in &self.sessions.named {
+                ui.horizontal(|ui| {
+                    if ui
+                        .selectable_label(self.session_name.as_ref() == Some(name), name)
+                        .on_hover_text(Session::title(&session.roots))
+                        .clicked()
+                    {
+                        opened = Some(name.clone());
+                    }
+
+                    if ui.small_button("Delete").clicked() {
+                        deleted = Some(name.clone());
+                    }
+                });
+            }
+
+            if let Some(name) = opened {
+                ui.close();
+                self.open_session(&name);
+            }
+
+            if let Some(name) = deleted {
+                self.sessions.named.remove(&name);
+
+                if self.session_name == Some(name) {
+                    self.session_name = None;
+                }
+            }
+        });
+
+        if ui.button("Save Session As...").clicked() {
+            ui.close();
+            self.save_session_dialog.replace(SaveSessionDialog {
+                name: self.session_name.clone().unwrap_or_default(),
+                ..Default::default()
+            });
         }
+
+        ui.separator();
+
+        ui.checkbox(
+            &mut self.sessions.reopen_last_session,
+            "Reopen the last session at startup",
+        );
     }
 
-    fn dir_tree(ui: &mut egui::Ui, node: &dir::Node) {
-        match node {
-            dir::Node::Directory(dir) => {
-                egui::CollapsingHeader::new(format!(
-                    "{} (ct: {}, id: {})",
-                    dir.dir_name().display(),
-                    dir.content_type(),
-                    dir.id()
-                ))
-                .show(ui, |ui| {
-                    for child in dir.children() {
-                        Self::dir_tree(ui, child);
-                    }
+    fn save_session_window(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.save_session_dialog.take() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut save_clicked = false;
+        let mut cancel_clicked = false;
+
+        egui::Window::new("Save Session")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut dialog.name);
+                });
+
+                if self.sessions.named.contains_key(dialog.name.trim()) {
+                    ui.label("The saved session with this name will be replaced.");
+                }
+
+                ui.horizontal(|ui| {
+                    save_clicked = ui.button("Save").clicked();
+                    cancel_clicked = ui.button("Cancel").clicked();
                 });
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("Error: {}.", error.description()),
+                    );
+                }
+            });
+
+        if save_clicked {
+            let name = dialog.name.trim();
+
+            if name.is_empty() {
+                dialog.error.replace(error!("the name cannot be empty"));
+            } else {
+                self.session_name = Some(name.to_owned());
+                self.store_session();
+                return;
             }
-            dir::Node::File(file) => {
-                ui.label(format!(
-                    "{} (ct: {}, id: {})",
-                    file.file_name().display(),
-                    file.content_type(),
-                    file.id()
+        }
+
+        if open && !cancel_clicked {
+            self.save_session_dialog.replace(dialog);
+        }
+    }
+
+    fn index_settings_window(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.index_settings_dialog.take() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply_clicked = false;
+        let mut cancel_clicked = false;
+
+        egui::Window::new("Index Settings")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut dialog.follow_symlinks, "Follow symbolic links")
+                    .on_hover_text("Links which would form a cycle are always skipped.");
+
+                ui.label("Ignored files and directories");
+                ui.add(
+                    egui::TextEdit::multiline(&mut dialog.ignore_patterns)
+                        .hint_text("One gitignore-style pattern per line, e.g. *.bak or /gfx/")
+                        .code_editor(),
+                );
+                ui.weak(format!(
+                    "Version control and editor files are always ignored. Patterns can also be \
+                     put in a {} file in the game or mod directory.",
+                    database::ignore::IgnoreRules::FILE_NAME
                 ));
+
+                ui.horizontal(|ui| {
+                    apply_clicked = ui.button("Apply").clicked();
+                    cancel_clicked = ui.button("Cancel").clicked();
+                });
+            });
+
+        if apply_clicked {
+            self.session.walk_options = database::dir::WalkOptions {
+                follow_symlinks: dialog.follow_symlinks,
+                ignore_patterns: dialog
+                    .ignore_patterns
+                    .lines()
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+            };
+
+            if self.database.is_some() {
+                self.open_database();
             }
+
+            return;
+        }
+
+        if open && !cancel_clicked {
+            self.index_settings_dialog.replace(dialog);
         }
     }
 
-    fn left_panel_content(&mut self, ui: &mut egui::Ui) {
-        ui.text_edit_singleline(&mut self.persistent_string)
-            .on_hover_text("The value in this field should persist.");
+    fn refresh_cache_window(&mut self) {
+        let Some(cache_window) = &mut self.cache_window else {
+            return;
+        };
 
-        if let Some(db) = &self.database {
-            Self::dir_tree(ui, db.dir_tree().root());
-        }
+        cache_window.databases = match Self::cache() {
+            Some(cache) => cache.list(),
+            None => Err(error!("failed to obtain a path to the database directory")),
+        };
     }
 
-    fn right_panel_content(&mut self, ui: &mut egui::Ui) {
-        let mut language_changed = false;
+    fn cache_window(&mut self, ctx: &egui::Context) {
+        let Some(cache_window) = &self.cache_window else {
+            return;
+        };
 
-        for value in database::Language::values() {
-            if ui
-                .selectable_value(&mut self.selected_language, *value, value.name())
-                .changed()
+        let current_path = Self::cache().and_then(|cache| {
+            self.database
+                .as_ref()
+                .and_then(|_| cache.database_file_path(&self.session.roots).ok())
+        });
+
+        let mut open = true;
+        let mut opened = None;
+        let mut rebuilt = None;
+        let mut deleted = None;
+
+        egui::Window::new("Indexes")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let databases = match &cache_window.databases {
+                    Ok(databases) => databases,
+                    Err(error) => {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            format!("Error: {}.", error.description()),
+                        );
+                        return;
+                    }
+                };
+
+                if databases.is_empty() {
+                    ui.weak("No directories were indexed yet.");
+                    return;
+                }
+
+                egui::Grid::new("indexes grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for title in ["Workspace", "Size", "Updated", ""] {
+                            ui.strong(title);
+                        }
+                        ui.end_row();
+
+                        for database in databases {
+                            let is_current = current_path.as_ref() == Some(&database.path);
+
+                            match &database.roots {
+                                Ok(roots) => {
+                                    let paths: Vec<_> = roots
+                                        .iter()
+                                        .map(|root| root.display().to_string())
+                                        .collect();
+                                    let mut title = egui::RichText::new(Session::title(roots));
+
+                                    if is_current {
+                                        title = title.strong();
+                                    }
+
+                                    ui.label(title).on_hover_text(paths.join("\n"));
+                                }
+                                Err(error) => {
+                                    ui.colored_label(ui.visuals().warn_fg_color, "Unreadable")
+                                        .on_hover_text(error.description());
+                                }
+                            }
+
+                            ui.label(format_size(database.size));
+                            ui.label(database.modified.map(format_age).unwrap_or_default())
+                                .on_hover_text(database.path.display().to_string());
+
+                            ui.horizontal(|ui| {
+                                if let Ok(roots) = &database.roots {
+                                    if !is_current && ui.button("Open").clicked() {
+                                        opened = Some(roots.clone());
+                                    }
+
+                                    if ui.button("Rebuild").clicked() {
+                                        rebuilt = Some((
+                                            roots.clone(),
+                                            database.walk_options.clone(),
+                                            is_current,
+                                        ));
+                                    }
+                                }
+
+                                if ui.button("Delete").clicked() {
+                                    deleted = Some((database.path.clone(), is_current));
+                                }
+                            });
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if !open {
+            self.cache_window = None;
+            return;
+        }
+
+        if let Some(roots) = opened {
+            self.open_mod_stack(roots);
+        }
+
+        if let Some((roots, walk_options, is_current)) = rebuilt {
+            if is_current {
+                self.rebuild_database();
+            } else if let Some(cache) = Self::cache()
+                && let Err(error) = cache.rebuild(&roots, &walk_options)
             {
-                language_changed = true;
+                self.error.replace(error);
             }
         }
 
-        if language_changed && self.database.is_some() {
-            if let Err(error) = self.load_localization_keys() {
+        if let Some((path, is_current)) = deleted {
+            if is_current {
+                self.close_database();
+            }
+
+            if let Some(cache) = Self::cache()
+                && let Err(error) = cache.delete(&path)
+            {
                 self.error.replace(error);
             }
         }
+
+        self.refresh_cache_window();
     }
 
-    fn central_panel_content(&mut self, ui: &mut egui::Ui) {
-        let Some(localization_keys) = &self.cached_localization_keys else {
+    fn reopen_window(&mut self, ctx: &egui::Context) {
+        if !self.reopen_prompt {
             return;
-        };
+        }
+
+        let mut reopen_clicked = false;
+        let mut dismiss_clicked = false;
+
+        egui::Window::new("Reopen Last Session")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let title = match &self.session_name {
+                    Some(name) => name.clone(),
+                    None => Session::title(&self.session.roots),
+                };
+
+                ui.label(format!("Reopen {}?", title));
+
+                for root in &self.session.roots {
+                    ui.weak(root.display().to_string());
+                }
 
-        let available_height = ui.available_height();
+                ui.checkbox(
+                    &mut self.sessions.reopen_last_session,
+                    "Always reopen the last session at startup",
+                );
 
-        egui_extras::TableBuilder::new(ui)
-            .column(egui_extras::Column::auto())
-            .column(egui_extras::Column::auto())
-            .striped(false)
-            .resizable(true)
-            .max_scroll_height(available_height)
-            .header(20.0, |mut header| {
-                header.col(|ui| {
-                    ui.strong("Key");
+                ui.horizontal(|ui| {
+                    reopen_clicked = ui.button("Reopen").clicked();
+                    dismiss_clicked = ui.button("Not Now").clicked();
                 });
-                header.col(|ui| {
-                    ui.strong("Value");
+            });
+
+        if reopen_clicked {
+            self.reopen_prompt = false;
+            self.open_database();
+        } else if dismiss_clicked {
+            self.reopen_prompt = false;
+        }
+    }
+
+    fn new_key_window(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.new_key_dialog.take() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut create_clicked = false;
+        let mut cancel_clicked = false;
+
+        egui::Window::new("New Localization Key")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("new key grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Key");
+                        ui.text_edit_singleline(&mut dialog.key);
+                        ui.end_row();
+
+                        ui.label("Value");
+                        ui.text_edit_multiline(&mut dialog.value);
+                        ui.end_row();
+
+                        ui.label("Languages");
+                        ui.horizontal_wrapped(|ui| {
+                            for language in database::Language::values() {
+                                let mut selected = dialog.languages.contains(language);
+
+                                if ui.checkbox(&mut selected, language.name()).changed() {
+                                    if selected {
+                                        dialog.languages.push(*language);
+                                    } else {
+                                        dialog.languages.retain(|value| value != language);
+                                    }
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("File");
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_salt("new key file")
+                                .selected_text("Existing")
+                                .show_ui(ui, |ui| {
+                                    for file_name in &dialog.file_names {
+                                        ui.selectable_value(
+                                            &mut dialog.file_name,
+                                            file_name.clone(),
+                                            file_name,
+                                        );
+                                    }
+                                });
+
+                            ui.text_edit_singleline(&mut dialog.file_name)
+                                .on_hover_text("Type a new name to create a new file.");
+                        });
+                        ui.end_row();
+                    });
+
+                if let Some(db) = &self.database {
+                    for language in &dialog.languages {
+                        let (relative_path, exists) =
+                            db.localization_file_for_new_keys(*language, &dialog.file_name);
+
+                        if exists {
+                            ui.label(format!("Append to {}", relative_path.display()));
+                        } else {
+                            ui.label(format!("Create {}", relative_path.display()));
+                        }
+                    }
+                }
+
+                ui.checkbox(&mut dialog.allow_override, "Override existing keys")
+                    .on_hover_text(
+                        "Existing keys are changed where they're defined, or overridden in the \
+                         replace folder if a layer below defines them.",
+                    );
+
+                ui.horizontal(|ui| {
+                    create_clicked = ui.button("Create").clicked();
+                    cancel_clicked = ui.button("Cancel").clicked();
                 });
-            })
-            .body(|mut body| {
-                for localization_key in localization_keys {
-                    let key = &localization_key.key;
-                    let value = &localization_key.value;
-
-                    body.row(20.0, |mut row| {
-                        row.col(|ui| {
-                            ui.label(key);
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("Error: {}.", error.description()),
+                    );
+                }
+            });
+
+        if create_clicked && let Some(db) = &mut self.database {
+            match db.create_localization_key(
+                &dialog.key,
+                &dialog.value,
+                &dialog.languages,
+                &dialog.file_name,
+                dialog.allow_override,
+            ) {
+                Ok(()) => {
+                    self.reload();
+                    return;
+                }
+                Err(error) => {
+                    dialog.error.replace(error);
+                }
+            }
+        }
+
+        if open && !cancel_clicked {
+            self.new_key_dialog.replace(dialog);
+        }
+    }
+
+    fn export_window(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.export_dialog.take() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut export_clicked = false;
+        let mut cancel_clicked = false;
+
+        egui::Window::new("Export Localization")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("export grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Languages");
+                        ui.horizontal_wrapped(|ui| {
+                            for language in database::Language::values() {
+                                let mut selected = dialog.languages.contains(language);
+
+                                if ui.checkbox(&mut selected, language.name()).changed() {
+                                    if selected {
+                                        dialog.languages.push(*language);
+                                    } else {
+                                        dialog.languages.retain(|value| value != language);
+                                    }
+                                }
+                            }
                         });
-                        row.col(|ui| {
-                            ui.label(value);
+                        ui.end_row();
+
+                        ui.label("Filter");
+                        ui.text_edit_singleline(&mut dialog.filter);
+                        ui.end_row();
+
+                        ui.label("Format");
+                        ui.horizontal(|ui| {
+                            for format in ExportFormat::values() {
+                                ui.selectable_value(&mut dialog.format, *format, format.name());
+                            }
                         });
+                        ui.end_row();
                     });
+
+                ui.horizontal(|ui| {
+                    export_clicked = ui.button("Export...").clicked();
+                    cancel_clicked = ui.button("Cancel").clicked();
+                });
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("Error: {}.", error.description()),
+                    );
+                }
+            });
+
+        if export_clicked
+            && let Some(db) = &mut self.database
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter(dialog.format.name(), &[dialog.format.extension()])
+                .set_file_name(format!("localization.{}", dialog.format.extension()))
+                .save_file()
+        {
+            // Languages are exported in the order in which they're listed, not the order in which
+            // they were selected.
+            let languages: Vec<_> = database::Language::values()
+                .iter()
+                .copied()
+                .filter(|language| dialog.languages.contains(language))
+                .collect();
+
+            match Export::new(db, &languages, &dialog.filter)
+                .and_then(|export| export.write(&path, dialog.format))
+            {
+                Ok(()) => return,
+                Err(error) => {
+                    dialog.error.replace(error);
+                }
+            }
+        }
+
+        if open && !cancel_clicked {
+            self.export_dialog.replace(dialog);
+        }
+    }
+
+    fn import_window(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.import_dialog.take() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut preview_clicked = false;
+        let mut apply_clicked = false;
+        let mut cancel_clicked = false;
+
+        egui::Window::new("Import Localization")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("import grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("File");
+                        ui.label(dialog.path.display().to_string());
+                        ui.end_row();
+
+                        for (label, language) in [
+                            ("Language", &mut dialog.language),
+                            ("Source language", &mut dialog.source_language),
+                        ] {
+                            ui.label(label);
+                            egui::ComboBox::from_id_salt(label)
+                                .selected_text(language.name())
+                                .show_ui(ui, |ui| {
+                                    for value in database::Language::values() {
+                                        if ui.selectable_value(language, *value, value.name()).changed() {
+                                            dialog.preview = None;
+                                        }
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(preview) = &dialog.preview {
+                    ui.separator();
+
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("import preview grid")
+                                .num_columns(4)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for change in preview.changes() {
+                                        ui.label(change.kind.name());
+                                        ui.label(&change.key);
+                                        ui.label(change.old_value.as_deref().unwrap_or_default());
+                                        ui.label(change.new_value.as_deref().unwrap_or_default());
+
+                                        if let Some(conflict) = &change.conflict {
+                                            ui.colored_label(egui::Color32::YELLOW, "Conflict")
+                                                .on_hover_text(format!(
+                                                    "The source changed from \"{}\" to \"{}\" after the export.",
+                                                    conflict.exported_source_value,
+                                                    conflict.current_source_value
+                                                ));
+                                        }
+
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+
+                    ui.label(format!(
+                        "{} change(s), {} unchanged, {} conflict(s).",
+                        preview.changes().len(),
+                        preview.unchanged_count(),
+                        preview.conflict_count()
+                    ));
+
+                    ui.checkbox(&mut dialog.include_conflicts, "Apply conflicting changes");
+                }
+
+                ui.horizontal(|ui| {
+                    preview_clicked = ui.button("Preview").clicked();
+
+                    if dialog.preview.is_some() {
+                        apply_clicked = ui.button("Apply").clicked();
+                    }
+
+                    cancel_clicked = ui.button("Cancel").clicked();
+                });
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("Error: {}.", error.description()),
+                    );
+                }
+            });
+
+        if preview_clicked && let Some(db) = &mut self.database {
+            let preview =
+                ImportedKey::read(&dialog.path, None, dialog.language, dialog.source_language)
+                    .and_then(|keys| {
+                        ImportPreview::new(db, dialog.language, dialog.source_language, keys)
+                    });
+
+            match preview {
+                Ok(preview) => {
+                    dialog.preview.replace(preview);
+                    dialog.error = None;
+                }
+                Err(error) => {
+                    dialog.error.replace(error);
+                }
+            }
+        }
+
+        if apply_clicked
+            && let Some(db) = &mut self.database
+            && let Some(preview) = &dialog.preview
+        {
+            match preview.apply(db, dialog.include_conflicts) {
+                Ok(_) => {
+                    self.reload();
+                    return;
                 }
+                Err(error) => {
+                    dialog.error.replace(error);
+                }
+            }
+        }
+
+        if open && !cancel_clicked {
+            self.import_dialog.replace(dialog);
+        }
+    }
+
+    fn bottom_panel_content(&mut self, ui: &mut egui::Ui) {
+        if let Some(error) = &self.error {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("Error: {}.", error.description()),
+            );
+        }
+    }
+
+    fn left_panel_content(&mut self, ui: &mut egui::Ui) {
+        if self.database.is_none() {
+            return;
+        }
+
+        if let Some(db) = &self.database {
+            let warnings: Vec<_> = db
+                .dir_trees()
+                .iter()
+                .flat_map(|dir_tree| dir_tree.warnings())
+                .map(String::as_str)
+                .collect();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} entries skipped", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+                ui.separator();
+            }
+        }
+
+        if let Some((full_path, relative_path)) = self.tree_view.ui(ui, &mut self.session.tree) {
+            self.session.tabs.open(Tab::File {
+                full_path,
+                relative_path,
             });
+        }
+    }
+
+    fn right_panel_content(&mut self, ui: &mut egui::Ui) {
+        let mut language_changed = false;
+
+        for value in database::Language::values() {
+            if ui
+                .selectable_value(&mut self.session.selected_language, *value, value.name())
+                .changed()
+            {
+                language_changed = true;
+            }
+        }
+
+        if language_changed
+            && self.database.is_some()
+            && let Err(error) = self.load_localization_keys()
+        {
+            self.error.replace(error);
+        }
+    }
+
+    fn central_panel_content(&mut self, ui: &mut egui::Ui) {
+        self.workspace.ui(
+            ui,
+            &mut self.session.tabs,
+            WorkspaceContext {
+                database: &mut self.database,
+                localization_table: self.localization_table.as_mut(),
+                localization_filter: &mut self.session.localization_filter,
+                language: self.session.selected_language,
+            },
+        );
     }
 }
 
@@ -229,6 +1305,29 @@ impl eframe::App for Explorer {
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         // Save the app state.
+        self.store_session();
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 }
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn format_age(time: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+
+    match seconds {
+        0..60 => "just now".to_owned(),
+        60..3600 => format!("{} min ago", seconds / 60),
+        3600..86400 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
@@ -0,0 +1,343 @@
+use super::localization_table::LocalizationTable;
+use super::workspace::{ViewAction, link, location_link};
+use crate::core::*;
+use crate::database::Language;
+use crate::database::dir::DirTree;
+use crate::decisions::{DecisionIndex, Entry};
+use crate::script::{Block, Value};
+use eframe::egui;
+
+enum Row {
+    Group(String),
+    // The index of the entry in the index.
+    Entry(usize),
+}
+
+// Lists the decisions and missions with their localized names and shows the details of the
+// selected one. The names are looked up in the localization table of the selected language.
+pub struct DecisionView {
+    index: DecisionIndex,
+    // The names of the layers, by layer.
+    layer_names: Vec<String>,
+    filter: String,
+    rows: Vec<Row>,
+    // The filter and the language the rows were computed for.
+    rows_key: Option<(String, Language)>,
+    selected: Option<usize>,
+}
+
+impl DecisionView {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        Ok(Self {
+            index: DecisionIndex::new(dir_trees)?,
+            layer_names: dir_trees.iter().map(DirTree::layer_name).collect(),
+            filter: String::new(),
+            rows: Vec::new(),
+            rows_key: None,
+            selected: None,
+        })
+    }
+
+    // The localized name of the entry, or its name if it isn't localized.
+    fn title<'a>(entry: &'a Entry, localization: Option<&'a LocalizationTable>) -> &'a str {
+        Self::localized(&entry.title_keys(), localization)
+            .map_or(entry.name.as_str(), |(_, value)| value)
+    }
+
+    // The first of the keys which is localized, along with its value.
+    fn localized<'a>(
+        keys: &[String],
+        localization: Option<&'a LocalizationTable>,
+    ) -> Option<(usize, &'a str)> {
+        let localization = localization?;
+
+        keys.iter()
+            .enumerate()
+            .find_map(|(idx, key)| Some((idx, localization.value(key)?)))
+    }
+
+    fn layer_name(&self, layer: u32) -> &str {
+        self.layer_names
+            .get(layer as usize)
+            .map_or("", String::as_str)
+    }
+
+    fn update_rows(&mut self, localization: Option<&LocalizationTable>, language: Language) {
+        if self
+            .rows_key
+            .as_ref()
+            .is_some_and(|(filter, rows_language)| {
+                filter == &self.filter && *rows_language == language
+            })
+        {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let mut group = None;
+        let mut rows = Vec::new();
+
+        for (idx, entry) in self.index.entries().iter().enumerate() {
+            if !filter.is_empty()
+                && !entry.name.to_lowercase().contains(&filter)
+                && !Self::title(entry, localization)
+                    .to_lowercase()
+                    .contains(&filter)
+            {
+                continue;
+            }
+
+            let entry_group = (entry.kind, &entry.group);
+
+            if group != Some(entry_group) {
+                group = Some(entry_group);
+                rows.push(Row::Group(match &entry.group {
+                    Some(name) => format!("{}s: {}", entry.kind.name(), name),
+                    None => format!("{}s", entry.kind.name()),
+                }));
+            }
+
+            rows.push(Row::Entry(idx));
+        }
+
+        self.rows = rows;
+        self.rows_key = Some((self.filter.clone(), language));
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        localization: Option<&LocalizationTable>,
+        language: Language,
+    ) -> Option<ViewAction> {
+        let mut action = None;
+
+        egui::SidePanel::left("decision list")
+            .resizable(true)
+            .default_width(320.0)
+            .show_inside(ui, |ui| {
+                self.list(ui, localization, language);
+            });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    action = self.details(ui, localization);
+                });
+        });
+
+        action
+    }
+
+    fn list(
+        &mut self,
+        ui: &mut egui::Ui,
+        localization: Option<&LocalizationTable>,
+        language: Language,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+
+        self.update_rows(localization, language);
+
+        ui.horizontal(|ui| {
+            let entry_count = self
+                .rows
+                .iter()
+                .filter(|row| matches!(row, Row::Entry(_)))
+                .count();
+            ui.weak(format!(
+                "{} of {} entries",
+                entry_count,
+                self.index.entries().len()
+            ));
+
+            let warnings = self.index.warnings();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+            }
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+            ui,
+            Self::ROW_HEIGHT,
+            self.rows.len(),
+            |ui, range| {
+                for row in &self.rows[range] {
+                    match row {
+                        Row::Group(name) => {
+                            ui.strong(name);
+                        }
+                        Row::Entry(idx) => {
+                            let entry = &self.index.entries()[*idx];
+                            let title = Self::title(entry, localization);
+                            let text = if title == entry.name {
+                                entry.name.clone()
+                            } else {
+                                format!("{}  {}", title, entry.name)
+                            };
+
+                            if ui
+                                .add(
+                                    egui::Button::selectable(self.selected == Some(*idx), text)
+                                        .truncate(),
+                                )
+                                .clicked()
+                            {
+                                self.selected = Some(*idx);
+                            }
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    fn details(
+        &self,
+        ui: &mut egui::Ui,
+        localization: Option<&LocalizationTable>,
+    ) -> Option<ViewAction> {
+        let Some(entry) = self.selected.map(|idx| &self.index.entries()[idx]) else {
+            ui.weak("Select a decision or mission to see its details.");
+            return None;
+        };
+
+        let mut action = None;
+
+        ui.heading(Self::title(entry, localization));
+        ui.horizontal(|ui| {
+            ui.label(&entry.name);
+            ui.weak(match &entry.group {
+                Some(group) => format!("({} in {})", entry.kind.name(), group),
+                None => format!("({})", entry.kind.name()),
+            });
+        });
+
+        ui.separator();
+
+        egui::Grid::new(("decision localization", &entry.name))
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (label, keys) in [
+                    ("Name", entry.title_keys()),
+                    ("Description", entry.desc_keys()),
+                ] {
+                    ui.label(label);
+
+                    let (key, value) = match Self::localized(&keys, localization) {
+                        Some((idx, value)) => (&keys[idx], Some(value)),
+                        None => (&keys[0], None),
+                    };
+
+                    if link(ui, key).on_hover_text("Inspect").clicked() {
+                        action = Some(ViewAction::InspectKey(key.clone()));
+                    }
+
+                    match value {
+                        Some(value) => ui.label(value),
+                        None => ui.weak("(not localized)"),
+                    };
+
+                    ui.end_row();
+                }
+
+                ui.label("Defined in");
+                ui.label(self.layer_name(entry.location.layer));
+
+                if let Some(location_action) = location_link(ui, &entry.location) {
+                    action = Some(location_action);
+                }
+
+                ui.end_row();
+
+                for location in entry.overridden.iter().rev() {
+                    ui.label("Overrides");
+                    ui.label(self.layer_name(location.layer));
+
+                    if let Some(location_action) = location_link(ui, location) {
+                        action = Some(location_action);
+                    }
+
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        let mut has_sections = false;
+
+        for (key, block) in entry.sections() {
+            has_sections = true;
+
+            egui::CollapsingHeader::new(key)
+                .id_salt(("decision section", &entry.name, key))
+                .default_open(true)
+                .show(ui, |ui| {
+                    script_tree(ui, block);
+                });
+        }
+
+        if !has_sections {
+            ui.weak("The entry doesn't have conditions or effects.");
+        }
+
+        action
+    }
+}
+
+// Shows the block as a tree, in which the nested blocks can be collapsed.
+fn script_tree(ui: &mut egui::Ui, block: &Block) {
+    if block.items.is_empty() {
+        ui.weak("(empty)");
+        return;
+    }
+
+    for (idx, item) in block.items.iter().enumerate() {
+        let key = item
+            .key
+            .as_ref()
+            .map(|(key, operator)| format!("{} {} ", key.text, operator.as_str()));
+
+        match &item.value {
+            Value::Block(nested) if nested.items.iter().any(|item| item.key.is_some()) => {
+                let mut title = key
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_end_matches(" = ")
+                    .to_owned();
+
+                if let Some(tag) = &nested.tag {
+                    title.push(' ');
+                    title.push_str(tag);
+                }
+
+                egui::CollapsingHeader::new(egui::RichText::new(title).monospace())
+                    .id_salt(idx)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        script_tree(ui, nested);
+                    });
+            }
+            value => {
+                // Scalars and lists of values, e.g. `{ FRA ENG }`, fit on a line.
+                let mut text = key.unwrap_or_default();
+                text.push_str(&value.to_script());
+
+                ui.monospace(text);
+            }
+        }
+    }
+}
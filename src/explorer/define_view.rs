@@ -0,0 +1,197 @@
+use super::workspace::{ViewAction, location_link};
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::defines::{Define, DefineIndex};
+use eframe::egui;
+
+// Shows the defines as a tree of namespaces with their values, where they're set and what the
+// game sets them to. The diff mode only shows the defines which the mods change or add.
+pub struct DefineView {
+    index: DefineIndex,
+    // The names of the layers, by layer.
+    layer_names: Vec<String>,
+    filter: String,
+    diff_only: bool,
+    // The namespaces with the indices of their defines which are shown.
+    namespaces: Vec<(String, Vec<usize>)>,
+    // The filter and the toggle the namespaces were computed for.
+    namespaces_key: Option<(String, bool)>,
+}
+
+impl DefineView {
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        Ok(Self {
+            index: DefineIndex::new(dir_trees)?,
+            layer_names: dir_trees.iter().map(DirTree::layer_name).collect(),
+            filter: String::new(),
+            diff_only: false,
+            namespaces: Vec::new(),
+            namespaces_key: None,
+        })
+    }
+
+    fn layer_name(&self, layer: u32) -> &str {
+        self.layer_names
+            .get(layer as usize)
+            .map_or("", String::as_str)
+    }
+
+    fn update_namespaces(&mut self) {
+        if self
+            .namespaces_key
+            .as_ref()
+            .is_some_and(|(filter, diff_only)| {
+                filter == &self.filter && *diff_only == self.diff_only
+            })
+        {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let mut namespaces: Vec<(String, Vec<usize>)> = Vec::new();
+
+        for (idx, define) in self.index.defines().iter().enumerate() {
+            if (self.diff_only && !define.is_changed())
+                || (!filter.is_empty()
+                    && !define.name.to_lowercase().contains(&filter)
+                    && !define.assignment().value.to_lowercase().contains(&filter))
+            {
+                continue;
+            }
+
+            // The defines of a namespace may be set in several places, e.g. in a mod's file which
+            // only changes a few of them.
+            match namespaces
+                .iter_mut()
+                .find(|(namespace, _)| namespace == &define.namespace)
+            {
+                Some((_, defines)) => defines.push(idx),
+                None => namespaces.push((define.namespace.clone(), vec![idx])),
+            }
+        }
+
+        self.namespaces = namespaces;
+        self.namespaces_key = Some((self.filter.clone(), self.diff_only));
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut self.filter);
+            ui.checkbox(&mut self.diff_only, "Diff against vanilla");
+
+            let defines = self.index.defines();
+            let changed_count = defines.iter().filter(|define| define.is_changed()).count();
+            ui.weak(format!(
+                "{} defines, {} changed by mods",
+                defines.len(),
+                changed_count
+            ));
+
+            let warnings = self.index.warnings();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+            }
+        });
+
+        ui.separator();
+
+        self.update_namespaces();
+
+        if self.namespaces.is_empty() {
+            ui.weak(if self.index.defines().is_empty() {
+                "There are no defines in `common/defines`."
+            } else {
+                "No defines match."
+            });
+            return None;
+        }
+
+        let mut action = None;
+        // The namespaces are opened when searching, so that the matches can be seen.
+        let is_open = (!self.filter.is_empty() || self.diff_only).then_some(true);
+
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                for (namespace, defines) in &self.namespaces {
+                    egui::CollapsingHeader::new(format!("{} ({})", namespace, defines.len()))
+                        .id_salt(("define namespace", namespace))
+                        .open(is_open)
+                        .show(ui, |ui| {
+                            egui::Grid::new(("defines", namespace))
+                                .num_columns(4)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for &idx in defines {
+                                        let define = &self.index.defines()[idx];
+
+                                        if let Some(define_action) = self.define_row(ui, define) {
+                                            action = Some(define_action);
+                                        }
+
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+            });
+
+        action
+    }
+
+    fn define_row(&self, ui: &mut egui::Ui, define: &Define) -> Option<ViewAction> {
+        let assignment = define.assignment();
+        let is_changed = define.is_changed();
+
+        ui.monospace(define.key()).on_hover_text(&define.name);
+
+        let value = egui::RichText::new(&assignment.value).monospace();
+        ui.label(if is_changed { value.strong() } else { value });
+
+        match define.vanilla_assignment() {
+            _ if !is_changed => ui.label(""),
+            Some(vanilla) => ui
+                .weak(format!("vanilla: {}", vanilla.value))
+                .on_hover_text(format!(
+                    "{}:{}",
+                    vanilla.location.relative_path.display(),
+                    vanilla.location.line
+                )),
+            None => ui.weak("added"),
+        };
+
+        ui.horizontal(|ui| {
+            // Every place the define is set, from the first to the one in effect.
+            let history: Vec<_> = define
+                .assignments
+                .iter()
+                .map(|assignment| {
+                    format!(
+                        "{}: {} ({}:{}{})",
+                        self.layer_name(assignment.location.layer),
+                        assignment.value,
+                        assignment.location.relative_path.display(),
+                        assignment.location.line,
+                        if assignment.is_replaced {
+                            ", replaced"
+                        } else {
+                            ""
+                        }
+                    )
+                })
+                .collect();
+
+            ui.label(self.layer_name(assignment.location.layer))
+                .on_hover_text(history.join("\n"));
+
+            location_link(ui, &assignment.location)
+        })
+        .inner
+    }
+}
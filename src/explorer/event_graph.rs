@@ -0,0 +1,268 @@
+use super::workspace::ViewAction;
+use crate::events::EventIndex;
+use crate::events::graph::{EdgeKind, EventGraph, GraphNode, NodeKind};
+use eframe::egui;
+use std::sync::Arc;
+
+// Draws the chains of events of a namespace. The graph can be panned by dragging or scrolling and
+// zoomed with ctrl + scroll; clicking a node opens its definition.
+pub struct EventGraphView {
+    index: Arc<EventIndex>,
+    namespace: Option<String>,
+    graph: EventGraph,
+    offset: egui::Vec2,
+    zoom: f32,
+}
+
+impl EventGraphView {
+    const COLUMN_WIDTH: f32 = 240.0;
+    const ROW_HEIGHT: f32 = 44.0;
+    const NODE_SIZE: egui::Vec2 = egui::vec2(200.0, 28.0);
+    const MARGIN: egui::Vec2 = egui::vec2(20.0, 20.0);
+    const ZOOM_RANGE: (f32, f32) = (0.2, 3.0);
+
+    pub fn new(index: Arc<EventIndex>) -> Self {
+        // All the events of a game at once are too many to make sense of, so the graph starts with
+        // the first namespace.
+        let namespace = index
+            .namespaces()
+            .first()
+            .map(|&namespace| namespace.to_owned());
+        let graph = EventGraph::new(&index, namespace.as_deref());
+
+        Self {
+            index,
+            namespace,
+            graph,
+            offset: Self::MARGIN,
+            zoom: 1.0,
+        }
+    }
+
+    fn set_namespace(&mut self, namespace: Option<String>) {
+        self.graph = EventGraph::new(&self.index, namespace.as_deref());
+        self.namespace = namespace;
+        self.reset_view();
+    }
+
+    fn reset_view(&mut self) {
+        self.offset = Self::MARGIN;
+        self.zoom = 1.0;
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        self.toolbar(ui);
+
+        ui.separator();
+
+        self.canvas(ui)
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Namespace");
+
+            let mut namespace = self.namespace.clone();
+
+            egui::ComboBox::from_id_salt("event graph namespace")
+                .selected_text(namespace.as_deref().unwrap_or("All namespaces"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut namespace, None, "All namespaces");
+
+                    for value in self.index.namespaces() {
+                        ui.selectable_value(&mut namespace, Some(value.to_owned()), value);
+                    }
+                });
+
+            if namespace != self.namespace {
+                self.set_namespace(namespace);
+            }
+
+            if ui.button("Reset View").clicked() {
+                self.reset_view();
+            }
+
+            ui.weak(format!(
+                "{} nodes, {} edges",
+                self.graph.nodes.len(),
+                self.graph.edges.len()
+            ));
+
+            ui.separator();
+
+            for kind in [EdgeKind::Fires, EdgeKind::OptionFires, EdgeKind::OnAction] {
+                ui.colored_label(edge_color(ui, kind), edge_label(kind));
+            }
+        });
+    }
+
+    fn canvas(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+
+        if response.dragged() {
+            self.offset += response.drag_delta();
+        }
+
+        if response.hovered() {
+            let (zoom_delta, scroll_delta) =
+                ui.input(|input| (input.zoom_delta(), input.smooth_scroll_delta));
+
+            if zoom_delta != 1.0 {
+                // Zooms around the pointer, so that what's under it stays there.
+                let anchor =
+                    response.hover_pos().unwrap_or(response.rect.center()) - response.rect.min;
+                let zoom = (self.zoom * zoom_delta).clamp(Self::ZOOM_RANGE.0, Self::ZOOM_RANGE.1);
+                self.offset = anchor - (anchor - self.offset) * (zoom / self.zoom);
+                self.zoom = zoom;
+            } else {
+                self.offset += scroll_delta;
+            }
+        }
+
+        if self.graph.nodes.is_empty() {
+            painter.text(
+                response.rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "There are no events to show.",
+                egui::TextStyle::Body.resolve(ui.style()),
+                ui.visuals().weak_text_color(),
+            );
+            return None;
+        }
+
+        let rects: Vec<_> = self
+            .graph
+            .nodes
+            .iter()
+            .map(|node| self.node_rect(response.rect.min, node))
+            .collect();
+        let clip_rect = painter.clip_rect();
+
+        for edge in &self.graph.edges {
+            let (from, to) = (rects[edge.from], rects[edge.to]);
+
+            if !clip_rect.intersects(from.union(to)) {
+                continue;
+            }
+
+            let start = from.right_center();
+            let end = to.left_center();
+            let stroke = egui::Stroke::new(1.5 * self.zoom.sqrt(), edge_color(ui, edge.kind));
+
+            painter.arrow(start, end - start, stroke);
+        }
+
+        let font_id = egui::FontId::proportional(12.0 * self.zoom);
+        let hovered = response
+            .hover_pos()
+            .and_then(|pos| rects.iter().position(|rect| rect.contains(pos)));
+
+        for (idx, (node, rect)) in self.graph.nodes.iter().zip(&rects).enumerate() {
+            if !clip_rect.intersects(*rect) {
+                continue;
+            }
+
+            let mut fill = node_color(ui, node.kind);
+
+            if node.is_external {
+                fill = fill.gamma_multiply(0.5);
+            }
+
+            let stroke = if node.location.is_none() {
+                // Fired, but not defined anywhere.
+                egui::Stroke::new(1.5, ui.visuals().error_fg_color)
+            } else if hovered == Some(idx) {
+                ui.visuals().widgets.hovered.fg_stroke
+            } else {
+                ui.visuals().widgets.noninteractive.bg_stroke
+            };
+
+            painter.rect(
+                *rect,
+                4.0 * self.zoom,
+                fill,
+                stroke,
+                egui::StrokeKind::Inside,
+            );
+            painter.with_clip_rect(rect.intersect(clip_rect)).text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                &node.name,
+                font_id.clone(),
+                ui.visuals().strong_text_color(),
+            );
+        }
+
+        let node = &self.graph.nodes[hovered?];
+        let response = response.on_hover_ui_at_pointer(|ui| {
+            ui.strong(&node.name);
+            ui.label(match node.kind {
+                NodeKind::Event => "event",
+                NodeKind::OnAction => "on_action",
+                NodeKind::Decision => "decision",
+            });
+
+            match &node.location {
+                Some(location) => ui.label(format!(
+                    "{}:{}",
+                    location.relative_path.display(),
+                    location.line
+                )),
+                None => ui.colored_label(ui.visuals().error_fg_color, "Not defined"),
+            };
+        });
+
+        let location = node.location.as_ref()?;
+
+        if response.clicked() {
+            return Some(ViewAction::OpenFile {
+                full_path: location.full_path.clone(),
+                relative_path: location.relative_path.clone(),
+                line: location.line,
+            });
+        }
+
+        None
+    }
+
+    fn node_rect(&self, canvas_min: egui::Pos2, node: &GraphNode) -> egui::Rect {
+        let position = egui::vec2(
+            node.column as f32 * Self::COLUMN_WIDTH,
+            node.row as f32 * Self::ROW_HEIGHT,
+        );
+
+        egui::Rect::from_min_size(
+            canvas_min + self.offset + position * self.zoom,
+            Self::NODE_SIZE * self.zoom,
+        )
+    }
+}
+
+fn node_color(ui: &egui::Ui, kind: NodeKind) -> egui::Color32 {
+    let dark_mode = ui.visuals().dark_mode;
+
+    match (kind, dark_mode) {
+        (NodeKind::Event, _) => ui.visuals().widgets.inactive.bg_fill,
+        (NodeKind::OnAction, true) => egui::Color32::from_rgb(110, 70, 20),
+        (NodeKind::OnAction, false) => egui::Color32::from_rgb(250, 210, 160),
+        (NodeKind::Decision, true) => egui::Color32::from_rgb(30, 90, 50),
+        (NodeKind::Decision, false) => egui::Color32::from_rgb(180, 230, 190),
+    }
+}
+
+fn edge_color(ui: &egui::Ui, kind: EdgeKind) -> egui::Color32 {
+    match kind {
+        EdgeKind::Fires => ui.visuals().text_color(),
+        EdgeKind::OptionFires => egui::Color32::from_rgb(90, 160, 230),
+        EdgeKind::OnAction => egui::Color32::from_rgb(230, 150, 60),
+    }
+}
+
+fn edge_label(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Fires => "fires",
+        EdgeKind::OptionFires => "has option that fires",
+        EdgeKind::OnAction => "called from on_action",
+    }
+}
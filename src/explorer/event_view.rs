@@ -0,0 +1,407 @@
+use super::highlight::{self, Syntax};
+use super::workspace::{ViewAction, link, location_link};
+use crate::database::Language;
+use crate::database::models::LocalizationKeyKeyValue;
+use crate::events::{Caller, Event, EventIndex};
+use crate::script::Block;
+use eframe::egui;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+enum Row {
+    Namespace(String),
+    // The index of the event in the index.
+    Event(usize),
+}
+
+// Lists the events grouped by namespace and shows the details of the selected one.
+pub struct EventView {
+    index: Arc<EventIndex>,
+    // The localization the titles and the descriptions are shown in.
+    language: Language,
+    localization: HashMap<String, String>,
+    filter: String,
+    rows: Vec<Row>,
+    rows_filter: Option<String>,
+    selected: Option<String>,
+}
+
+impl EventView {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    pub fn new(
+        index: Arc<EventIndex>,
+        language: Language,
+        keys: Vec<LocalizationKeyKeyValue>,
+    ) -> Self {
+        let mut event_view = Self {
+            index,
+            language,
+            localization: HashMap::new(),
+            filter: String::new(),
+            rows: Vec::new(),
+            rows_filter: None,
+            selected: None,
+        };
+        event_view.set_localization(language, keys);
+        event_view
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_localization(&mut self, language: Language, keys: Vec<LocalizationKeyKeyValue>) {
+        self.language = language;
+        self.localization = keys.into_iter().map(|key| (key.key, key.value)).collect();
+        // The titles are filtered as well.
+        self.rows_filter = None;
+    }
+
+    // The title of the event in the selected language, or its key if it isn't localized.
+    fn title<'a>(&'a self, event: &'a Event) -> Option<&'a str> {
+        let key = event.titles.first()?;
+        Some(self.localization.get(key).map_or(key, String::as_str))
+    }
+
+    fn update_rows(&mut self) {
+        if self.rows_filter.as_deref() == Some(&self.filter) {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let mut namespace = None;
+        let mut rows = Vec::new();
+
+        for (idx, event) in self.index.events().iter().enumerate() {
+            if !filter.is_empty()
+                && !event.id.to_lowercase().contains(&filter)
+                && !self
+                    .title(event)
+                    .is_some_and(|title| title.to_lowercase().contains(&filter))
+            {
+                continue;
+            }
+
+            if namespace != Some(&event.namespace) {
+                namespace = Some(&event.namespace);
+                rows.push(Row::Namespace(
+                    event
+                        .namespace
+                        .clone()
+                        .unwrap_or_else(|| "(no namespace)".to_owned()),
+                ));
+            }
+
+            rows.push(Row::Event(idx));
+        }
+
+        self.rows = rows;
+        self.rows_filter = Some(self.filter.clone());
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let mut action = None;
+
+        egui::SidePanel::left("event list")
+            .resizable(true)
+            .default_width(320.0)
+            .show_inside(ui, |ui| {
+                self.list(ui);
+            });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    action = self.details(ui);
+                });
+        });
+
+        action
+    }
+
+    fn list(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+
+        self.update_rows();
+
+        ui.horizontal(|ui| {
+            let event_count = self
+                .rows
+                .iter()
+                .filter(|row| matches!(row, Row::Event(_)))
+                .count();
+            ui.weak(format!(
+                "{} of {} events",
+                event_count,
+                self.index.events().len()
+            ));
+
+            let warnings = self.index.warnings();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+            }
+        });
+
+        ui.separator();
+
+        let mut selected = None;
+
+        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+            ui,
+            Self::ROW_HEIGHT,
+            self.rows.len(),
+            |ui, range| {
+                for row in &self.rows[range] {
+                    match row {
+                        Row::Namespace(namespace) => {
+                            ui.strong(namespace);
+                        }
+                        Row::Event(idx) => {
+                            let event = &self.index.events()[*idx];
+                            let text = match self.title(event) {
+                                Some(title) => format!("{}  {}", event.id, title),
+                                None => event.id.clone(),
+                            };
+                            let is_selected = self.selected.as_ref() == Some(&event.id);
+
+                            if ui
+                                .add(egui::Button::selectable(is_selected, text).truncate())
+                                .clicked()
+                            {
+                                selected = Some(event.id.clone());
+                            }
+                        }
+                    }
+                }
+            },
+        );
+
+        if selected.is_some() {
+            self.selected = selected;
+        }
+    }
+
+    fn details(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let Some(selected) = &self.selected else {
+            ui.weak("Select an event to see its details.");
+            return None;
+        };
+
+        let Some(event) = self.index.event(selected) else {
+            ui.weak(format!("Event `{}` isn't defined.", selected));
+            return None;
+        };
+
+        let mut action = None;
+        let mut selected = None;
+
+        ui.heading(&event.id);
+
+        let flags: Vec<_> = [
+            (event.hidden, "hidden"),
+            (event.is_triggered_only, "triggered only"),
+            (event.fire_only_once, "fires only once"),
+        ]
+        .into_iter()
+        .filter_map(|(is_set, flag)| is_set.then_some(flag))
+        .collect();
+
+        ui.horizontal(|ui| {
+            ui.label(&event.kind);
+
+            if !flags.is_empty() {
+                ui.weak(format!("({})", flags.join(", ")));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Defined in");
+
+            if let Some(location_action) = location_link(ui, &event.location) {
+                action = Some(location_action);
+            }
+        });
+
+        ui.separator();
+
+        egui::Grid::new(("event localization", &event.id))
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (label, keys) in [("Title", &event.titles), ("Description", &event.descs)] {
+                    for key in keys {
+                        ui.label(label);
+
+                        if link(ui, key).on_hover_text("Inspect").clicked() {
+                            action = Some(ViewAction::InspectKey(key.clone()));
+                        }
+
+                        match self.localization.get(key) {
+                            Some(value) => ui.label(value),
+                            None => ui.weak("(not localized)"),
+                        };
+
+                        ui.end_row();
+                    }
+                }
+            });
+
+        ui.separator();
+
+        ui.strong("Fired by");
+        let callers = self.index.callers(&event.id);
+
+        if callers.is_empty() {
+            ui.weak("No event, on_action or decision fires this event.");
+        }
+
+        for caller in callers {
+            match caller {
+                Caller::Event(id) => {
+                    ui.horizontal(|ui| {
+                        ui.label("event");
+
+                        if link(ui, id).clicked() {
+                            selected = Some(id.clone());
+                        }
+                    });
+                }
+                Caller::OnAction(name) => {
+                    // An on_action can be extended in several files, so every part which fires
+                    // the event is listed.
+                    for on_action in self.index.on_actions().iter().filter(|on_action| {
+                        &on_action.name == name
+                            && on_action.calls.iter().any(|call| call.event_id == event.id)
+                    }) {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("on_action {}", name));
+
+                            if let Some(location_action) = location_link(ui, &on_action.location) {
+                                action = Some(location_action);
+                            }
+                        });
+                    }
+                }
+                Caller::Decision(name) => {
+                    for decision in self
+                        .index
+                        .decisions()
+                        .iter()
+                        .filter(|decision| &decision.name == name)
+                    {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("decision {}", name));
+
+                            if let Some(location_action) = location_link(ui, &decision.location) {
+                                action = Some(location_action);
+                            }
+                        });
+                    }
+                }
+            }
+        }
+
+        ui.add_space(4.0);
+        ui.strong("Fires");
+
+        if event.calls.is_empty() {
+            ui.weak("This event doesn't fire other events.");
+        }
+
+        for call in &event.calls {
+            ui.horizontal(|ui| {
+                ui.label(&call.context);
+
+                if self.index.event(&call.event_id).is_some() {
+                    if link(ui, &call.event_id).clicked() {
+                        selected = Some(call.event_id.clone());
+                    }
+                } else {
+                    ui.label(&call.event_id);
+                    ui.weak("(not defined)");
+                }
+
+                if link(ui, &format!("line {}", call.line))
+                    .on_hover_text("Show the call")
+                    .clicked()
+                {
+                    action = Some(ViewAction::OpenFile {
+                        full_path: event.location.full_path.clone(),
+                        relative_path: event.location.relative_path.clone(),
+                        line: call.line,
+                    });
+                }
+            });
+        }
+
+        ui.separator();
+
+        for (title, block) in [
+            ("Trigger", &event.trigger),
+            ("Mean time to happen", &event.mean_time_to_happen),
+            ("Immediate", &event.immediate),
+        ] {
+            if let Some(block) = block {
+                script_section(ui, title, block, true);
+            }
+        }
+
+        if !event.options.is_empty() {
+            egui::CollapsingHeader::new(format!("Options ({})", event.options.len()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for (idx, option) in event.options.iter().enumerate() {
+                        let name = option.name.as_deref().map_or_else(
+                            || format!("Option {}", idx + 1),
+                            |name| {
+                                self.localization
+                                    .get(name)
+                                    .cloned()
+                                    .unwrap_or_else(|| name.to_owned())
+                            },
+                        );
+
+                        egui::CollapsingHeader::new(name)
+                            .id_salt(("event option", &event.id, idx))
+                            .show(ui, |ui| {
+                                script(ui, &option.block);
+                            });
+                    }
+                });
+        }
+
+        if let Some(block) = &event.after {
+            script_section(ui, "After", block, false);
+        }
+
+        if selected.is_some() {
+            self.selected = selected;
+        }
+
+        action
+    }
+}
+
+fn script_section(ui: &mut egui::Ui, title: &str, block: &Block, default_open: bool) {
+    egui::CollapsingHeader::new(title)
+        .default_open(default_open)
+        .show(ui, |ui| {
+            script(ui, block);
+        });
+}
+
+// Shows the block as highlighted script.
+fn script(ui: &mut egui::Ui, block: &Block) {
+    for line in block.to_script().lines() {
+        ui.label(highlight::highlight(line, Syntax::Script, ui.style()));
+    }
+}
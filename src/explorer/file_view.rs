@@ -0,0 +1,408 @@
+use super::highlight::{self, Syntax};
+use super::workspace::ViewAction;
+use crate::core::*;
+use crate::database::vfs;
+use eframe::egui;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+// Offsets of the beginnings of the lines in a file. Only the index is kept in memory; the lines
+// themselves are read when they're displayed.
+struct LineIndex {
+    offsets: Vec<u64>,
+    file_len: u64,
+}
+
+impl LineIndex {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    fn build(path: &Path) -> Result<Self> {
+        let mut file = vfs::open(path)?;
+        let mut offsets = vec![0];
+        let mut buffer = vec![0; Self::CHUNK_SIZE];
+        let mut position = 0;
+
+        loop {
+            let read = file.read(&mut buffer)?;
+
+            if read == 0 {
+                break;
+            }
+
+            offsets.extend(
+                buffer[..read]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte == b'\n')
+                    .map(|(idx, _)| position + idx as u64 + 1),
+            );
+
+            position += read as u64;
+        }
+
+        // A trailing line break doesn't start another line.
+        if offsets.len() > 1 && offsets.last() == Some(&position) {
+            offsets.pop();
+        }
+
+        Ok(Self {
+            offsets,
+            file_len: position,
+        })
+    }
+
+    fn line_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    fn read_lines(&self, path: &Path, lines: Range<usize>) -> Result<Vec<String>> {
+        let lines = lines.start.min(self.line_count())..lines.end.min(self.line_count());
+
+        if lines.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let start = self.offsets[lines.start];
+        let end = self
+            .offsets
+            .get(lines.end)
+            .copied()
+            .unwrap_or(self.file_len);
+
+        let mut file = vfs::open(path)?;
+        file.seek(SeekFrom::Start(start))?;
+
+        let mut bytes = vec![0; (end - start) as usize];
+        file.read_exact(&mut bytes)?;
+
+        // Game files aren't always valid UTF-8, so they're decoded lossily.
+        let text = String::from_utf8_lossy(&bytes);
+
+        Ok(text
+            .split('\n')
+            .take(lines.len())
+            .enumerate()
+            .map(|(idx, line)| {
+                let line = line.strip_suffix('\r').unwrap_or(line);
+
+                if lines.start + idx == 0 {
+                    line.trim_start_matches('\u{feff}').to_owned()
+                } else {
+                    line.to_owned()
+                }
+            })
+            .collect())
+    }
+
+    // Returns the numbers (0-based) of the lines which contain the needle, ignoring case.
+    fn find(&self, path: &Path, needle: &str) -> Result<Vec<usize>> {
+        let needle = needle.to_lowercase();
+        let mut reader = BufReader::new(vfs::open(path)?);
+        let mut line = Vec::new();
+        let mut matches = Vec::new();
+        let mut line_number = 0;
+
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            if String::from_utf8_lossy(&line)
+                .to_lowercase()
+                .contains(&needle)
+            {
+                matches.push(line_number);
+            }
+
+            line.clear();
+            line_number += 1;
+        }
+
+        Ok(matches)
+    }
+}
+
+pub struct FileView {
+    full_path: PathBuf,
+    relative_path: PathBuf,
+    syntax: Syntax,
+    line_index: LineIndex,
+
+    // The lines which were displayed last, so that they're not read again every frame.
+    cached_lines: Range<usize>,
+    cached_line_content: Vec<String>,
+
+    find_text: String,
+    find_matches: Vec<usize>,
+    current_match: Option<usize>,
+    go_to_line_text: String,
+    scroll_to_line: Option<usize>,
+    highlighted_line: Option<usize>,
+    error: Option<Error>,
+}
+
+impl FileView {
+    pub fn open(full_path: &Path, relative_path: &Path) -> Result<Self> {
+        Ok(Self {
+            full_path: full_path.to_owned(),
+            relative_path: relative_path.to_owned(),
+            syntax: Syntax::from_path(full_path),
+            line_index: LineIndex::build(full_path)?,
+
+            cached_lines: 0..0,
+            cached_line_content: Vec::new(),
+
+            find_text: String::new(),
+            find_matches: Vec::new(),
+            current_match: None,
+            go_to_line_text: String::new(),
+            scroll_to_line: None,
+            highlighted_line: None,
+            error: None,
+        })
+    }
+
+    // Scrolls to and highlights the given line (1-based).
+    pub fn go_to_line(&mut self, line_number: usize) {
+        let line = line_number
+            .saturating_sub(1)
+            .min(self.line_index.line_count() - 1);
+        self.scroll_to_line = Some(line);
+        self.highlighted_line = Some(line);
+    }
+
+    // Ctrl-clicking a name in a script file asks to go to its definition.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        self.toolbar(ui);
+
+        ui.separator();
+
+        if let Some(error) = &self.error {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("Error: {}.", error.description()),
+            );
+        }
+
+        self.content(ui)
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui) {
+        let find_id = ui.make_persistent_id(("find", &self.full_path));
+        let go_to_line_id = ui.make_persistent_id(("go to line", &self.full_path));
+
+        let (find_shortcut, go_to_line_shortcut) = ui.input(|input| {
+            (
+                input.modifiers.command && input.key_pressed(egui::Key::F),
+                input.modifiers.command && input.key_pressed(egui::Key::G),
+            )
+        });
+
+        if find_shortcut {
+            ui.memory_mut(|memory| memory.request_focus(find_id));
+        }
+
+        if go_to_line_shortcut {
+            ui.memory_mut(|memory| memory.request_focus(go_to_line_id));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(self.relative_path.display().to_string());
+            ui.weak(format!(
+                "({}, {} lines)",
+                self.syntax.name(),
+                self.line_index.line_count()
+            ));
+
+            ui.separator();
+
+            let find = ui.add(
+                egui::TextEdit::singleline(&mut self.find_text)
+                    .id(find_id)
+                    .hint_text("Find (Ctrl+F)")
+                    .desired_width(160.0),
+            );
+
+            if find.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                self.update_matches();
+                find.request_focus();
+            }
+
+            if ui.button("<").on_hover_text("Previous match").clicked() {
+                self.next_match(false);
+            }
+
+            if ui.button(">").on_hover_text("Next match").clicked() {
+                self.next_match(true);
+            }
+
+            if let Some(current_match) = self.current_match {
+                ui.label(format!("{}/{}", current_match + 1, self.find_matches.len()));
+            } else if !self.find_text.is_empty() && self.find_matches.is_empty() {
+                ui.weak("No matches");
+            }
+
+            ui.separator();
+
+            let go_to_line = ui.add(
+                egui::TextEdit::singleline(&mut self.go_to_line_text)
+                    .id(go_to_line_id)
+                    .hint_text("Line (Ctrl+G)")
+                    .desired_width(80.0),
+            );
+
+            if go_to_line.lost_focus()
+                && ui.input(|input| input.key_pressed(egui::Key::Enter))
+                && let Ok(line_number) = self.go_to_line_text.trim().parse()
+            {
+                self.go_to_line(line_number);
+            }
+        });
+    }
+
+    // Finds the text and goes to its first occurrence.
+    pub fn find(&mut self, text: &str) {
+        self.find_text = text.to_owned();
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        self.find_matches.clear();
+        self.current_match = None;
+
+        if self.find_text.is_empty() {
+            return;
+        }
+
+        match self.line_index.find(&self.full_path, &self.find_text) {
+            Ok(matches) => {
+                self.find_matches = matches;
+                self.next_match(true);
+            }
+            Err(error) => {
+                self.error.replace(error);
+            }
+        }
+    }
+
+    fn next_match(&mut self, forward: bool) {
+        if self.find_matches.is_empty() {
+            return;
+        }
+
+        let count = self.find_matches.len();
+        let current_match = match (self.current_match, forward) {
+            (None, _) => 0,
+            (Some(current), true) => (current + 1) % count,
+            (Some(current), false) => (current + count - 1) % count,
+        };
+
+        self.current_match = Some(current_match);
+        self.go_to_line(self.find_matches[current_match] + 1);
+    }
+
+    fn content(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let line_count = self.line_index.line_count();
+        let line_number_width = line_count.to_string().len();
+
+        let mut scroll_area = egui::ScrollArea::both().auto_shrink(false);
+        let mut action = None;
+
+        if let Some(line) = self.scroll_to_line.take() {
+            let spacing = ui.spacing().item_spacing.y;
+            let offset =
+                (line as f32 * (row_height + spacing) - ui.available_height() / 3.0).max(0.0);
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+
+        scroll_area.show_rows(ui, row_height, line_count, |ui, rows| {
+            if self.cached_lines != rows {
+                match self.line_index.read_lines(&self.full_path, rows.clone()) {
+                    Ok(lines) => {
+                        self.cached_line_content = lines;
+                        self.cached_lines = rows.clone();
+                    }
+                    Err(error) => {
+                        self.error.replace(error);
+                        return;
+                    }
+                }
+            }
+
+            let style = ui.style().clone();
+            let is_command_down = ui.input(|input| input.modifiers.command);
+
+            for (line, content) in rows.zip(&self.cached_line_content) {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{:>width$}",
+                            line + 1,
+                            width = line_number_width
+                        ))
+                        .monospace()
+                        .weak(),
+                    );
+
+                    let mut job = highlight::highlight(content, self.syntax, &style);
+
+                    if self.highlighted_line == Some(line) {
+                        for section in &mut job.sections {
+                            section.format.background = ui.visuals().selection.bg_fill;
+                        }
+                    }
+
+                    if self.syntax != Syntax::Script {
+                        ui.add(egui::Label::new(job).extend());
+                        return;
+                    }
+
+                    let galley = ui.fonts(|fonts| fonts.layout_job(job));
+                    let mut response =
+                        ui.add(egui::Label::new(galley.clone()).sense(egui::Sense::click()));
+
+                    if is_command_down {
+                        response = response.on_hover_cursor(egui::CursorIcon::PointingHand);
+                    }
+
+                    if response.clicked()
+                        && is_command_down
+                        && let Some(pos) = response.interact_pointer_pos()
+                    {
+                        let cursor = galley.cursor_from_pos(pos - response.rect.min);
+
+                        if let Some(word) = word_at(content, cursor.index) {
+                            action = Some(ViewAction::GoToDefinition(word.to_owned()));
+                        }
+                    }
+                });
+            }
+        });
+
+        action
+    }
+}
+
+// The name at the character index of the line, e.g. `my_effect` in `my_effect = yes`.
+fn word_at(line: &str, char_index: usize) -> Option<&str> {
+    let is_name_char = |char: char| char.is_alphanumeric() || char == '_';
+    let chars: Vec<_> = line.char_indices().collect();
+
+    // Clicking right after the name, e.g. on the space, still counts.
+    let idx = [Some(char_index), char_index.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .find(|&idx| chars.get(idx).is_some_and(|&(_, char)| is_name_char(char)))?;
+
+    let start = chars[..idx]
+        .iter()
+        .rposition(|&(_, char)| !is_name_char(char))
+        .map_or(0, |position| position + 1);
+    let end = chars[idx..]
+        .iter()
+        .position(|&(_, char)| !is_name_char(char))
+        .map_or(chars.len(), |position| idx + position);
+
+    let start_offset = chars[start].0;
+    let end_offset = chars.get(end).map_or(line.len(), |&(offset, _)| offset);
+
+    Some(&line[start_offset..end_offset])
+}
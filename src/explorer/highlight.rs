@@ -0,0 +1,408 @@
+use eframe::egui;
+use eframe::egui::text::{LayoutJob, TextFormat};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Script,
+    Interface,
+    Localization,
+    Csv,
+    PlainText,
+}
+
+impl Syntax {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("txt" | "lua" | "mod" | "asset") => Self::Script,
+            Some("gui" | "gfx") => Self::Interface,
+            Some("yml") => Self::Localization,
+            Some("csv") => Self::Csv,
+            _ => Self::PlainText,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Script => "PDX Script",
+            Self::Interface => "Interface",
+            Self::Localization => "Localization",
+            Self::Csv => "CSV",
+            Self::PlainText => "Plain Text",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Text,
+    Comment,
+    String,
+    Number,
+    Keyword,
+    Operator,
+    Key,
+    Variable,
+    Type,
+    Separator,
+}
+
+struct Palette {
+    text: egui::Color32,
+    comment: egui::Color32,
+    string: egui::Color32,
+    number: egui::Color32,
+    keyword: egui::Color32,
+    operator: egui::Color32,
+    key: egui::Color32,
+    variable: egui::Color32,
+    type_: egui::Color32,
+    separator: egui::Color32,
+}
+
+impl Palette {
+    const DARK: Self = Self {
+        text: egui::Color32::from_rgb(212, 212, 212),
+        comment: egui::Color32::from_rgb(106, 153, 85),
+        string: egui::Color32::from_rgb(206, 145, 120),
+        number: egui::Color32::from_rgb(181, 206, 168),
+        keyword: egui::Color32::from_rgb(86, 156, 214),
+        operator: egui::Color32::from_rgb(180, 180, 180),
+        key: egui::Color32::from_rgb(156, 220, 254),
+        variable: egui::Color32::from_rgb(220, 220, 170),
+        type_: egui::Color32::from_rgb(78, 201, 176),
+        separator: egui::Color32::from_rgb(128, 128, 128),
+    };
+
+    const LIGHT: Self = Self {
+        text: egui::Color32::from_rgb(30, 30, 30),
+        comment: egui::Color32::from_rgb(0, 128, 0),
+        string: egui::Color32::from_rgb(163, 21, 21),
+        number: egui::Color32::from_rgb(9, 134, 88),
+        keyword: egui::Color32::from_rgb(0, 0, 255),
+        operator: egui::Color32::from_rgb(80, 80, 80),
+        key: egui::Color32::from_rgb(0, 16, 128),
+        variable: egui::Color32::from_rgb(121, 94, 38),
+        type_: egui::Color32::from_rgb(38, 127, 153),
+        separator: egui::Color32::from_rgb(150, 150, 150),
+    };
+
+    fn color(&self, token: Token) -> egui::Color32 {
+        match token {
+            Token::Text => self.text,
+            Token::Comment => self.comment,
+            Token::String => self.string,
+            Token::Number => self.number,
+            Token::Keyword => self.keyword,
+            Token::Operator => self.operator,
+            Token::Key => self.key,
+            Token::Variable => self.variable,
+            Token::Type => self.type_,
+            Token::Separator => self.separator,
+        }
+    }
+}
+
+// Highlights a single line. Every line is highlighted on its own, so that only the visible lines
+// of a file have to be read; none of the supported formats have tokens which span lines.
+pub fn highlight(line: &str, syntax: Syntax, style: &egui::Style) -> LayoutJob {
+    let palette = if style.visuals.dark_mode {
+        &Palette::DARK
+    } else {
+        &Palette::LIGHT
+    };
+    let font_id = egui::TextStyle::Monospace.resolve(style);
+
+    let tokens = match syntax {
+        Syntax::Script => tokenize_script(line, false),
+        Syntax::Interface => tokenize_script(line, true),
+        Syntax::Localization => tokenize_localization(line),
+        Syntax::Csv => tokenize_csv(line),
+        Syntax::PlainText => vec![(0..line.len(), Token::Text)],
+    };
+
+    let mut job = LayoutJob::default();
+
+    for (range, token) in tokens {
+        job.append(
+            &line[range],
+            0.0,
+            TextFormat::simple(font_id.clone(), palette.color(token)),
+        );
+    }
+
+    job
+}
+
+type Tokens = Vec<(std::ops::Range<usize>, Token)>;
+
+fn push(tokens: &mut Tokens, start: usize, end: usize, token: Token) {
+    if start >= end {
+        return;
+    }
+
+    // Merge with the previous token of the same kind to keep the number of sections low.
+    if let Some((range, last)) = tokens.last_mut()
+        && *last == token
+        && range.end == start
+    {
+        range.end = end;
+        return;
+    }
+
+    tokens.push((start..end, token));
+}
+
+// Returns the end of the run of characters starting at `start` which satisfy the predicate.
+fn run_end(line: &str, start: usize, predicate: impl Fn(char) -> bool) -> usize {
+    line[start..]
+        .char_indices()
+        .find(|(_, c)| !predicate(*c))
+        .map_or(line.len(), |(idx, _)| start + idx)
+}
+
+// Returns the end of the string starting with a quote at `start` (including the closing quote).
+fn string_end(line: &str, start: usize) -> usize {
+    let mut escaped = false;
+
+    for (idx, c) in line[start + 1..].char_indices() {
+        match c {
+            '\\' => escaped = !escaped,
+            '"' if !escaped => return start + 1 + idx + 1,
+            _ => escaped = false,
+        }
+    }
+
+    line.len()
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | ':' | '@' | '-' | '\'')
+}
+
+// PDX script looks like this:
+//
+// country_event = {
+//     id = flavor.1 # comment
+//     trigger = { tag = FRA NOT = { prestige > 50 } }
+//     1444.11.11 = { set_variable = { which = @my_var value = 1.5 } }
+// }
+fn tokenize_script(line: &str, interface: bool) -> Tokens {
+    let mut tokens = Tokens::new();
+    let mut idx = 0;
+
+    while let Some(c) = line[idx..].chars().next() {
+        let start = idx;
+
+        match c {
+            '#' => {
+                push(&mut tokens, start, line.len(), Token::Comment);
+                break;
+            }
+            '"' => {
+                idx = string_end(line, start);
+                push(&mut tokens, start, idx, Token::String);
+            }
+            '=' | '<' | '>' | '!' | '?' => {
+                idx = run_end(line, start, |c| matches!(c, '=' | '<' | '>' | '!' | '?'));
+                push(&mut tokens, start, idx, Token::Operator);
+            }
+            '{' | '}' | '[' | ']' | '(' | ')' | ',' => {
+                idx += c.len_utf8();
+                push(&mut tokens, start, idx, Token::Operator);
+            }
+            '$' => {
+                idx = line[start + 1..]
+                    .find('$')
+                    .map_or(line.len(), |end| start + 1 + end + 1);
+                push(&mut tokens, start, idx, Token::Variable);
+            }
+            c if c.is_whitespace() => {
+                idx = run_end(line, start, char::is_whitespace);
+                push(&mut tokens, start, idx, Token::Text);
+            }
+            _ => {
+                idx = run_end(line, start, is_identifier_char).max(start + c.len_utf8());
+                let word = &line[start..idx];
+
+                let token = if word.starts_with('@') {
+                    Token::Variable
+                } else if word
+                    .trim_start_matches('-')
+                    .starts_with(|c: char| c.is_ascii_digit())
+                    && word
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '%'))
+                {
+                    // Numbers and dates (1444.11.11).
+                    Token::Number
+                } else if matches!(word, "yes" | "no" | "AND" | "OR" | "NOT" | "NOR" | "NAND") {
+                    Token::Keyword
+                } else if interface && word.to_ascii_lowercase().ends_with("type") {
+                    Token::Type
+                } else if line[idx..].trim_start().starts_with(['=', '<', '>']) {
+                    Token::Key
+                } else {
+                    Token::Text
+                };
+
+                push(&mut tokens, start, idx, token);
+            }
+        }
+    }
+
+    tokens
+}
+
+// Localization files look like this:
+//
+// l_english:
+//  key:0 "Value with $reference$, §Ycolor§! and [Scope.GetName]" # comment
+fn tokenize_localization(line: &str) -> Tokens {
+    let mut tokens = Tokens::new();
+    let content_start = run_end(line, 0, |c| c.is_whitespace() || c == '\u{feff}');
+    push(&mut tokens, 0, content_start, Token::Text);
+
+    let content = &line[content_start..];
+
+    if content.starts_with('#') {
+        push(&mut tokens, content_start, line.len(), Token::Comment);
+        return tokens;
+    }
+
+    let Some(colon_idx) = content.find(':').map(|idx| content_start + idx) else {
+        push(&mut tokens, content_start, line.len(), Token::Text);
+        return tokens;
+    };
+
+    // The language specifier.
+    if line[colon_idx + 1..].trim().is_empty() {
+        push(&mut tokens, content_start, colon_idx + 1, Token::Keyword);
+        push(&mut tokens, colon_idx + 1, line.len(), Token::Text);
+        return tokens;
+    }
+
+    push(&mut tokens, content_start, colon_idx, Token::Key);
+    push(&mut tokens, colon_idx, colon_idx + 1, Token::Operator);
+
+    let revision_end = run_end(line, colon_idx + 1, |c| c.is_ascii_digit());
+    push(&mut tokens, colon_idx + 1, revision_end, Token::Number);
+
+    let value_start = run_end(line, revision_end, char::is_whitespace);
+    push(&mut tokens, revision_end, value_start, Token::Text);
+
+    // The value ends with the last quote before the comment.
+    let mut in_quotes = false;
+    let mut value_end = line.len();
+
+    for (idx, c) in line[value_start..].char_indices() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                value_end = value_start + idx + 1;
+            }
+            '#' if !in_quotes => break,
+            _ => {}
+        }
+    }
+
+    tokenize_localization_value(&mut tokens, line, value_start, value_end);
+
+    let comment_start = line[value_end..]
+        .find('#')
+        .map_or(line.len(), |idx| value_end + idx);
+    push(&mut tokens, value_end, comment_start, Token::Text);
+    push(&mut tokens, comment_start, line.len(), Token::Comment);
+
+    tokens
+}
+
+fn tokenize_localization_value(tokens: &mut Tokens, line: &str, start: usize, end: usize) {
+    let mut idx = start;
+
+    while idx < end {
+        let Some(c) = line[idx..end].chars().next() else {
+            break;
+        };
+        let token_start = idx;
+
+        let (token_end, token) = match c {
+            '$' => (
+                line[idx + 1..end]
+                    .find('$')
+                    .map_or(end, |offset| idx + 1 + offset + 1),
+                Token::Variable,
+            ),
+            '[' => (
+                line[idx..end]
+                    .find(']')
+                    .map_or(end, |offset| idx + offset + 1),
+                Token::Type,
+            ),
+            // Color codes, e.g. §Y.
+            '§' => (
+                line[idx + c.len_utf8()..end]
+                    .chars()
+                    .next()
+                    .map_or(end, |code| idx + c.len_utf8() + code.len_utf8()),
+                Token::Keyword,
+            ),
+            _ => (
+                line[idx..end]
+                    .find(['$', '[', '§'])
+                    .map_or(end, |offset| idx + offset),
+                Token::String,
+            ),
+        };
+
+        idx = token_end.max(idx + c.len_utf8());
+        push(tokens, token_start, idx, token);
+    }
+}
+
+// CSV files used by the games are separated with semicolons, but commas are supported as well.
+// Every other column is highlighted, so that the columns are easier to tell apart.
+fn tokenize_csv(line: &str) -> Tokens {
+    let mut tokens = Tokens::new();
+
+    if line.trim_start().starts_with('#') {
+        push(&mut tokens, 0, line.len(), Token::Comment);
+        return tokens;
+    }
+
+    let mut column = 0;
+    let mut field_start = 0;
+    let mut in_quotes = false;
+
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' | ',' if !in_quotes => {
+                let token = if column % 2 == 0 {
+                    Token::Text
+                } else {
+                    Token::Key
+                };
+
+                push(&mut tokens, field_start, idx, token);
+                push(&mut tokens, idx, idx + 1, Token::Separator);
+                field_start = idx + 1;
+                column += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let token = if column % 2 == 0 {
+        Token::Text
+    } else {
+        Token::Key
+    };
+    push(&mut tokens, field_start, line.len(), token);
+
+    tokens
+}
@@ -0,0 +1,301 @@
+use super::workspace::{ViewAction, link, location_link};
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::history::{Date, Entity, HistoryEntry, HistoryIndex};
+use eframe::egui;
+
+// Lists the entities of the history files and shows the timeline of the selected one, along with
+// its state at a date. The date is kept when another entity is selected, so that entities can be
+// compared at the same point in time.
+pub struct HistoryView {
+    index: HistoryIndex,
+    // The subfolder of `history` which is listed, or all of them.
+    kind: Option<String>,
+    filter: String,
+    // The indices of the entities which are listed.
+    rows: Vec<usize>,
+    // The kind and the filter the rows were computed for.
+    rows_key: Option<(Option<String>, String)>,
+    selected: Option<usize>,
+    date_text: String,
+}
+
+impl HistoryView {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        Ok(Self {
+            index: HistoryIndex::new(dir_trees)?,
+            kind: None,
+            filter: String::new(),
+            rows: Vec::new(),
+            rows_key: None,
+            selected: None,
+            date_text: String::new(),
+        })
+    }
+
+    fn update_rows(&mut self) {
+        if self
+            .rows_key
+            .as_ref()
+            .is_some_and(|(kind, filter)| kind == &self.kind && filter == &self.filter)
+        {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+
+        self.rows = self
+            .index
+            .entities()
+            .iter()
+            .enumerate()
+            .filter(|(_, entity)| {
+                self.kind.as_ref().is_none_or(|kind| &entity.kind == kind)
+                    && (filter.is_empty() || entity.name.to_lowercase().contains(&filter))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.rows_key = Some((self.kind.clone(), self.filter.clone()));
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let mut action = None;
+
+        egui::SidePanel::left("history list")
+            .resizable(true)
+            .default_width(280.0)
+            .show_inside(ui, |ui| {
+                self.list(ui);
+            });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    action = self.details(ui);
+                });
+        });
+
+        action
+    }
+
+    fn list(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Kind");
+
+            egui::ComboBox::from_id_salt("history kind")
+                .selected_text(self.kind.as_deref().unwrap_or("All"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.kind, None, "All");
+
+                    for kind in self.index.kinds() {
+                        ui.selectable_value(&mut self.kind, Some(kind.clone()), kind);
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+
+        self.update_rows();
+
+        ui.horizontal(|ui| {
+            ui.weak(format!(
+                "{} of {} entities",
+                self.rows.len(),
+                self.index.entities().len()
+            ));
+
+            let warnings = self.index.warnings();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+            }
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+            ui,
+            Self::ROW_HEIGHT,
+            self.rows.len(),
+            |ui, range| {
+                for &idx in &self.rows[range] {
+                    let entity = &self.index.entities()[idx];
+                    let text = if self.kind.is_some() {
+                        entity.name.clone()
+                    } else {
+                        format!("{}/{}", entity.kind, entity.name)
+                    };
+
+                    if ui
+                        .add(egui::Button::selectable(self.selected == Some(idx), text).truncate())
+                        .clicked()
+                    {
+                        self.selected = Some(idx);
+                    }
+                }
+            },
+        );
+    }
+
+    fn details(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let Some(entity) = self.selected.map(|idx| &self.index.entities()[idx]) else {
+            ui.weak("Select an entity to see its history.");
+            return None;
+        };
+
+        let mut action = None;
+
+        ui.heading(&entity.name);
+        ui.horizontal(|ui| {
+            ui.weak(format!("({})", entity.kind));
+
+            if let Some(location_action) = location_link(ui, &entity.location) {
+                action = Some(location_action);
+            }
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("State at");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.date_text)
+                    .hint_text("start")
+                    .desired_width(100.0),
+            );
+
+            if ui.button("Start").clicked() {
+                self.date_text.clear();
+            }
+
+            if let Some(&last) = entity.dates().last()
+                && ui.button("End").clicked()
+            {
+                self.date_text = last.to_string();
+            }
+        });
+
+        let date_text = self.date_text.trim();
+        let date = Date::parse(date_text);
+
+        if !date_text.is_empty() && date.is_none() {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                "Enter a date like 1444.11.11, or nothing for the start.",
+            );
+        }
+
+        egui::CollapsingHeader::new("State")
+            .id_salt(("history state", &entity.name))
+            .default_open(true)
+            .show(ui, |ui| {
+                if let Some(state_action) = Self::state(ui, entity, date) {
+                    action = Some(state_action);
+                }
+            });
+
+        let dates = entity.dates();
+        let mut selected_date = None;
+
+        egui::CollapsingHeader::new(format!("Timeline ({} dates)", dates.len()))
+            .id_salt(("history timeline", &entity.name))
+            .default_open(true)
+            .show(ui, |ui| {
+                for entry_date in dates {
+                    let is_applied = date.is_some_and(|date| entry_date <= date);
+
+                    let text = egui::RichText::new(entry_date.to_string()).strong();
+
+                    if ui
+                        .selectable_label(is_applied, text)
+                        .on_hover_text("Show the state at this date")
+                        .clicked()
+                    {
+                        selected_date = Some(entry_date);
+                    }
+
+                    ui.indent(("history date", entry_date.to_string()), |ui| {
+                        for entry in entity
+                            .entries
+                            .iter()
+                            .filter(|entry| entry.date == Some(entry_date))
+                        {
+                            ui.horizontal(|ui| {
+                                ui.monospace(format!("{} = {}", entry.key, entry.value));
+
+                                if link(ui, &format!("line {}", entry.line))
+                                    .on_hover_text(entity.location.full_path.display().to_string())
+                                    .clicked()
+                                {
+                                    action = Some(Self::open(entity, entry));
+                                }
+                            });
+                        }
+                    });
+                }
+            });
+
+        if let Some(selected_date) = selected_date {
+            self.date_text = selected_date.to_string();
+        }
+
+        action
+    }
+
+    fn state(ui: &mut egui::Ui, entity: &Entity, date: Option<Date>) -> Option<ViewAction> {
+        let state = entity.state_at(date);
+
+        if state.is_empty() {
+            ui.weak("Nothing is set up yet.");
+            return None;
+        }
+
+        let mut action = None;
+
+        egui::Grid::new(("history state grid", &entity.name))
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for value in state {
+                    ui.monospace(value.key);
+                    ui.monospace(&value.entry.value);
+
+                    // Where the value comes from.
+                    let source = match value.entry.date {
+                        Some(date) => format!("{}, line {}", date, value.entry.line),
+                        None => format!("start, line {}", value.entry.line),
+                    };
+
+                    if link(ui, &source)
+                        .on_hover_text(format!("{} = {}", value.entry.key, value.entry.value))
+                        .clicked()
+                    {
+                        action = Some(Self::open(entity, value.entry));
+                    }
+
+                    ui.end_row();
+                }
+            });
+
+        action
+    }
+
+    fn open(entity: &Entity, entry: &HistoryEntry) -> ViewAction {
+        let location = entity.location_of(entry);
+
+        ViewAction::OpenFile {
+            full_path: location.full_path,
+            relative_path: location.relative_path,
+            line: location.line,
+        }
+    }
+}
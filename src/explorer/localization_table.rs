@@ -0,0 +1,107 @@
+use crate::database::models::LocalizationKeyKeyValue;
+use eframe::egui;
+use std::collections::HashMap;
+
+pub struct LocalizationTable {
+    keys: Vec<LocalizationKeyKeyValue>,
+    // Indices of the keys by their name, for the views which show localized names.
+    key_indices: HashMap<String, usize>,
+    // Indices of the keys which match the filter the rows were computed for. They're only
+    // recomputed when the filter changes, so that every frame only lays out the visible rows.
+    rows: Vec<usize>,
+    rows_filter: Option<String>,
+}
+
+impl LocalizationTable {
+    const ROW_HEIGHT: f32 = 20.0;
+
+    pub fn new(keys: Vec<LocalizationKeyKeyValue>) -> Self {
+        let key_indices = keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| (key.key.clone(), idx))
+            .collect();
+
+        Self {
+            keys,
+            key_indices,
+            rows: Vec::new(),
+            rows_filter: None,
+        }
+    }
+
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.key_indices
+            .get(key)
+            .map(|&idx| self.keys[idx].value.as_str())
+    }
+
+    fn update_rows(&mut self, filter: &str) {
+        if self.rows_filter.as_deref() == Some(filter) {
+            return;
+        }
+
+        self.rows = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|(_, localization_key)| {
+                localization_key.key.contains(filter) || localization_key.value.contains(filter)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.rows_filter = Some(filter.to_owned());
+    }
+
+    // Returns the key which was clicked, if any.
+    pub fn ui(&mut self, ui: &mut egui::Ui, filter: &mut String) -> Option<String> {
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(filter);
+            ui.weak(format!("{} of {} keys", self.rows.len(), self.keys.len()));
+        });
+
+        self.update_rows(filter);
+
+        let mut clicked = None;
+        let available_height = ui.available_height();
+
+        egui_extras::TableBuilder::new(ui)
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .striped(false)
+            .resizable(true)
+            .max_scroll_height(available_height)
+            .header(Self::ROW_HEIGHT, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Key");
+                });
+                header.col(|ui| {
+                    ui.strong("Value");
+                });
+            })
+            .body(|body| {
+                body.rows(Self::ROW_HEIGHT, self.rows.len(), |mut row| {
+                    let localization_key = &self.keys[self.rows[row.index()]];
+
+                    row.col(|ui| {
+                        let response = ui
+                            .add(
+                                egui::Label::new(&localization_key.key).sense(egui::Sense::click()),
+                            )
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Inspect");
+
+                        if response.clicked() {
+                            clicked = Some(localization_key.key.clone());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(&localization_key.value).truncate());
+                    });
+                });
+            });
+
+        clicked
+    }
+}
@@ -0,0 +1,284 @@
+use super::localization_table::LocalizationTable;
+use super::workspace::{ViewAction, link, location_link};
+use crate::core::*;
+use crate::database::Language;
+use crate::database::dir::DirTree;
+use crate::definitions::{Definition, DefinitionIndex, DefinitionKind};
+use eframe::egui;
+
+enum Row {
+    Kind(DefinitionKind),
+    // The index of the definition in the index.
+    Definition(usize),
+}
+
+// Lists the modifiers by kind and shows where the selected one is defined and used. The list can
+// be narrowed down to the modifiers without a `modifier_<name>` key in the localization of the
+// selected language.
+pub struct ModifierView {
+    index: DefinitionIndex,
+    // The names of the layers, by layer.
+    layer_names: Vec<String>,
+    filter: String,
+    missing_only: bool,
+    rows: Vec<Row>,
+    // The number of modifiers without localization, for the language the rows were computed for.
+    missing_count: usize,
+    // The filter, the toggle and the language the rows were computed for.
+    rows_key: Option<(String, bool, Language)>,
+    selected: Option<usize>,
+}
+
+impl ModifierView {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        Ok(Self {
+            index: DefinitionIndex::new(dir_trees, DefinitionKind::MODIFIERS)?,
+            layer_names: dir_trees.iter().map(DirTree::layer_name).collect(),
+            filter: String::new(),
+            missing_only: false,
+            rows: Vec::new(),
+            missing_count: 0,
+            rows_key: None,
+            selected: None,
+        })
+    }
+
+    fn is_localized(definition: &Definition, localization: Option<&LocalizationTable>) -> bool {
+        localization.is_some_and(|localization| {
+            localization.value(&definition.localization_key()).is_some()
+        })
+    }
+
+    fn layer_name(&self, layer: u32) -> &str {
+        self.layer_names
+            .get(layer as usize)
+            .map_or("", String::as_str)
+    }
+
+    fn update_rows(&mut self, localization: Option<&LocalizationTable>, language: Language) {
+        if self
+            .rows_key
+            .as_ref()
+            .is_some_and(|(filter, missing_only, rows_language)| {
+                filter == &self.filter
+                    && *missing_only == self.missing_only
+                    && *rows_language == language
+            })
+        {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let mut kind = None;
+        let mut rows = Vec::new();
+        let mut missing_count = 0;
+
+        for (idx, definition) in self.index.definitions().iter().enumerate() {
+            let is_localized = Self::is_localized(definition, localization);
+
+            if !is_localized {
+                missing_count += 1;
+            }
+
+            if (self.missing_only && is_localized)
+                || (!filter.is_empty() && !definition.name.to_lowercase().contains(&filter))
+            {
+                continue;
+            }
+
+            if kind != Some(definition.kind) {
+                kind = Some(definition.kind);
+                rows.push(Row::Kind(definition.kind));
+            }
+
+            rows.push(Row::Definition(idx));
+        }
+
+        self.rows = rows;
+        self.missing_count = missing_count;
+        self.rows_key = Some((self.filter.clone(), self.missing_only, language));
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        localization: Option<&LocalizationTable>,
+        language: Language,
+    ) -> Option<ViewAction> {
+        let mut action = None;
+
+        egui::SidePanel::left("modifier list")
+            .resizable(true)
+            .default_width(320.0)
+            .show_inside(ui, |ui| {
+                self.list(ui, localization, language);
+            });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    action = self.details(ui, localization);
+                });
+        });
+
+        action
+    }
+
+    fn list(
+        &mut self,
+        ui: &mut egui::Ui,
+        localization: Option<&LocalizationTable>,
+        language: Language,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        ui.checkbox(&mut self.missing_only, "Missing localization only");
+
+        self.update_rows(localization, language);
+
+        ui.horizontal(|ui| {
+            let definition_count = self
+                .rows
+                .iter()
+                .filter(|row| matches!(row, Row::Definition(_)))
+                .count();
+            ui.weak(format!(
+                "{} of {} modifiers, {} not localized",
+                definition_count,
+                self.index.definitions().len(),
+                self.missing_count
+            ));
+
+            let warnings = self.index.warnings();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+            }
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+            ui,
+            Self::ROW_HEIGHT,
+            self.rows.len(),
+            |ui, range| {
+                for row in &self.rows[range] {
+                    match row {
+                        Row::Kind(kind) => {
+                            ui.strong(format!("{}s", kind.name()));
+                        }
+                        Row::Definition(idx) => {
+                            let definition = &self.index.definitions()[*idx];
+                            let mut text = egui::RichText::new(&definition.name);
+
+                            if !Self::is_localized(definition, localization) {
+                                text = text.color(ui.visuals().warn_fg_color);
+                            }
+
+                            if ui
+                                .add(
+                                    egui::Button::selectable(self.selected == Some(*idx), text)
+                                        .truncate(),
+                                )
+                                .clicked()
+                            {
+                                self.selected = Some(*idx);
+                            }
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    fn details(
+        &self,
+        ui: &mut egui::Ui,
+        localization: Option<&LocalizationTable>,
+    ) -> Option<ViewAction> {
+        let Some(definition) = self.selected.map(|idx| &self.index.definitions()[idx]) else {
+            ui.weak("Select a modifier to see where it's defined and used.");
+            return None;
+        };
+
+        let mut action = None;
+
+        ui.heading(&definition.name);
+        ui.weak(format!("({})", definition.kind.name()));
+
+        ui.separator();
+
+        egui::Grid::new(("modifier details", &definition.name))
+            .num_columns(3)
+            .show(ui, |ui| {
+                let key = definition.localization_key();
+
+                ui.label("Name");
+
+                if link(ui, &key).on_hover_text("Inspect").clicked() {
+                    action = Some(ViewAction::InspectKey(key.clone()));
+                }
+
+                match localization.and_then(|localization| localization.value(&key)) {
+                    Some(value) => ui.label(value),
+                    None => ui.colored_label(ui.visuals().warn_fg_color, "(not localized)"),
+                };
+
+                ui.end_row();
+
+                // A name can be defined as more than one kind of modifier.
+                for other in self.index.definitions_of(&definition.name) {
+                    ui.label(if other.kind == definition.kind {
+                        "Defined in".to_owned()
+                    } else {
+                        format!("Also {}", other.kind.name())
+                    });
+                    ui.label(self.layer_name(other.location.layer));
+
+                    if let Some(location_action) = location_link(ui, &other.location) {
+                        action = Some(location_action);
+                    }
+
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        let references = self.index.references(&definition.name);
+
+        if references.is_empty() {
+            ui.weak("The modifier isn't used in the scripts.");
+            return action;
+        }
+
+        ui.strong(format!("Used in {} places", references.len()));
+
+        egui::Grid::new(("modifier references", &definition.name))
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for reference in references {
+                    ui.monospace(&reference.context);
+
+                    if let Some(location_action) = location_link(ui, &reference.location) {
+                        action = Some(location_action);
+                    }
+
+                    ui.end_row();
+                }
+            });
+
+        action
+    }
+}
@@ -0,0 +1,328 @@
+use super::workspace::{ViewAction, location_link};
+use crate::definitions::{DefinitionIndex, DefinitionKind, Reference};
+use eframe::egui;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+enum Row {
+    Kind(DefinitionKind),
+    // The index of the definition in the index.
+    Definition(usize),
+    Undefined,
+    // The name of a scripted trigger or effect which is called but not defined.
+    UndefinedName(String),
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum Selection {
+    Definition(usize),
+    Undefined(String),
+}
+
+// Lists the scripted triggers, scripted effects and script values with the places they're used,
+// and the calls of scripted triggers and effects which aren't defined. The unused ones are
+// highlighted and can be listed on their own.
+pub struct ScriptedView {
+    index: Arc<DefinitionIndex>,
+    // The calls of the undefined scripted triggers and effects, by name.
+    undefined_calls: BTreeMap<String, Vec<Reference>>,
+    filter: String,
+    unused_only: bool,
+    rows: Vec<Row>,
+    // The filter and the toggle the rows were computed for.
+    rows_key: Option<(String, bool)>,
+    selection: Option<Selection>,
+}
+
+impl ScriptedView {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    pub fn new(index: Arc<DefinitionIndex>) -> Self {
+        let mut undefined_calls: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+        for call in index.undefined_calls() {
+            undefined_calls
+                .entry(call.name.clone())
+                .or_default()
+                .push(call.clone());
+        }
+
+        Self {
+            index,
+            undefined_calls,
+            filter: String::new(),
+            unused_only: false,
+            rows: Vec::new(),
+            rows_key: None,
+            selection: None,
+        }
+    }
+
+    // Shows the definition with the name, or its calls if it isn't defined.
+    pub fn select(&mut self, name: &str) {
+        self.filter = name.to_owned();
+        self.unused_only = false;
+        self.selection = match self
+            .index
+            .definitions()
+            .iter()
+            .position(|definition| definition.name == name)
+        {
+            Some(idx) => Some(Selection::Definition(idx)),
+            None => Some(Selection::Undefined(name.to_owned())),
+        };
+    }
+
+    fn is_unused(&self, name: &str) -> bool {
+        self.index.references(name).is_empty()
+    }
+
+    fn update_rows(&mut self) {
+        if self.rows_key.as_ref().is_some_and(|(filter, unused_only)| {
+            filter == &self.filter && *unused_only == self.unused_only
+        }) {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let matches = |name: &str| filter.is_empty() || name.to_lowercase().contains(&filter);
+        let mut kind = None;
+        let mut rows = Vec::new();
+
+        for (idx, definition) in self.index.definitions().iter().enumerate() {
+            if (self.unused_only && !self.is_unused(&definition.name)) || !matches(&definition.name)
+            {
+                continue;
+            }
+
+            if kind != Some(definition.kind) {
+                kind = Some(definition.kind);
+                rows.push(Row::Kind(definition.kind));
+            }
+
+            rows.push(Row::Definition(idx));
+        }
+
+        if !self.unused_only {
+            let names: Vec<_> = self
+                .undefined_calls
+                .keys()
+                .filter(|name| matches(name))
+                .map(|name| Row::UndefinedName(name.clone()))
+                .collect();
+
+            if !names.is_empty() {
+                rows.push(Row::Undefined);
+                rows.extend(names);
+            }
+        }
+
+        self.rows = rows;
+        self.rows_key = Some((self.filter.clone(), self.unused_only));
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let mut action = None;
+
+        egui::SidePanel::left("scripted list")
+            .resizable(true)
+            .default_width(320.0)
+            .show_inside(ui, |ui| {
+                self.list(ui);
+            });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    action = self.details(ui);
+                });
+        });
+
+        action
+    }
+
+    fn list(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        ui.checkbox(&mut self.unused_only, "Unused only");
+
+        self.update_rows();
+
+        ui.horizontal(|ui| {
+            let definitions = self.index.definitions();
+            let unused_count = definitions
+                .iter()
+                .filter(|definition| self.is_unused(&definition.name))
+                .count();
+            ui.weak(format!(
+                "{} defined, {} unused, {} undefined",
+                definitions.len(),
+                unused_count,
+                self.undefined_calls.len()
+            ));
+
+            let warnings = self.index.warnings();
+
+            if !warnings.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", warnings.len()),
+                )
+                .on_hover_text(warnings.join("\n"));
+            }
+        });
+
+        ui.separator();
+
+        let mut selection = None;
+
+        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+            ui,
+            Self::ROW_HEIGHT,
+            self.rows.len(),
+            |ui, range| {
+                for row in &self.rows[range] {
+                    let (row_selection, text) = match row {
+                        Row::Kind(kind) => {
+                            ui.strong(format!("{}s", kind.name()));
+                            continue;
+                        }
+                        Row::Undefined => {
+                            ui.strong("undefined");
+                            continue;
+                        }
+                        Row::Definition(idx) => {
+                            let name = &self.index.definitions()[*idx].name;
+                            let mut text = egui::RichText::new(name);
+
+                            if self.is_unused(name) {
+                                text = text.color(ui.visuals().warn_fg_color);
+                            }
+
+                            (Selection::Definition(*idx), text)
+                        }
+                        Row::UndefinedName(name) => (
+                            Selection::Undefined(name.clone()),
+                            egui::RichText::new(name).color(ui.visuals().error_fg_color),
+                        ),
+                    };
+
+                    let is_selected = self.selection.as_ref() == Some(&row_selection);
+
+                    if ui
+                        .add(egui::Button::selectable(is_selected, text).truncate())
+                        .clicked()
+                    {
+                        selection = Some(row_selection);
+                    }
+                }
+            },
+        );
+
+        if selection.is_some() {
+            self.selection = selection;
+        }
+    }
+
+    fn details(&self, ui: &mut egui::Ui) -> Option<ViewAction> {
+        let mut action = None;
+
+        let (name, calls) = match &self.selection {
+            Some(Selection::Definition(idx)) => {
+                let definition = &self.index.definitions()[*idx];
+
+                ui.heading(&definition.name);
+                ui.weak(format!("({})", definition.kind.name()));
+
+                ui.separator();
+
+                egui::Grid::new(("scripted definition", &definition.name))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for other in self.index.definitions_of(&definition.name) {
+                            ui.label(if other.kind == definition.kind {
+                                "Defined in".to_owned()
+                            } else {
+                                format!("Also {}", other.kind.name())
+                            });
+
+                            if let Some(location_action) = location_link(ui, &other.location) {
+                                action = Some(location_action);
+                            }
+
+                            ui.end_row();
+                        }
+
+                        if !definition.parameters.is_empty() {
+                            ui.label("Parameters");
+                            ui.monospace(
+                                definition
+                                    .parameters
+                                    .iter()
+                                    .map(|parameter| format!("${}$", parameter))
+                                    .collect::<Vec<_>>()
+                                    .join(" "),
+                            );
+                            ui.end_row();
+                        }
+                    });
+
+                (&definition.name, self.index.references(&definition.name))
+            }
+            Some(Selection::Undefined(name)) => {
+                ui.heading(name);
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    "Called like a scripted trigger or effect, but not defined.",
+                );
+
+                (
+                    name,
+                    self.undefined_calls
+                        .get(name)
+                        .map_or(&[][..], Vec::as_slice),
+                )
+            }
+            None => {
+                ui.weak("Select a scripted trigger, effect or value to see where it's used.");
+                return None;
+            }
+        };
+
+        ui.separator();
+
+        if calls.is_empty() {
+            ui.colored_label(ui.visuals().warn_fg_color, "It isn't used anywhere.");
+            return action;
+        }
+
+        ui.strong(format!("Used in {} places", calls.len()));
+
+        egui::Grid::new(("scripted usages", name))
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for call in calls {
+                    ui.monospace(&call.context);
+
+                    if let Some(location_action) = location_link(ui, &call.location) {
+                        action = Some(location_action);
+                    }
+
+                    ui.monospace(
+                        call.arguments
+                            .iter()
+                            .map(|(key, value)| format!("{} = {}", key, value))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    );
+                    ui.end_row();
+                }
+            });
+
+        action
+    }
+}
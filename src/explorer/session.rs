@@ -0,0 +1,66 @@
+use super::tree_view::TreeState;
+use super::workspace::Tabs;
+use crate::database::Language;
+use crate::database::dir::WalkOptions;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// Everything which is restored when a session is reopened.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Session {
+    // The game and the mods on top of it, in the load order.
+    pub roots: Vec<PathBuf>,
+    // How the directories are walked when they're indexed.
+    pub walk_options: WalkOptions,
+    pub selected_language: Language,
+    pub localization_filter: String,
+    pub tree: TreeState,
+    pub tabs: Tabs,
+}
+
+impl Session {
+    // Names the mod stack after its directories, e.g. "Europa Universalis IV + my_mod".
+    pub fn title(roots: &[PathBuf]) -> String {
+        roots
+            .iter()
+            .map(|root| {
+                root.file_name()
+                    .unwrap_or(root.as_os_str())
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Sessions {
+    pub named: BTreeMap<String, Session>,
+    // Mod stacks which were opened, the most recent first.
+    pub recent: Vec<Vec<PathBuf>>,
+    // Whether the last session is reopened at startup without asking.
+    pub reopen_last_session: bool,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self {
+            named: BTreeMap::new(),
+            recent: Vec::new(),
+            reopen_last_session: true,
+        }
+    }
+}
+
+impl Sessions {
+    const MAX_RECENT: usize = 10;
+
+    pub fn add_recent(&mut self, roots: &[PathBuf]) {
+        self.recent.retain(|recent| recent != roots);
+        self.recent.insert(0, roots.to_owned());
+        self.recent.truncate(Self::MAX_RECENT);
+    }
+}
@@ -0,0 +1,386 @@
+use crate::database::dir::{DirTree, Node};
+use crate::glob::Pattern;
+use eframe::egui;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+// Per-file data from the database which is shown next to the nodes.
+#[derive(Default)]
+pub struct TreeStats {
+    // Number of localization keys by file ID.
+    pub localization_key_counts: HashMap<u64, usize>,
+    // Parse errors by file ID.
+    pub parse_errors: HashMap<u64, Vec<String>>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct RowStats {
+    files: usize,
+    localization_keys: usize,
+    parse_errors: usize,
+}
+
+// A node of the directory tree, flattened so that the visible part of the tree can be laid out
+// without walking it.
+struct TreeRow {
+    layer: u32,
+    depth: usize,
+    name: String,
+    full_path: PathBuf,
+    relative_path: PathBuf,
+    // Relative path with `/` as the separator, which is what the filter is matched against.
+    filter_path: String,
+    is_dir: bool,
+    is_game_file: bool,
+    // Whether the path is valid UTF-8. Other files are shown in a lossy form and can't be opened,
+    // since the open tabs are persisted as text.
+    has_utf8_path: bool,
+    parent: Option<usize>,
+    // Index of the first row after the subtree of this node.
+    subtree_end: usize,
+    // Totals of the subtree for directories.
+    stats: RowStats,
+    parse_errors: Vec<String>,
+}
+
+// The part of the tree view which is persisted.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TreeState {
+    // Layers and relative paths of the expanded directories.
+    expanded: HashSet<(u32, PathBuf)>,
+    filter: String,
+    hide_non_game_files: bool,
+}
+
+impl Default for TreeState {
+    fn default() -> Self {
+        Self {
+            // Only the root of the game is expanded at first.
+            expanded: HashSet::from([(0, PathBuf::new())]),
+            filter: String::new(),
+            hide_non_game_files: false,
+        }
+    }
+}
+
+impl TreeState {
+    fn is_filtering(&self) -> bool {
+        !self.filter.trim().is_empty()
+    }
+
+    fn is_expanded(&self, row: &TreeRow) -> bool {
+        self.expanded
+            .contains(&(row.layer, row.relative_path.clone()))
+    }
+}
+
+#[derive(Default)]
+pub struct TreeView {
+    layer_names: Vec<String>,
+    // All nodes of all layers in depth-first order.
+    rows: Vec<TreeRow>,
+    // Indices of the rows which are shown. Rebuilt only when the rows, the filter or the expanded
+    // directories change.
+    visible_rows: Vec<usize>,
+    visible_rows_outdated: bool,
+}
+
+impl TreeView {
+    const INDENT: f32 = 16.0;
+
+    // Shows the trees of all layers, each one under its own root.
+    pub fn set_dir_trees(&mut self, dir_trees: &[DirTree], mut stats: TreeStats) {
+        self.rows.clear();
+        self.layer_names = dir_trees.iter().map(DirTree::layer_name).collect();
+
+        for dir_tree in dir_trees {
+            Self::flatten(
+                dir_tree.root(),
+                dir_tree.layer(),
+                0,
+                None,
+                &mut stats,
+                &mut self.rows,
+            );
+        }
+
+        // Add up the totals of the directories; children always come after their parents.
+        for idx in (0..self.rows.len()).rev() {
+            if let Some(parent) = self.rows[idx].parent {
+                let child = self.rows[idx].stats;
+                let parent = &mut self.rows[parent].stats;
+
+                parent.files += child.files;
+                parent.localization_keys += child.localization_keys;
+                parent.parse_errors += child.parse_errors;
+            }
+        }
+
+        self.visible_rows_outdated = true;
+    }
+
+    fn flatten(
+        node: &Node,
+        layer: u32,
+        depth: usize,
+        parent: Option<usize>,
+        stats: &mut TreeStats,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        let idx = rows.len();
+        let is_dir = matches!(node, Node::Directory(_));
+        let parse_errors = if is_dir {
+            Vec::new()
+        } else {
+            stats.parse_errors.remove(&node.id()).unwrap_or_default()
+        };
+
+        rows.push(TreeRow {
+            layer,
+            depth,
+            name: node.file_name().display().to_string(),
+            full_path: node.full_path().to_owned(),
+            relative_path: node.relative_path().to_owned(),
+            filter_path: node
+                .relative_path()
+                .to_string_lossy()
+                .replace('\\', "/")
+                .to_lowercase(),
+            is_dir,
+            is_game_file: node.is_game_file(),
+            has_utf8_path: node.has_utf8_path(),
+            parent,
+            subtree_end: idx + 1,
+            stats: if is_dir {
+                RowStats::default()
+            } else {
+                RowStats {
+                    files: 1,
+                    localization_keys: stats
+                        .localization_key_counts
+                        .get(&node.id())
+                        .copied()
+                        .unwrap_or_default(),
+                    parse_errors: parse_errors.len(),
+                }
+            },
+            parse_errors,
+        });
+
+        if let Node::Directory(dir) = node {
+            for child in dir.children() {
+                Self::flatten(child, layer, depth + 1, Some(idx), stats, rows);
+            }
+
+            rows[idx].subtree_end = rows.len();
+        }
+    }
+
+    fn update_visible_rows(&mut self, state: &TreeState) {
+        self.visible_rows.clear();
+
+        if state.is_filtering() {
+            self.update_filtered_rows(state);
+            return;
+        }
+
+        let mut idx = 0;
+
+        while let Some(row) = self.rows.get(idx) {
+            if state.hide_non_game_files && !row.is_game_file {
+                idx = row.subtree_end;
+                continue;
+            }
+
+            self.visible_rows.push(idx);
+
+            idx = if row.is_dir && !state.is_expanded(row) {
+                row.subtree_end
+            } else {
+                idx + 1
+            };
+        }
+    }
+
+    // Shows the nodes which match the filter along with their ancestors, all expanded. A directory
+    // which matches shows its whole subtree. The filter is a glob pattern if it contains any
+    // wildcards (matched against the name, or the relative path if it contains `/`) and a
+    // substring of the relative path otherwise.
+    fn update_filtered_rows(&mut self, state: &TreeState) {
+        let filter = state.filter.trim().to_lowercase();
+        let pattern = Pattern::is_pattern(&filter).then(|| Pattern::case_insensitive(&filter));
+        let matches = |row: &TreeRow| match &pattern {
+            Some(pattern) if filter.contains('/') => pattern.matches(&row.filter_path),
+            Some(pattern) => pattern.matches(&row.name.to_lowercase()),
+            None => row.filter_path.contains(&filter),
+        };
+
+        let mut included = vec![false; self.rows.len()];
+        let mut idx = 0;
+
+        while let Some(row) = self.rows.get(idx) {
+            if state.hide_non_game_files && !row.is_game_file {
+                idx = row.subtree_end;
+            } else if matches(row) {
+                let end = if row.is_dir { row.subtree_end } else { idx + 1 };
+                included[idx..end].fill(true);
+                idx = end;
+            } else {
+                idx += 1;
+            }
+        }
+
+        // The hidden nodes inside of matching directories have to be left out again.
+        if state.hide_non_game_files {
+            for (idx, row) in self.rows.iter().enumerate() {
+                if !row.is_game_file {
+                    included[idx..row.subtree_end].fill(false);
+                }
+            }
+        }
+
+        for idx in (0..self.rows.len()).rev() {
+            if included[idx]
+                && let Some(parent) = self.rows[idx].parent
+            {
+                included[parent] = true;
+            }
+        }
+
+        self.visible_rows
+            .extend((0..self.rows.len()).filter(|idx| included[*idx]));
+    }
+
+    fn toggle(&mut self, state: &mut TreeState, idx: usize) {
+        let row = &self.rows[idx];
+        let key = (row.layer, row.relative_path.clone());
+
+        if !state.expanded.remove(&key) {
+            state.expanded.insert(key);
+        }
+
+        self.visible_rows_outdated = true;
+    }
+
+    // Returns the full and the relative path of the file which was clicked, if any.
+    pub fn ui(&mut self, ui: &mut egui::Ui, state: &mut TreeState) -> Option<(PathBuf, PathBuf)> {
+        ui.horizontal(|ui| {
+            let filter = ui.add(
+                egui::TextEdit::singleline(&mut state.filter)
+                    .hint_text("Filter (e.g. events or **/*.gui)")
+                    .desired_width(200.0),
+            );
+
+            if filter.changed() {
+                self.visible_rows_outdated = true;
+            }
+        });
+
+        if ui
+            .checkbox(&mut state.hide_non_game_files, "Hide non-game files")
+            .changed()
+        {
+            self.visible_rows_outdated = true;
+        }
+
+        ui.separator();
+
+        if std::mem::take(&mut self.visible_rows_outdated) {
+            self.update_visible_rows(state);
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let filtering = state.is_filtering();
+        let mut toggled = None;
+        let mut clicked = None;
+
+        egui::ScrollArea::both().auto_shrink(false).show_rows(
+            ui,
+            row_height,
+            self.visible_rows.len(),
+            |ui, visible| {
+                for &idx in &self.visible_rows[visible] {
+                    let row = &self.rows[idx];
+                    let expanded = filtering || state.is_expanded(row);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(row.depth as f32 * Self::INDENT);
+
+                        if self.row_ui(ui, row, expanded).clicked() {
+                            if !row.is_dir && row.has_utf8_path {
+                                clicked = Some(idx);
+                            } else if !filtering {
+                                toggled = Some(idx);
+                            }
+                        }
+                    });
+                }
+            },
+        );
+
+        if let Some(idx) = toggled {
+            self.toggle(state, idx);
+        }
+
+        clicked.map(|idx| {
+            let row = &self.rows[idx];
+            (row.full_path.clone(), row.relative_path.clone())
+        })
+    }
+
+    fn row_ui(&self, ui: &mut egui::Ui, row: &TreeRow, expanded: bool) -> egui::Response {
+        let text = match (row.is_dir, expanded) {
+            (true, true) => format!("⏷ {}", row.name),
+            (true, false) => format!("⏵ {}", row.name),
+            (false, _) => row.name.clone(),
+        };
+
+        let mut label = egui::RichText::new(text);
+
+        if !row.is_game_file {
+            label = label.weak();
+        }
+
+        let response = ui
+            .add(egui::Label::new(label).sense(egui::Sense::click()).extend())
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        let stats = row.stats;
+
+        if !row.has_utf8_path {
+            ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+                .on_hover_text(
+                    "The path isn't valid UTF-8, so it's shown with replacement characters. The \
+                     file is indexed, but it isn't parsed and can't be opened here.",
+                );
+        }
+
+        if row.is_dir {
+            ui.weak(format!("{} files", stats.files));
+        }
+
+        if stats.localization_keys > 0 {
+            ui.weak(format!("{} keys", stats.localization_keys));
+        }
+
+        if stats.parse_errors > 0 {
+            let errors = ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("{} errors", stats.parse_errors),
+            );
+
+            if !row.parse_errors.is_empty() {
+                errors.on_hover_text(row.parse_errors.join("\n"));
+            }
+        }
+
+        if row.is_dir
+            && let Some(layer_name) = self.layer_names.get(row.layer as usize)
+        {
+            ui.weak(format!("[{}]", layer_name));
+        }
+
+        response
+    }
+}
@@ -0,0 +1,900 @@
+use super::decision_view::DecisionView;
+use super::define_view::DefineView;
+use super::event_graph::EventGraphView;
+use super::event_view::EventView;
+use super::file_view::FileView;
+use super::history_view::HistoryView;
+use super::localization_table::LocalizationTable;
+use super::modifier_view::ModifierView;
+use super::scripted_view::ScriptedView;
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::database::{self, models};
+use crate::definitions::{DefinitionIndex, DefinitionKind};
+use crate::events::EventIndex;
+use crate::script::Location;
+use eframe::egui;
+use egui_dock::tab_viewer::OnCloseResponse;
+use egui_dock::{DockArea, DockState, TabViewer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
+use std::thread;
+
+// Describes what a tab shows. Only the descriptions are persisted; the state of the views (open
+// files, search results) is rebuilt from them when the tabs are shown again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum Tab {
+    Localization,
+    File {
+        full_path: PathBuf,
+        relative_path: PathBuf,
+    },
+    Search {
+        query: String,
+    },
+    // Shows a localization key in all languages.
+    Inspector {
+        key: String,
+    },
+    Events,
+    EventGraph,
+    Decisions,
+    Modifiers,
+    // Scripted triggers, scripted effects and script values.
+    Scripted,
+    Defines,
+    History,
+}
+
+impl Tab {
+    fn title(&self) -> String {
+        match self {
+            Self::Localization => "Localization".to_owned(),
+            Self::File { relative_path, .. } => relative_path
+                .file_name()
+                .unwrap_or(relative_path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            Self::Search { query } => format!("Search: {}", query),
+            Self::Inspector { key } => format!("Key: {}", key),
+            Self::Events => "Events".to_owned(),
+            Self::EventGraph => "Event Graph".to_owned(),
+            Self::Decisions => "Decisions".to_owned(),
+            Self::Modifiers => "Modifiers".to_owned(),
+            Self::Scripted => "Scripted Items".to_owned(),
+            Self::Defines => "Defines".to_owned(),
+            Self::History => "History".to_owned(),
+        }
+    }
+}
+
+struct SearchResults {
+    files: Vec<models::File>,
+    keys: Vec<(models::LocalizationKey, String)>,
+}
+
+// Runtime state of the tabs.
+#[derive(Default)]
+struct TabState {
+    file_views: HashMap<PathBuf, Result<FileView>>,
+    search_results: HashMap<String, Result<SearchResults>>,
+    inspected_keys: HashMap<String, Result<Vec<(models::LocalizationKey, models::File)>>>,
+    // Shared by the event views, since it takes a while to build.
+    event_index: Option<Loader<Arc<EventIndex>>>,
+    event_view: Option<EventView>,
+    event_graph: Option<EventGraphView>,
+    decision_view: Option<Loader<DecisionView>>,
+    modifier_view: Option<Loader<ModifierView>>,
+    // Shared by the scripted items view and going to definitions from the file views.
+    scripted_index: Option<Loader<Arc<DefinitionIndex>>>,
+    scripted_view: Option<ScriptedView>,
+    // The definition to go to once the scripted items are indexed.
+    pending_definition: Option<String>,
+    define_view: Option<Loader<DefineView>>,
+    history_view: Option<Loader<HistoryView>>,
+}
+
+// Builds a value on a worker thread, since parsing the script files of a game takes long enough
+// to freeze the UI.
+enum Loader<T> {
+    Loading(mpsc::Receiver<Result<T>>),
+    Loaded(Result<T>),
+}
+
+impl<T: Send + 'static> Loader<T> {
+    // Builds the value from the layers of the database. The worker gets its own copy of them,
+    // since the database stays with the UI thread.
+    fn spawn(
+        ctx: &egui::Context,
+        db: &database::Database,
+        build: impl FnOnce(&[DirTree]) -> Result<T> + Send + 'static,
+    ) -> Self {
+        let ctx = ctx.clone();
+        let dir_trees = db.dir_trees().to_vec();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            // The receiver is gone if the state was dropped in the meantime.
+            let _ = sender.send(build(&dir_trees));
+            ctx.request_repaint();
+        });
+
+        Self::Loading(receiver)
+    }
+}
+
+impl<T> Loader<T> {
+    // The value, or `None` while it's still being built.
+    fn poll(&mut self) -> Option<&mut Result<T>> {
+        if let Self::Loading(receiver) = self {
+            match receiver.try_recv() {
+                Ok(result) => *self = Self::Loaded(result),
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    *self = Self::Loaded(Err(error!("The worker thread stopped unexpectedly")));
+                }
+            }
+        }
+
+        match self {
+            Self::Loading(_) => None,
+            Self::Loaded(result) => Some(result),
+        }
+    }
+}
+
+// What the user asked for in a view, which is up to the workspace to do.
+pub enum ViewAction {
+    OpenFile {
+        full_path: PathBuf,
+        relative_path: PathBuf,
+        line: usize,
+    },
+    InspectKey(String),
+    // Goes to the scripted trigger, effect or value with the name.
+    GoToDefinition(String),
+}
+
+// Where to go in a file which is opened from another tab.
+enum FileTarget {
+    // The first line which contains the text.
+    Text(String),
+    Line(usize),
+}
+
+// State of the explorer the tabs need to display their content.
+pub struct WorkspaceContext<'a> {
+    pub database: &'a mut Option<database::Database>,
+    pub localization_table: Option<&'a mut LocalizationTable>,
+    pub localization_filter: &'a mut String,
+    // The language the events are shown in.
+    pub language: database::Language,
+}
+
+// The open tabs and their layout.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct Tabs {
+    dock_state: DockState<Tab>,
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Self {
+            dock_state: DockState::new(vec![Tab::Localization]),
+        }
+    }
+}
+
+impl Tabs {
+    // Focuses the tab, opening it in the focused leaf if it's not open yet.
+    pub fn open(&mut self, tab: Tab) {
+        if let Some((surface, node, tab_idx)) = self.dock_state.find_tab(&tab) {
+            self.dock_state.set_active_tab((surface, node, tab_idx));
+            self.dock_state
+                .set_focused_node_and_surface((surface, node));
+        } else {
+            self.dock_state.push_to_focused_leaf(tab);
+        }
+    }
+}
+
+// Shows the tabs. The state of the views is kept here rather than in the tabs, so that the tabs
+// can be persisted.
+#[derive(Default)]
+pub struct Workspace {
+    tab_state: TabState,
+}
+
+impl Workspace {
+    // Opens the file and highlights the target line.
+    fn open_file_at(
+        &mut self,
+        tabs: &mut Tabs,
+        full_path: &Path,
+        relative_path: &Path,
+        target: &FileTarget,
+    ) {
+        tabs.open(Tab::File {
+            full_path: full_path.to_owned(),
+            relative_path: relative_path.to_owned(),
+        });
+
+        if let Ok(file_view) = self.tab_state.file_view(full_path, relative_path) {
+            match target {
+                FileTarget::Text(text) => file_view.find(text),
+                FileTarget::Line(line) => file_view.go_to_line(*line),
+            }
+        }
+    }
+
+    // Drops the state derived from the database, e.g. after the localization files were changed.
+    // It's rebuilt when the tabs are shown again.
+    pub fn invalidate(&mut self) {
+        self.tab_state = TabState::default();
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, tabs: &mut Tabs, context: WorkspaceContext) {
+        let mut viewer = Viewer {
+            ctx: ui.ctx().clone(),
+            context,
+            tab_state: &mut self.tab_state,
+            opened_tabs: Vec::new(),
+        };
+
+        DockArea::new(&mut tabs.dock_state)
+            .style(egui_dock::Style::from_egui(ui.style()))
+            .show_leaf_collapse_buttons(false)
+            .show_inside(ui, &mut viewer);
+
+        for (tab, target) in viewer.opened_tabs {
+            match (tab, target) {
+                (
+                    Tab::File {
+                        full_path,
+                        relative_path,
+                    },
+                    Some(target),
+                ) => self.open_file_at(tabs, &full_path, &relative_path, &target),
+                (tab, _) => tabs.open(tab),
+            }
+        }
+    }
+}
+
+impl TabState {
+    fn file_view(&mut self, full_path: &Path, relative_path: &Path) -> &mut Result<FileView> {
+        self.file_views
+            .entry(full_path.to_owned())
+            .or_insert_with(|| FileView::open(full_path, relative_path))
+    }
+
+    // The index, or `None` while it's being built.
+    fn event_index(
+        &mut self,
+        ctx: &egui::Context,
+        db: &database::Database,
+    ) -> Option<Result<Arc<EventIndex>>> {
+        self.event_index
+            .get_or_insert_with(|| {
+                Loader::spawn(ctx, db, |dir_trees| {
+                    EventIndex::new(dir_trees).map(Arc::new)
+                })
+            })
+            .poll()
+            .map(|result| result.clone())
+    }
+
+    // The index, or `None` while it's being built.
+    fn scripted_index(
+        &mut self,
+        ctx: &egui::Context,
+        db: &database::Database,
+    ) -> Option<Result<Arc<DefinitionIndex>>> {
+        self.scripted_index
+            .get_or_insert_with(|| {
+                Loader::spawn(ctx, db, |dir_trees| {
+                    DefinitionIndex::new(dir_trees, DefinitionKind::SCRIPTED).map(Arc::new)
+                })
+            })
+            .poll()
+            .map(|result| result.clone())
+    }
+}
+
+struct Viewer<'a, 'b> {
+    ctx: egui::Context,
+    context: WorkspaceContext<'a>,
+    tab_state: &'b mut TabState,
+    // Tabs opened from within other tabs, along with where to go if it's a file.
+    opened_tabs: Vec<(Tab, Option<FileTarget>)>,
+}
+
+impl TabViewer for Viewer<'_, '_> {
+    type Tab = Tab;
+
+    fn title(&mut self, tab: &mut Tab) -> egui::WidgetText {
+        tab.title().into()
+    }
+
+    fn id(&mut self, tab: &mut Tab) -> egui::Id {
+        egui::Id::new(&*tab)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Tab) {
+        match tab {
+            Tab::Localization => self.localization_table(ui),
+            Tab::File {
+                full_path,
+                relative_path,
+            } => match self.tab_state.file_view(full_path, relative_path) {
+                Ok(file_view) => {
+                    let action = file_view.ui(ui);
+                    self.handle_view_action(action);
+                }
+                Err(error) => error_label(ui, error),
+            },
+            Tab::Search { query } => self.search_results(ui, query),
+            Tab::Inspector { key } => self.inspector(ui, key),
+            Tab::Events => self.events(ui),
+            Tab::EventGraph => self.event_graph(ui),
+            Tab::Decisions => self.decisions(ui),
+            Tab::Modifiers => self.modifiers(ui),
+            Tab::Scripted => self.scripted(ui),
+            Tab::Defines => self.defines(ui),
+            Tab::History => self.history(ui),
+        }
+    }
+
+    fn on_close(&mut self, tab: &mut Tab) -> OnCloseResponse {
+        match tab {
+            Tab::File { full_path, .. } => {
+                self.tab_state.file_views.remove(full_path);
+            }
+            Tab::Search { query } => {
+                self.tab_state.search_results.remove(query);
+            }
+            Tab::Inspector { key } => {
+                self.tab_state.inspected_keys.remove(key);
+            }
+            Tab::Events => {
+                self.tab_state.event_view = None;
+            }
+            Tab::EventGraph => {
+                self.tab_state.event_graph = None;
+            }
+            Tab::Decisions => {
+                self.tab_state.decision_view = None;
+            }
+            Tab::Modifiers => {
+                self.tab_state.modifier_view = None;
+            }
+            Tab::Scripted => {
+                self.tab_state.scripted_view = None;
+            }
+            Tab::Defines => {
+                self.tab_state.define_view = None;
+            }
+            Tab::History => {
+                self.tab_state.history_view = None;
+            }
+            Tab::Localization => {}
+        }
+
+        OnCloseResponse::Close
+    }
+
+    fn scroll_bars(&self, _tab: &Tab) -> [bool; 2] {
+        // The views have their own scroll areas.
+        [false, false]
+    }
+}
+
+impl Viewer<'_, '_> {
+    fn localization_table(&mut self, ui: &mut egui::Ui) {
+        let Some(localization_table) = self.context.localization_table.as_deref_mut() else {
+            ui.weak("Open a game or mod directory to see its localization.");
+            return;
+        };
+
+        if let Some(key) = localization_table.ui(ui, self.context.localization_filter) {
+            self.opened_tabs.push((Tab::Inspector { key }, None));
+        }
+    }
+
+    fn search_results(&mut self, ui: &mut egui::Ui, query: &str) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to search it.");
+            return;
+        };
+
+        let results = self
+            .tab_state
+            .search_results
+            .entry(query.to_owned())
+            .or_insert_with(|| {
+                let query_lowercase = query.to_lowercase();
+                let files = db
+                    .get_files(None)?
+                    .into_iter()
+                    .filter(|file| file.relative_path.to_lowercase().contains(&query_lowercase))
+                    .collect();
+                let keys =
+                    db.get_localization_keys_with_files(database::Language::values(), query)?;
+
+                Ok(SearchResults { files, keys })
+            });
+
+        let results = match results {
+            Ok(results) => results,
+            Err(error) => {
+                error_label(ui, error);
+                return;
+            }
+        };
+
+        ui.label(format!(
+            "{} file(s) and {} localization key(s) match \"{}\".",
+            results.files.len(),
+            results.keys.len(),
+            query
+        ));
+
+        ui.separator();
+
+        let available_height = ui.available_height();
+
+        egui_extras::TableBuilder::new(ui)
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .striped(true)
+            .resizable(true)
+            .max_scroll_height(available_height)
+            .header(20.0, |mut header| {
+                for title in ["Kind", "Name", "Language", "Value"] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                let file_count = results.files.len();
+
+                body.rows(20.0, file_count + results.keys.len(), |mut row| {
+                    let idx = row.index();
+
+                    if let Some(file) = results.files.get(idx) {
+                        row.col(|ui| {
+                            ui.label("File");
+                        });
+                        row.col(|ui| {
+                            // The open tabs are persisted as text, so a file whose path isn't
+                            // valid UTF-8 can't be opened in one.
+                            if file.has_raw_path() {
+                                ui.label(&file.relative_path)
+                                    .on_hover_text("The path isn't valid UTF-8.");
+                            } else if link(ui, &file.relative_path).clicked() {
+                                self.opened_tabs.push((
+                                    Tab::File {
+                                        full_path: file.path(),
+                                        relative_path: PathBuf::from(&file.relative_path),
+                                    },
+                                    None,
+                                ));
+                            }
+                        });
+                        row.col(|_| {});
+                        row.col(|_| {});
+                    } else if let Some((key, file)) = results.keys.get(idx - file_count) {
+                        row.col(|ui| {
+                            ui.label("Key");
+                        });
+                        row.col(|ui| {
+                            if link(ui, &key.key).on_hover_text(file).clicked() {
+                                self.opened_tabs.push((
+                                    Tab::Inspector {
+                                        key: key.key.clone(),
+                                    },
+                                    None,
+                                ));
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(&key.language);
+                        });
+                        row.col(|ui| {
+                            ui.label(&key.value);
+                        });
+                    }
+                });
+            });
+    }
+
+    fn inspector(&mut self, ui: &mut egui::Ui, key: &str) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to inspect its keys.");
+            return;
+        };
+
+        let values = self
+            .tab_state
+            .inspected_keys
+            .entry(key.to_owned())
+            .or_insert_with(|| {
+                db.get_localization_key(key, None)?
+                    .into_iter()
+                    .map(|(value, _)| {
+                        let file = db.get_file(value.file_id as u64)?;
+                        Ok((value, file))
+                    })
+                    .collect()
+            });
+
+        let values = match values {
+            Ok(values) => values,
+            Err(error) => {
+                error_label(ui, error);
+                return;
+            }
+        };
+
+        ui.heading(key);
+
+        if values.is_empty() {
+            ui.weak("The key isn't defined in any language.");
+            return;
+        }
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new(("inspector grid", key))
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for title in ["Language", "Value", "Revision", "File"] {
+                        ui.strong(title);
+                    }
+                    ui.end_row();
+
+                    for language in database::Language::values() {
+                        let Some((value, file)) = values
+                            .iter()
+                            .find(|(value, _)| value.language == language.name())
+                        else {
+                            continue;
+                        };
+
+                        ui.label(language.name());
+                        ui.label(&value.value);
+                        ui.label(
+                            value
+                                .revision
+                                .map(|revision| revision.to_string())
+                                .unwrap_or_default(),
+                        );
+
+                        if link(ui, &file.relative_path)
+                            .on_hover_text(format!("Show the definition in {}", file.full_path))
+                            .clicked()
+                        {
+                            self.opened_tabs.push((
+                                Tab::File {
+                                    full_path: file.path(),
+                                    relative_path: PathBuf::from(&file.relative_path),
+                                },
+                                Some(FileTarget::Text(format!("{}:", key))),
+                            ));
+                        }
+
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn events(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to browse its events.");
+            return;
+        };
+
+        let language = self.context.language;
+
+        // The localization is loaded again when another language is selected.
+        if self
+            .tab_state
+            .event_view
+            .as_ref()
+            .is_none_or(|event_view| event_view.language() != language)
+        {
+            let Some(result) = self.tab_state.event_index(&self.ctx, db) else {
+                loading_label(ui);
+                return;
+            };
+            let result = result.and_then(|index| {
+                let keys = db.get_localization_keys_for_language(language)?;
+                Ok((index, keys))
+            });
+            let (index, keys) = match result {
+                Ok(result) => result,
+                Err(error) => {
+                    error_label(ui, &error);
+                    return;
+                }
+            };
+
+            match &mut self.tab_state.event_view {
+                Some(event_view) => event_view.set_localization(language, keys),
+                None => self.tab_state.event_view = Some(EventView::new(index, language, keys)),
+            }
+        }
+
+        let action = self
+            .tab_state
+            .event_view
+            .as_mut()
+            .and_then(|event_view| event_view.ui(ui));
+        self.handle_view_action(action);
+    }
+
+    fn event_graph(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to see its events.");
+            return;
+        };
+
+        if self.tab_state.event_graph.is_none() {
+            match self.tab_state.event_index(&self.ctx, db) {
+                Some(Ok(index)) => self.tab_state.event_graph = Some(EventGraphView::new(index)),
+                Some(Err(error)) => {
+                    error_label(ui, &error);
+                    return;
+                }
+                None => {
+                    loading_label(ui);
+                    return;
+                }
+            }
+        }
+
+        let action = self
+            .tab_state
+            .event_graph
+            .as_mut()
+            .and_then(|event_graph| event_graph.ui(ui));
+        self.handle_view_action(action);
+    }
+
+    fn decisions(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to browse its decisions.");
+            return;
+        };
+
+        let loader = self
+            .tab_state
+            .decision_view
+            .get_or_insert_with(|| Loader::spawn(&self.ctx, db, DecisionView::new));
+
+        let Some(decision_view) = loaded(ui, loader) else {
+            return;
+        };
+
+        let action = decision_view.ui(
+            ui,
+            self.context.localization_table.as_deref(),
+            self.context.language,
+        );
+        self.handle_view_action(action);
+    }
+
+    fn modifiers(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to browse its modifiers.");
+            return;
+        };
+
+        let loader = self
+            .tab_state
+            .modifier_view
+            .get_or_insert_with(|| Loader::spawn(&self.ctx, db, ModifierView::new));
+
+        let Some(modifier_view) = loaded(ui, loader) else {
+            return;
+        };
+
+        let action = modifier_view.ui(
+            ui,
+            self.context.localization_table.as_deref(),
+            self.context.language,
+        );
+        self.handle_view_action(action);
+    }
+
+    fn scripted(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to browse its scripted triggers and effects.");
+            return;
+        };
+
+        if self.tab_state.scripted_view.is_none() {
+            match self.tab_state.scripted_index(&self.ctx, db) {
+                Some(Ok(index)) => self.tab_state.scripted_view = Some(ScriptedView::new(index)),
+                Some(Err(error)) => {
+                    error_label(ui, &error);
+                    return;
+                }
+                None => {
+                    loading_label(ui);
+                    return;
+                }
+            }
+        }
+
+        if let Some(name) = self.tab_state.pending_definition.take() {
+            self.go_to_definition(&name);
+        }
+
+        let action = self
+            .tab_state
+            .scripted_view
+            .as_mut()
+            .and_then(|scripted_view| scripted_view.ui(ui));
+        self.handle_view_action(action);
+    }
+
+    fn defines(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to browse its defines.");
+            return;
+        };
+
+        let loader = self
+            .tab_state
+            .define_view
+            .get_or_insert_with(|| Loader::spawn(&self.ctx, db, DefineView::new));
+
+        let Some(define_view) = loaded(ui, loader) else {
+            return;
+        };
+
+        let action = define_view.ui(ui);
+        self.handle_view_action(action);
+    }
+
+    fn history(&mut self, ui: &mut egui::Ui) {
+        let Some(db) = self.context.database else {
+            ui.weak("Open a game or mod directory to browse its history.");
+            return;
+        };
+
+        let loader = self
+            .tab_state
+            .history_view
+            .get_or_insert_with(|| Loader::spawn(&self.ctx, db, HistoryView::new));
+
+        let Some(history_view) = loaded(ui, loader) else {
+            return;
+        };
+
+        let action = history_view.ui(ui);
+        self.handle_view_action(action);
+    }
+
+    // Opens the definition if there's only one; otherwise shows the definitions, or the calls if
+    // there aren't any, in the scripted items view.
+    fn go_to_definition(&mut self, name: &str) {
+        let Some(db) = self.context.database else {
+            return;
+        };
+
+        let index = match self.tab_state.scripted_index(&self.ctx, db) {
+            Some(Ok(index)) => index,
+            Some(Err(_)) => {
+                // The view shows the error.
+                self.opened_tabs.push((Tab::Scripted, None));
+                return;
+            }
+            None => {
+                // The view goes to the definition once the index is built.
+                self.tab_state.pending_definition = Some(name.to_owned());
+                self.opened_tabs.push((Tab::Scripted, None));
+                return;
+            }
+        };
+
+        let mut definitions = index.definitions_of(name);
+
+        if let (Some(definition), None) = (definitions.next(), definitions.next()) {
+            let location = &definition.location;
+
+            self.opened_tabs.push((
+                Tab::File {
+                    full_path: location.full_path.clone(),
+                    relative_path: location.relative_path.clone(),
+                },
+                Some(FileTarget::Line(location.line)),
+            ));
+            return;
+        }
+
+        self.tab_state
+            .scripted_view
+            .get_or_insert_with(|| ScriptedView::new(index.clone()))
+            .select(name);
+        self.opened_tabs.push((Tab::Scripted, None));
+    }
+
+    fn handle_view_action(&mut self, action: Option<ViewAction>) {
+        match action {
+            Some(ViewAction::OpenFile {
+                full_path,
+                relative_path,
+                line,
+            }) => self.opened_tabs.push((
+                Tab::File {
+                    full_path,
+                    relative_path,
+                },
+                Some(FileTarget::Line(line)),
+            )),
+            Some(ViewAction::InspectKey(key)) => {
+                self.opened_tabs.push((Tab::Inspector { key }, None));
+            }
+            Some(ViewAction::GoToDefinition(name)) => self.go_to_definition(&name),
+            None => {}
+        }
+    }
+}
+
+pub fn link(ui: &mut egui::Ui, text: &str) -> egui::Response {
+    ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+}
+
+// A link to the definition at the location.
+pub fn location_link(ui: &mut egui::Ui, location: &Location) -> Option<ViewAction> {
+    link(
+        ui,
+        &format!("{}:{}", location.relative_path.display(), location.line),
+    )
+    .on_hover_text(location.full_path.display().to_string())
+    .clicked()
+    .then(|| ViewAction::OpenFile {
+        full_path: location.full_path.clone(),
+        relative_path: location.relative_path.clone(),
+        line: location.line,
+    })
+}
+
+// Shows that the value is still being built, or the error if building it failed.
+fn loaded<'a, T>(ui: &mut egui::Ui, loader: &'a mut Loader<T>) -> Option<&'a mut T> {
+    match loader.poll() {
+        Some(Ok(value)) => Some(value),
+        Some(Err(error)) => {
+            error_label(ui, error);
+            None
+        }
+        None => {
+            loading_label(ui);
+            None
+        }
+    }
+}
+
+fn loading_label(ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.spinner();
+        ui.weak("Indexing the script files…");
+    });
+}
+
+fn error_label(ui: &mut egui::Ui, error: &Error) {
+    ui.colored_label(
+        egui::Color32::YELLOW,
+        format!("Error: {}.", error.description()),
+    );
+}
@@ -0,0 +1,291 @@
+use crate::core::*;
+use crate::database::{Database, Language};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Tsv => "TSV",
+            Self::Json => "JSON",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::values()
+            .iter()
+            .copied()
+            .find(|value| value.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn values() -> &'static [Self] {
+        &[Self::Csv, Self::Tsv, Self::Json]
+    }
+
+    // Delimiter for the delimiter-separated formats.
+    pub(crate) fn delimiter(&self) -> Option<char> {
+        match self {
+            Self::Csv => Some(','),
+            Self::Tsv => Some('\t'),
+            Self::Json => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExportedValue {
+    pub value: String,
+    pub file: String,
+    pub revision: Option<i32>,
+}
+
+// A single localization key with its value for each of the exported languages (in the same order
+// as the languages). The value is missing if the key isn't defined for a language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedKey {
+    pub key: String,
+    pub values: Vec<Option<ExportedValue>>,
+}
+
+pub struct Export {
+    languages: Vec<Language>,
+    keys: Vec<ExportedKey>,
+}
+
+impl Export {
+    // The UTF-8 BOM makes spreadsheet applications detect the encoding of delimited files.
+    pub(crate) const BYTE_ORDER_MARK: &'static str = "\u{feff}";
+    pub(crate) const KEY_COLUMN: &'static str = "key";
+    pub(crate) const FILE_COLUMN_SUFFIX: &'static str = "_file";
+    pub(crate) const REVISION_COLUMN_SUFFIX: &'static str = "_revision";
+
+    // Collects the keys of the given languages whose key or value contains the filter.
+    pub fn new(database: &mut Database, languages: &[Language], filter: &str) -> Result<Self> {
+        if languages.is_empty() {
+            fail!("no language selected for the export");
+        }
+
+        let mut keys: Vec<ExportedKey> = Vec::new();
+
+        for (key, file) in database.get_localization_keys_with_files(languages, filter)? {
+            let Some(position) = languages
+                .iter()
+                .position(|language| language.name() == key.language)
+            else {
+                continue;
+            };
+
+            if keys.last().is_none_or(|last| last.key != key.key) {
+                keys.push(ExportedKey {
+                    key: key.key.clone(),
+                    values: vec![None; languages.len()],
+                });
+            }
+
+            if let Some(last) = keys.last_mut() {
+                last.values[position] = Some(ExportedValue {
+                    value: key.value,
+                    file,
+                    revision: key.revision,
+                });
+            }
+        }
+
+        Ok(Self {
+            languages: languages.to_owned(),
+            keys,
+        })
+    }
+
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
+
+    pub fn keys(&self) -> &[ExportedKey] {
+        &self.keys
+    }
+
+    pub fn render(&self, format: ExportFormat) -> Result<String> {
+        match format.delimiter() {
+            Some(delimiter) => Ok(self.to_delimited(delimiter)),
+            None => self.to_json(),
+        }
+    }
+
+    pub fn write(&self, path: &Path, format: ExportFormat) -> Result<()> {
+        fs::write(path, self.render(format)?)?;
+        Ok(())
+    }
+
+    // The columns are the key followed by the value, the file and the revision for each language,
+    // e.g. key,l_english,l_english_file,l_english_revision,l_german,...
+    fn to_delimited(&self, delimiter: char) -> String {
+        let mut header = vec![Self::KEY_COLUMN.to_owned()];
+
+        for language in &self.languages {
+            header.push(language.specifier().to_owned());
+            header.push(format!(
+                "{}{}",
+                language.specifier(),
+                Self::FILE_COLUMN_SUFFIX
+            ));
+            header.push(format!(
+                "{}{}",
+                language.specifier(),
+                Self::REVISION_COLUMN_SUFFIX
+            ));
+        }
+
+        let mut content = Self::BYTE_ORDER_MARK.to_owned();
+        Self::push_record(&mut content, &header, delimiter);
+
+        for key in &self.keys {
+            let mut record = vec![key.key.clone()];
+
+            for value in &key.values {
+                match value {
+                    Some(value) => {
+                        record.push(value.value.clone());
+                        record.push(value.file.clone());
+                        record.push(
+                            value
+                                .revision
+                                .map(|revision| revision.to_string())
+                                .unwrap_or_default(),
+                        );
+                    }
+                    None => record.extend([String::new(), String::new(), String::new()]),
+                }
+            }
+
+            Self::push_record(&mut content, &record, delimiter);
+        }
+
+        content
+    }
+
+    fn to_json(&self) -> Result<String> {
+        let keys: Vec<_> = self
+            .keys
+            .iter()
+            .map(|key| {
+                let values: serde_json::Map<String, serde_json::Value> = self
+                    .languages
+                    .iter()
+                    .zip(&key.values)
+                    .filter_map(|(language, value)| {
+                        let value = serde_json::to_value(value.as_ref()?).ok()?;
+                        Some((language.specifier().to_owned(), value))
+                    })
+                    .collect();
+
+                serde_json::json!({ "key": key.key, "values": values })
+            })
+            .collect();
+
+        let languages: Vec<_> = self
+            .languages
+            .iter()
+            .map(|language| language.specifier())
+            .collect();
+
+        let json = serde_json::json!({ "languages": languages, "keys": keys });
+        serde_json::to_string_pretty(&json).map_err(|error| error!("{}", error))
+    }
+
+    fn push_record(content: &mut String, record: &[String], delimiter: char) {
+        let fields: Vec<_> = record
+            .iter()
+            .map(|field| Self::quote_field(field, delimiter))
+            .collect();
+
+        content.push_str(&fields.join(&delimiter.to_string()));
+        content.push_str("\r\n");
+    }
+
+    // Fields which contain the delimiter, quotes or line breaks are enclosed in quotes, with the
+    // quotes inside doubled (RFC 4180).
+    fn quote_field(field: &str, delimiter: char) -> String {
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(value: &str, revision: Option<i32>) -> Option<ExportedValue> {
+        Some(ExportedValue {
+            value: value.to_owned(),
+            file: "localization/a_l_english.yml".to_owned(),
+            revision,
+        })
+    }
+
+    #[test]
+    fn fields_are_quoted_when_needed() {
+        assert_eq!(Export::quote_field("plain", ','), "plain");
+        assert_eq!(Export::quote_field("a,b", ','), "\"a,b\"");
+        assert_eq!(Export::quote_field("a,b", '\t'), "a,b");
+        assert_eq!(Export::quote_field("a\tb", '\t'), "\"a\tb\"");
+        assert_eq!(Export::quote_field("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(Export::quote_field("a\nb", ','), "\"a\nb\"");
+        assert_eq!(Export::quote_field("a\r\nb", '\t'), "\"a\r\nb\"");
+        assert_eq!(Export::quote_field("", ','), "");
+    }
+
+    #[test]
+    fn delimited_export_has_a_header_and_a_record_per_key() {
+        let export = Export {
+            languages: vec![Language::English, Language::German],
+            keys: vec![
+                ExportedKey {
+                    key: "a".to_owned(),
+                    values: vec![value("one, \"two\"", Some(1)), None],
+                },
+                ExportedKey {
+                    key: "b".to_owned(),
+                    values: vec![value("line\nbreak", None), value("tab\there", Some(0))],
+                },
+            ],
+        };
+
+        assert_eq!(
+            export.to_delimited(','),
+            "\u{feff}key,l_english,l_english_file,l_english_revision,\
+             l_german,l_german_file,l_german_revision\r\n\
+             a,\"one, \"\"two\"\"\",localization/a_l_english.yml,1,,,\r\n\
+             b,\"line\nbreak\",localization/a_l_english.yml,,\
+             tab\there,localization/a_l_english.yml,0\r\n"
+        );
+        assert_eq!(
+            export.to_delimited('\t'),
+            "\u{feff}key\tl_english\tl_english_file\tl_english_revision\t\
+             l_german\tl_german_file\tl_german_revision\r\n\
+             a\t\"one, \"\"two\"\"\"\tlocalization/a_l_english.yml\t1\t\t\t\r\n\
+             b\t\"line\nbreak\"\tlocalization/a_l_english.yml\t\t\
+             \"tab\there\"\tlocalization/a_l_english.yml\t0\r\n"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// Shell-style wildcard patterns for matching relative paths (with `/` as the separator):
+//
+// * `*` matches any run of characters except `/`
+// * `**` matches any run of characters, including `/`; `**/` also matches no directory at all
+// * `?` matches a single character except `/`
+// * `[abc]`, `[a-z]` and `[!abc]` match a single character of (or not of) the set
+// * `\` escapes the next character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+    case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    AnyChar,
+    Star,
+    DoubleStar,
+    // `**/`, which matches nothing or any run of characters ending with `/`.
+    Directories,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Self {
+        Self::with_case_sensitivity(pattern, true)
+    }
+
+    pub fn case_insensitive(pattern: &str) -> Self {
+        Self::with_case_sensitivity(pattern, false)
+    }
+
+    // Whether the text contains any wildcards, i.e. whether it should be treated as a pattern
+    // rather than as plain text.
+    pub fn is_pattern(text: &str) -> bool {
+        text.contains(['*', '?', '['])
+    }
+
+    fn with_case_sensitivity(pattern: &str, case_sensitive: bool) -> Self {
+        let fold = |c: char| {
+            if case_sensitive {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        };
+
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '\\' => Token::Char(fold(chars.next().unwrap_or('\\'))),
+                '?' => Token::AnyChar,
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        Token::Directories
+                    } else {
+                        Token::DoubleStar
+                    }
+                }
+                '*' => Token::Star,
+                '[' => {
+                    // An unterminated class is matched literally.
+                    let rest: String = chars.clone().collect();
+
+                    match Self::parse_class(&rest, fold) {
+                        Some((token, len)) => {
+                            for _ in 0..len {
+                                chars.next();
+                            }
+                            token
+                        }
+                        None => Token::Char('['),
+                    }
+                }
+                c => Token::Char(fold(c)),
+            };
+
+            tokens.push(token);
+        }
+
+        Self {
+            tokens,
+            case_sensitive,
+        }
+    }
+
+    // Parses the part of a class after the opening bracket. Returns the token and the number of
+    // characters it takes up, including the closing bracket.
+    fn parse_class(text: &str, fold: impl Fn(char) -> char) -> Option<(Token, usize)> {
+        let mut chars = text.chars().peekable();
+        let mut len = 0;
+        let mut negated = false;
+        let mut ranges = Vec::new();
+
+        if let Some('!' | '^') = chars.peek() {
+            chars.next();
+            len += 1;
+            negated = true;
+        }
+
+        loop {
+            let c = chars.next()?;
+            len += 1;
+
+            // A closing bracket right after the opening one is a part of the set.
+            if c == ']' && !ranges.is_empty() {
+                return Some((Token::Class { negated, ranges }, len));
+            }
+
+            let start = fold(c);
+            let mut end = start;
+
+            if chars.peek() == Some(&'-') {
+                let mut lookahead = chars.clone();
+                lookahead.next();
+
+                if let Some(range_end) = lookahead.next().filter(|c| *c != ']') {
+                    chars = lookahead;
+                    len += 2;
+                    end = fold(range_end);
+                }
+            }
+
+            ranges.push((start, end));
+        }
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = if self.case_sensitive {
+            text.chars().collect()
+        } else {
+            text.chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect()
+        };
+
+        // next[j] tells whether the tokens after the current one match text[j..]. The tokens are
+        // processed from the last one, so that every token is only looked at once per character.
+        let mut next = vec![false; text.len() + 1];
+        next[text.len()] = true;
+
+        for token in self.tokens.iter().rev() {
+            let mut current = vec![false; text.len() + 1];
+            // Whether some run of characters starting at j and ending with `/` is followed by a
+            // match of the remaining tokens.
+            let mut directories_match = false;
+
+            for j in (0..=text.len()).rev() {
+                let c = text.get(j).copied();
+
+                if c == Some('/') && next[j + 1] {
+                    directories_match = true;
+                }
+
+                current[j] = match token {
+                    Token::Char(expected) => c == Some(*expected) && next[j + 1],
+                    Token::AnyChar => c.is_some_and(|c| c != '/') && next[j + 1],
+                    Token::Star => next[j] || (c.is_some_and(|c| c != '/') && current[j + 1]),
+                    Token::DoubleStar => next[j] || (c.is_some() && current[j + 1]),
+                    Token::Directories => next[j] || directories_match,
+                    Token::Class { negated, ranges } => {
+                        c.is_some_and(|c| {
+                            c != '/'
+                                && ranges
+                                    .iter()
+                                    .any(|(start, end)| (*start..=*end).contains(&c))
+                                    != *negated
+                        }) && next[j + 1]
+                    }
+                };
+            }
+
+            next = current;
+        }
+
+        next[0]
+    }
+}
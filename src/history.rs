@@ -0,0 +1,320 @@
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::script::{self, Block, Location, ScriptFile, Value};
+use std::fmt;
+
+// The entities of the files in `history`, e.g. the countries, provinces and characters, with the
+// statements which set them up, either from the start or at a date:
+//
+// capital = 183
+// 1444.11.11 = { monarch = { name = "Charles" } }
+//
+// EU4 and HOI4 have a file per entity; CK3 has many entities in a file, as blocks like
+// `163110 = { ... }`. HOI4 states keep the statements in a `history` block.
+#[derive(Debug, Default)]
+pub struct HistoryIndex {
+    entities: Vec<Entity>,
+    // The subfolders of `history` the entities are in, e.g. `countries`, sorted.
+    kinds: Vec<String>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entity {
+    // The subfolder of `history` the entity is in, e.g. `countries`.
+    pub kind: String,
+    pub name: String,
+    pub location: Location,
+    // The undated statements first, then the dated ones by date; statements of the same date are
+    // in the order they're written.
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    // The statements outside of a dated block have no date.
+    pub date: Option<Date>,
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+// A value of the state of an entity at a date, along with the statement which set it.
+#[derive(Debug, Clone, Copy)]
+pub struct StateValue<'a> {
+    // The key without `add_`, e.g. `core` for `add_core = FRA`.
+    pub key: &'a str,
+    pub entry: &'a HistoryEntry,
+}
+
+impl Date {
+    // Reads dates like `1444.11.11`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('.');
+        let date = Self {
+            year: parts.next()?.parse().ok()?,
+            month: parts.next()?.parse().ok()?,
+            day: parts.next()?.parse().ok()?,
+        };
+
+        parts.next().is_none().then_some(date)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.year, self.month, self.day)
+    }
+}
+
+impl Entity {
+    pub fn location_of(&self, entry: &HistoryEntry) -> Location {
+        Location {
+            line: entry.line,
+            ..self.location.clone()
+        }
+    }
+
+    // The dates of the dated statements, in order.
+    pub fn dates(&self) -> Vec<Date> {
+        let mut dates: Vec<_> = self.entries.iter().filter_map(|entry| entry.date).collect();
+        dates.dedup();
+        dates
+    }
+
+    // The state after the statements up to and including the date are applied in order, or after
+    // the undated statements if there's no date. A statement sets the value of its key, except for
+    // `add_<key>` and `remove_<key>`, which add values to the key and remove them, like
+    // `add_core = FRA`.
+    pub fn state_at(&self, date: Option<Date>) -> Vec<StateValue<'_>> {
+        let mut state: Vec<StateValue> = Vec::new();
+
+        for entry in self.entries.iter().take_while(|entry| entry.date <= date) {
+            if let Some(key) = entry.key.strip_prefix("add_") {
+                if !state
+                    .iter()
+                    .any(|value| value.key == key && value.entry.value == entry.value)
+                {
+                    state.push(StateValue { key, entry });
+                }
+            } else if let Some(key) = entry.key.strip_prefix("remove_") {
+                state.retain(|value| value.key != key || value.entry.value != entry.value);
+            } else {
+                let value = StateValue {
+                    key: &entry.key,
+                    entry,
+                };
+
+                match state.iter().position(|value| value.key == entry.key) {
+                    Some(idx) => state[idx] = value,
+                    None => state.push(value),
+                }
+            }
+        }
+
+        state
+    }
+}
+
+impl HistoryIndex {
+    const FOLDER: &'static str = "history";
+
+    pub fn new(dir_trees: &[DirTree]) -> Result<Self> {
+        let mut index = Self::default();
+        let (files, warnings) = script::load_folder(dir_trees, Self::FOLDER)?;
+        index.warnings = warnings;
+
+        for file in &files {
+            let kind = file
+                .relative_path
+                .strip_prefix(Self::FOLDER)
+                .ok()
+                .and_then(|path| path.parent())
+                .and_then(|path| path.iter().next())
+                .map_or(String::new(), |kind| kind.to_string_lossy().into_owned());
+
+            Self::read_entities(file, kind, &mut index.entities);
+        }
+
+        index.kinds = index
+            .entities
+            .iter()
+            .map(|entity| entity.kind.clone())
+            .collect();
+        index.kinds.sort();
+        index.kinds.dedup();
+
+        Ok(index)
+    }
+
+    // The entities in the order of their files.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn kinds(&self) -> &[String] {
+        &self.kinds
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    fn read_entities(file: &ScriptFile, kind: String, entities: &mut Vec<Entity>) {
+        // A file with statements at the top level is about a single entity, which is named after
+        // the file, e.g. `FRA - France`.
+        let is_single_entity = file.block.fields().any(|(key, _, value)| {
+            Date::parse(&key.text).is_some() || matches!(value, Value::Scalar(_))
+        });
+
+        if is_single_entity {
+            let name = file
+                .relative_path
+                .file_stem()
+                .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+
+            Self::push_entity(file, kind, name, 1, &file.block, entities);
+            return;
+        }
+
+        for (key, _, value) in file.block.fields() {
+            let Value::Block(block) = value else {
+                continue;
+            };
+
+            // HOI4 states are all named `state`, with an `id`.
+            let name = match block.get_scalar("id") {
+                Some(id) => format!("{} {}", key.text, id),
+                None => key.text.clone(),
+            };
+
+            Self::push_entity(file, kind.clone(), name, key.line, block, entities);
+        }
+    }
+
+    fn push_entity(
+        file: &ScriptFile,
+        kind: String,
+        name: String,
+        line: usize,
+        block: &Block,
+        entities: &mut Vec<Entity>,
+    ) {
+        let mut entries = Vec::new();
+        Self::read_entries(block, None, &mut entries);
+
+        if entries.is_empty() {
+            return;
+        }
+
+        entries.sort_by_key(|entry| entry.date);
+
+        entities.push(Entity {
+            kind,
+            name,
+            location: file.location(line),
+            entries,
+        });
+    }
+
+    fn read_entries(block: &Block, date: Option<Date>, entries: &mut Vec<HistoryEntry>) {
+        for (key, _, value) in block.fields() {
+            match (Date::parse(&key.text), value) {
+                (Some(date), Value::Block(dated)) => Self::read_entries(dated, Some(date), entries),
+                (None, Value::Block(history)) if key.text == "history" && date.is_none() => {
+                    Self::read_entries(history, None, entries)
+                }
+                _ => entries.push(HistoryEntry {
+                    date,
+                    key: key.text.clone(),
+                    value: value.to_script(),
+                    line: key.line,
+                }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    #[test]
+    fn state_is_resolved_at_dates() {
+        let temp_dir = TempDir::new("history");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("history/countries")).unwrap();
+        fs::create_dir_all(root.join("history/characters")).unwrap();
+        fs::write(
+            root.join("history/countries/FRA - France.txt"),
+            "capital = 183\n\
+             add_core = 183\n\
+             1500.1.1 = { capital = 200 remove_core = 183 }\n\
+             1444.11.11 = { add_core = 200 }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("history/characters/a.txt"),
+            "1 = { name = Hugh 940.1.1 = { birth = yes } }\n\
+             2 = { name = Robert }\n",
+        )
+        .unwrap();
+
+        let dir_tree = DirTree::new(root, &WalkOptions::default()).unwrap();
+        let index = HistoryIndex::new(&[dir_tree]).unwrap();
+
+        assert_eq!(index.kinds(), ["characters", "countries"]);
+        assert_eq!(
+            index
+                .entities()
+                .iter()
+                .map(|entity| (entity.kind.as_str(), entity.name.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("characters", "1"),
+                ("characters", "2"),
+                ("countries", "FRA - France")
+            ]
+        );
+
+        let france = &index.entities()[2];
+        let state = |date| {
+            france
+                .state_at(Date::parse(date))
+                .iter()
+                .map(|value| (value.key, value.entry.value.as_str(), value.entry.line))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            france.dates(),
+            [
+                Date::parse("1444.11.11").unwrap(),
+                Date::parse("1500.1.1").unwrap()
+            ]
+        );
+        assert_eq!(state(""), [("capital", "183", 1), ("core", "183", 2)]);
+        assert_eq!(
+            state("1450.1.1"),
+            [
+                ("capital", "183", 1),
+                ("core", "183", 2),
+                ("core", "200", 4)
+            ]
+        );
+        assert_eq!(
+            state("1600.1.1"),
+            [("capital", "200", 3), ("core", "200", 4)]
+        );
+    }
+}
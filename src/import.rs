@@ -0,0 +1,562 @@
+use crate::core::*;
+use crate::database::{Database, Language, LocalizationUpdate};
+use crate::export::{Export, ExportFormat};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// A single row of an imported spreadsheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedKey {
+    pub key: String,
+    pub value: String,
+    // Value in the source language at the time of the export, if the spreadsheet contains it.
+    pub source_value: Option<String>,
+    // File the key was exported from (in the imported language or in the source language).
+    pub file: Option<String>,
+}
+
+impl ImportedKey {
+    // Reads a file in one of the export formats. Delimited files must contain the `key` column and
+    // a column for the imported language (e.g. `l_german`). JSON files are either in the export
+    // format or a flat object which maps keys to values.
+    pub fn read(
+        path: &Path,
+        format: Option<ExportFormat>,
+        language: Language,
+        source_language: Language,
+    ) -> Result<Vec<Self>> {
+        let format = match format {
+            Some(format) => format,
+            None => path
+                .extension()
+                .and_then(|extension| ExportFormat::from_extension(&extension.to_string_lossy()))
+                .ok_or_else(|| {
+                    error!(
+                        "failed to determine the format of `{}` from its extension",
+                        path.display()
+                    )
+                })?,
+        };
+
+        let content = fs::read_to_string(path)?;
+        let content = content
+            .strip_prefix(Export::BYTE_ORDER_MARK)
+            .unwrap_or(&content);
+
+        match format.delimiter() {
+            Some(delimiter) => Self::from_delimited(content, delimiter, language, source_language),
+            None => Self::from_json(content, language, source_language),
+        }
+    }
+
+    fn from_delimited(
+        content: &str,
+        delimiter: char,
+        language: Language,
+        source_language: Language,
+    ) -> Result<Vec<Self>> {
+        let mut records = parse_delimited(content, delimiter)?.into_iter();
+
+        let Some(header) = records.next() else {
+            return Ok(Vec::new());
+        };
+
+        let column = |name: &str| header.iter().position(|column| column == name);
+        let file_column = |language: Language| {
+            column(&format!(
+                "{}{}",
+                language.specifier(),
+                Export::FILE_COLUMN_SUFFIX
+            ))
+        };
+
+        let Some(key_column) = column(Export::KEY_COLUMN) else {
+            fail!("missing the `{}` column", Export::KEY_COLUMN);
+        };
+
+        let Some(value_column) = column(language.specifier()) else {
+            fail!("missing the `{}` column", language.specifier());
+        };
+
+        let source_column =
+            column(source_language.specifier()).filter(|_| language != source_language);
+        let file_columns = [file_column(language), file_column(source_language)];
+
+        let field = |record: &[String], column: Option<usize>| {
+            column
+                .and_then(|column| record.get(column))
+                .filter(|field| !field.is_empty())
+                .cloned()
+        };
+
+        Ok(records
+            .filter(|record| record.iter().any(|field| !field.is_empty()))
+            .map(|record| Self {
+                key: field(&record, Some(key_column)).unwrap_or_default(),
+                value: field(&record, Some(value_column)).unwrap_or_default(),
+                source_value: field(&record, source_column),
+                file: file_columns
+                    .iter()
+                    .find_map(|column| field(&record, *column)),
+            })
+            .collect())
+    }
+
+    fn from_json(
+        content: &str,
+        language: Language,
+        source_language: Language,
+    ) -> Result<Vec<Self>> {
+        let json: serde_json::Value =
+            serde_json::from_str(content).map_err(|error| error!("{}", error))?;
+
+        // A flat object which maps keys to values.
+        if let Some(object) = json.as_object()
+            && !object.contains_key("keys")
+        {
+            return object
+                .iter()
+                .map(|(key, value)| {
+                    let Some(value) = value.as_str() else {
+                        fail!("value of key `{}` is not a string", key);
+                    };
+
+                    Ok(Self {
+                        key: key.clone(),
+                        value: value.to_owned(),
+                        source_value: None,
+                        file: None,
+                    })
+                })
+                .collect();
+        }
+
+        let Some(keys) = json.get("keys").and_then(|keys| keys.as_array()) else {
+            fail!("expected an object with a `keys` array");
+        };
+
+        let string = |value: Option<&serde_json::Value>, field: &str| {
+            value
+                .and_then(|value| value.get(field))
+                .and_then(|field| field.as_str())
+                .map(str::to_owned)
+        };
+
+        keys.iter()
+            .map(|entry| {
+                let Some(key) = entry.get("key").and_then(|key| key.as_str()) else {
+                    fail!("entry without a `key`: `{}`", entry);
+                };
+
+                let values = entry.get("values");
+                let value = values.and_then(|values| values.get(language.specifier()));
+                let source = values
+                    .and_then(|values| values.get(source_language.specifier()))
+                    .filter(|_| language != source_language);
+
+                Ok(Self {
+                    key: key.to_owned(),
+                    value: string(value, "value").unwrap_or_default(),
+                    source_value: string(source, "value"),
+                    file: string(value, "file").or_else(|| string(source, "file")),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportChangeKind {
+    Changed,
+    New,
+    Removed,
+}
+
+impl ImportChangeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Changed => "Changed",
+            Self::New => "New",
+            Self::Removed => "Removed",
+        }
+    }
+}
+
+// The source string was changed after the export was made, so the translation may be outdated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportConflict {
+    pub exported_source_value: String,
+    pub current_source_value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportChange {
+    pub kind: ImportChangeKind,
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub conflict: Option<ImportConflict>,
+    // Name of the file (without the _l_<language>.yml suffix) new keys are added to.
+    file_name: String,
+}
+
+pub struct ImportPreview {
+    language: Language,
+    changes: Vec<ImportChange>,
+    unchanged: usize,
+}
+
+impl ImportPreview {
+    // File name used for new keys when the spreadsheet doesn't say which file they came from.
+    const DEFAULT_FILE_NAME: &'static str = "imported";
+
+    // Compares the imported keys with the current state of the database. Rows with an empty value
+    // remove the key; keys which aren't in the import are left untouched.
+    pub fn new(
+        database: &mut Database,
+        language: Language,
+        source_language: Language,
+        keys: Vec<ImportedKey>,
+    ) -> Result<Self> {
+        let mut current: HashMap<String, String> = HashMap::new();
+        let mut current_source: HashMap<String, String> = HashMap::new();
+
+        for (key, _) in
+            database.get_localization_keys_with_files(&[language, source_language], "")?
+        {
+            if key.language == language.name() {
+                current.insert(key.key, key.value);
+            } else {
+                current_source.insert(key.key, key.value);
+            }
+        }
+
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+
+        for imported in keys {
+            let old_value = current.get(&imported.key).cloned();
+
+            let kind = match (&old_value, imported.value.is_empty()) {
+                (None, true) => continue,
+                (None, false) => ImportChangeKind::New,
+                (Some(_), true) => ImportChangeKind::Removed,
+                (Some(old_value), false) if *old_value != imported.value => {
+                    ImportChangeKind::Changed
+                }
+                (Some(_), false) => {
+                    unchanged += 1;
+                    continue;
+                }
+            };
+
+            let conflict = match (&imported.source_value, current_source.get(&imported.key)) {
+                (Some(exported), Some(current)) if exported != current => Some(ImportConflict {
+                    exported_source_value: exported.clone(),
+                    current_source_value: current.clone(),
+                }),
+                _ => None,
+            };
+
+            let file_name = imported
+                .file
+                .as_deref()
+                .and_then(Self::file_name_without_suffix)
+                .unwrap_or(Self::DEFAULT_FILE_NAME)
+                .to_owned();
+
+            changes.push(ImportChange {
+                kind,
+                key: imported.key,
+                old_value,
+                new_value: (!imported.value.is_empty()).then_some(imported.value),
+                conflict,
+                file_name,
+            });
+        }
+
+        Ok(Self {
+            language,
+            changes,
+            unchanged,
+        })
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn changes(&self) -> &[ImportChange] {
+        &self.changes
+    }
+
+    pub fn unchanged_count(&self) -> usize {
+        self.unchanged
+    }
+
+    pub fn conflict_count(&self) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| change.conflict.is_some())
+            .count()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for change in &self.changes {
+            text.push_str(&format!(
+                "{:<8} {}: \"{}\" -> \"{}\"\n",
+                change.kind.name(),
+                change.key,
+                change.old_value.as_deref().unwrap_or_default(),
+                change.new_value.as_deref().unwrap_or_default()
+            ));
+
+            if let Some(conflict) = &change.conflict {
+                text.push_str(&format!(
+                    "         conflict: the source changed from \"{}\" to \"{}\" after the export\n",
+                    conflict.exported_source_value, conflict.current_source_value
+                ));
+            }
+        }
+
+        let count = |kind| {
+            self.changes
+                .iter()
+                .filter(|change| change.kind == kind)
+                .count()
+        };
+
+        text.push_str(&format!(
+            "{} changed, {} new, {} removed, {} unchanged, {} conflict(s).\n",
+            count(ImportChangeKind::Changed),
+            count(ImportChangeKind::New),
+            count(ImportChangeKind::Removed),
+            self.unchanged,
+            self.conflict_count()
+        ));
+        text
+    }
+
+    // Writes the changes into the localization files. Conflicting changes are skipped unless
+    // `include_conflicts` is set. Returns the number of applied changes.
+    pub fn apply(&self, database: &mut Database, include_conflicts: bool) -> Result<usize> {
+        let updates: Vec<_> = self
+            .changes
+            .iter()
+            .filter(|change| include_conflicts || change.conflict.is_none())
+            .map(|change| {
+                let key = change.key.clone();
+                let value = change.new_value.clone().unwrap_or_default();
+
+                match change.kind {
+                    ImportChangeKind::Changed => LocalizationUpdate::Change { key, value },
+                    ImportChangeKind::New => LocalizationUpdate::Add {
+                        key,
+                        value,
+                        file_name: change.file_name.clone(),
+                    },
+                    ImportChangeKind::Removed => LocalizationUpdate::Remove { key },
+                }
+            })
+            .collect();
+
+        if !updates.is_empty() {
+            database.update_localization_keys(self.language, &updates)?;
+        }
+
+        Ok(updates.len())
+    }
+
+    // localization/english/events_l_english.yml -> events
+    fn file_name_without_suffix(path: &str) -> Option<&str> {
+        let file_name = path.rsplit(['/', '\\']).next()?;
+        let stem = file_name.strip_suffix(".yml")?;
+        let (name, _) = stem.rsplit_once("_l_")?;
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+// Parses delimiter-separated records (RFC 4180): fields may be enclosed in quotes, in which case
+// they can contain the delimiter, line breaks and doubled quotes.
+fn parse_delimited(content: &str, delimiter: char) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else {
+            match c {
+                '"' if field.is_empty() => in_quotes = true,
+                '\r' if chars.peek() == Some(&'\n') => {}
+                '\n' => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                c if c == delimiter => record.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+    }
+
+    if in_quotes {
+        fail!("unterminated quoted field in record {}", records.len() + 1);
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+
+    fn imported(key: &str, value: &str, source_value: Option<&str>) -> ImportedKey {
+        ImportedKey {
+            key: key.to_owned(),
+            value: value.to_owned(),
+            source_value: source_value.map(str::to_owned),
+            file: None,
+        }
+    }
+
+    #[test]
+    fn delimited_records_are_parsed() {
+        let content =
+            "key,l_german\r\n\"a\",\"Zeile 1\r\nZeile 2\"\r\nb,\"say \"\"hi\"\", ok\"\r\nc,\r\n";
+
+        assert_eq!(
+            parse_delimited(content, ',').unwrap(),
+            [
+                vec!["key", "l_german"],
+                vec!["a", "Zeile 1\r\nZeile 2"],
+                vec!["b", "say \"hi\", ok"],
+                vec!["c", ""],
+            ]
+        );
+        assert_eq!(
+            parse_delimited("a\tb,c\n\"d\te\"\tf", '\t').unwrap(),
+            [vec!["a", "b,c"], vec!["d\te", "f"]]
+        );
+        assert!(parse_delimited("a,\"b\n", ',').is_err());
+    }
+
+    #[test]
+    fn delimited_columns_are_read_by_name() {
+        let content = "l_english,key,l_german,l_german_file\n\
+                       A,a,A de,localization/german/x_l_german.yml\n\
+                       ,,,\n\
+                       B,b,,\n";
+
+        assert_eq!(
+            ImportedKey::from_delimited(content, ',', Language::German, Language::English).unwrap(),
+            [
+                ImportedKey {
+                    file: Some("localization/german/x_l_german.yml".to_owned()),
+                    ..imported("a", "A de", Some("A"))
+                },
+                imported("b", "", Some("B")),
+            ]
+        );
+        assert!(
+            ImportedKey::from_delimited("key,l_french\n", ',', Language::German, Language::English)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn preview_detects_changes_and_conflicts() {
+        let temp_dir = TempDir::new("import");
+        let root = temp_dir.path().join("mod");
+        let database_file_path = temp_dir.path().join("database.sqlite3");
+        fs::create_dir_all(root.join("localization")).unwrap();
+        fs::write(
+            root.join("localization/a_l_english.yml"),
+            "\u{feff}l_english:\r\n a:0 \"A\"\r\n b:0 \"B\"\r\n c:0 \"C\"\r\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("localization/a_l_german.yml"),
+            "\u{feff}l_german:\r\n a:0 \"A de\"\r\n b:0 \"B de\"\r\n c:0 \"C de\"\r\n",
+        )
+        .unwrap();
+
+        let mut database = Database::new(
+            std::slice::from_ref(&root),
+            &WalkOptions::default(),
+            &database_file_path,
+        )
+        .unwrap();
+
+        let keys = vec![
+            // The source changed since the export.
+            imported("a", "A neu", Some("Old A")),
+            // An empty cell removes the key.
+            imported("b", "", Some("B")),
+            imported("c", "C de", Some("C")),
+            ImportedKey {
+                file: Some("localization/x_l_english.yml".to_owned()),
+                ..imported("d", "D de", Some("D"))
+            },
+            // An empty cell of a key which doesn't exist does nothing.
+            imported("e", "", None),
+        ];
+
+        let preview =
+            ImportPreview::new(&mut database, Language::German, Language::English, keys).unwrap();
+        let changes: Vec<_> = preview
+            .changes()
+            .iter()
+            .map(|change| {
+                (
+                    change.kind,
+                    change.key.as_str(),
+                    change.new_value.as_deref(),
+                    change.conflict.is_some(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            changes,
+            [
+                (ImportChangeKind::Changed, "a", Some("A neu"), true),
+                (ImportChangeKind::Removed, "b", None, false),
+                (ImportChangeKind::New, "d", Some("D de"), false),
+            ]
+        );
+        assert_eq!(preview.unchanged_count(), 1);
+        assert_eq!(preview.conflict_count(), 1);
+        assert_eq!(preview.changes()[2].file_name, "x");
+
+        // The conflicting change is skipped.
+        assert_eq!(preview.apply(&mut database, false).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(root.join("localization/a_l_german.yml")).unwrap(),
+            "\u{feff}l_german:\r\n a:0 \"A de\"\r\n c:0 \"C de\"\r\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("localization/x_l_german.yml")).unwrap(),
+            "\u{feff}l_german:\n d:0 \"D de\"\n"
+        );
+    }
+}
@@ -1,3 +1,15 @@
-mod core;
-mod database;
+pub mod core;
+pub mod database;
+pub mod decisions;
+pub mod defines;
+pub mod definitions;
+pub mod events;
 pub mod explorer;
+pub mod export;
+pub mod glob;
+pub mod history;
+pub mod import;
+pub mod lint;
+pub mod script;
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,1022 @@
+use crate::core::*;
+use crate::database::dir::{DirTree, File};
+use crate::database::parser::Parser;
+use crate::database::vfs;
+use crate::database::{ContentType, Language};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    InvalidEncoding,
+    MissingBom,
+    BadHeader,
+    WrongFileNameSuffix,
+    UnknownLanguage,
+    MalformedEntry,
+    UnbalancedQuotes,
+    DuplicateKey,
+    ShadowedKey,
+    DanglingReference,
+    UntranslatedKey,
+}
+
+impl Rule {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::InvalidEncoding => "invalid-encoding",
+            Self::MissingBom => "missing-bom",
+            Self::BadHeader => "bad-header",
+            Self::WrongFileNameSuffix => "wrong-file-name-suffix",
+            Self::UnknownLanguage => "unknown-language",
+            Self::MalformedEntry => "malformed-entry",
+            Self::UnbalancedQuotes => "unbalanced-quotes",
+            Self::DuplicateKey => "duplicate-key",
+            Self::ShadowedKey => "shadowed-key",
+            Self::DanglingReference => "dangling-reference",
+            Self::UntranslatedKey => "untranslated-key",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::InvalidEncoding => "Localization files must be encoded in UTF-8.",
+            Self::MissingBom => "Localization files must start with a UTF-8 byte order mark.",
+            Self::BadHeader => "The first line must be the language specifier, e.g. `l_english:`.",
+            Self::WrongFileNameSuffix => {
+                "The file name must end with _l_<language>.yml matching the header."
+            }
+            Self::UnknownLanguage => "The language specifier isn't recognized.",
+            Self::MalformedEntry => "Entries must look like `key:0 \"value\"`.",
+            Self::UnbalancedQuotes => "The value isn't enclosed in a balanced pair of quotes.",
+            Self::DuplicateKey => "The key is defined more than once in the same file.",
+            Self::ShadowedKey => {
+                "The key is overridden by a definition in another file outside a replace folder."
+            }
+            Self::DanglingReference => "The value references a key which doesn't exist.",
+            Self::UntranslatedKey => "The key is missing for a language which has other keys.",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::InvalidEncoding
+            | Self::MissingBom
+            | Self::BadHeader
+            | Self::WrongFileNameSuffix
+            | Self::UnknownLanguage
+            | Self::MalformedEntry
+            | Self::UnbalancedQuotes
+            | Self::DuplicateKey => Severity::Error,
+            Self::ShadowedKey | Self::DanglingReference | Self::UntranslatedKey => {
+                Severity::Warning
+            }
+        }
+    }
+
+    pub fn values() -> &'static [Self] {
+        &[
+            Self::InvalidEncoding,
+            Self::MissingBom,
+            Self::BadHeader,
+            Self::WrongFileNameSuffix,
+            Self::UnknownLanguage,
+            Self::MalformedEntry,
+            Self::UnbalancedQuotes,
+            Self::DuplicateKey,
+            Self::ShadowedKey,
+            Self::DanglingReference,
+            Self::UntranslatedKey,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    // Relative to the root of the dir tree.
+    pub file: PathBuf,
+    // 1-based; 0 means that the diagnostic applies to the whole file.
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct Report {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    // Whether the check failed, i.e. whether the command line tool exits with a failure.
+    pub fn is_failure(&self, warnings_as_errors: bool) -> bool {
+        self.has_errors() || (warnings_as_errors && self.warning_count() > 0)
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Text => Ok(self.to_text()),
+            ReportFormat::Json => self.to_json(),
+            ReportFormat::Sarif => self.to_sarif(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for diagnostic in &self.diagnostics {
+            // The diagnostics about the whole file have no line.
+            let location = match diagnostic.line {
+                0 => format!("{}", diagnostic.file.display()),
+                line => format!("{}:{}", diagnostic.file.display(), line),
+            };
+
+            text.push_str(&format!(
+                "{}: {}[{}]: {}\n",
+                location,
+                diagnostic.severity.name(),
+                diagnostic.rule.id(),
+                diagnostic.message
+            ));
+        }
+
+        text.push_str(&format!(
+            "{} error(s), {} warning(s) found.\n",
+            self.error_count(),
+            self.warning_count()
+        ));
+        text
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let json = serde_json::json!({
+            "errors": self.error_count(),
+            "warnings": self.warning_count(),
+            "diagnostics": self.diagnostics,
+        });
+
+        serde_json::to_string_pretty(&json).map_err(|error| error!("{}", error))
+    }
+
+    // SARIF 2.1.0 is understood by code hosting platforms, which use it to annotate the files in
+    // pull requests.
+    pub fn to_sarif(&self) -> Result<String> {
+        let rules: Vec<_> = Rule::values()
+            .iter()
+            .map(|rule| {
+                serde_json::json!({
+                    "id": rule.id(),
+                    "shortDescription": { "text": rule.description() },
+                    "defaultConfiguration": { "level": rule.severity().name() },
+                })
+            })
+            .collect();
+
+        let results: Vec<_> = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let mut physical_location = serde_json::json!({
+                    "artifactLocation": { "uri": Self::uri(&diagnostic.file) },
+                });
+
+                if diagnostic.line > 0 {
+                    physical_location["region"] =
+                        serde_json::json!({ "startLine": diagnostic.line });
+                }
+
+                serde_json::json!({
+                    "ruleId": diagnostic.rule.id(),
+                    "level": diagnostic.severity.name(),
+                    "message": { "text": diagnostic.message },
+                    "locations": [{ "physicalLocation": physical_location }],
+                })
+            })
+            .collect();
+
+        let sarif = serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }],
+        });
+
+        serde_json::to_string_pretty(&sarif).map_err(|error| error!("{}", error))
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    fn uri(path: &Path) -> String {
+        path.components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+// Runs all the localization checks on the localization files in the dir tree.
+pub fn check(dir_tree: &DirTree) -> Result<Report> {
+    Linter::default().check(dir_tree)
+}
+
+struct Entry {
+    key: String,
+    value: String,
+    file: PathBuf,
+    line: usize,
+    in_replace_folder: bool,
+}
+
+#[derive(Default)]
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+    entries: HashMap<Language, Vec<Entry>>,
+}
+
+impl Linter {
+    const REPLACE_FOLDER_NAME: &'static str = "replace";
+
+    fn check(mut self, dir_tree: &DirTree) -> Result<Report> {
+        let mut files: Vec<&File> = dir_tree
+            .files()
+            .into_iter()
+            .filter(|file| {
+                file.content_type() == ContentType::Localization
+                    && file.file_name().extension().is_some_and(|ext| ext == "yml")
+            })
+            .collect();
+
+        // Localization files are processed in reverse alphabetical order (from Z to A), so that
+        // the definition from the file which comes first alphabetically is the one that's used.
+        files.sort_by(|a, b| b.file_name().cmp(a.file_name()));
+
+        for file in files {
+            self.check_file(file)?;
+        }
+
+        self.check_shadowed_keys();
+        self.check_dangling_references();
+        self.check_untranslated_keys();
+
+        let mut diagnostics = self.diagnostics;
+        diagnostics.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+
+        Ok(Report { diagnostics })
+    }
+
+    fn report(&mut self, rule: Rule, file: &Path, line: usize, message: String) {
+        self.diagnostics.push(Diagnostic {
+            rule,
+            severity: rule.severity(),
+            file: file.to_owned(),
+            line,
+            message,
+        });
+    }
+
+    fn check_file(&mut self, file: &File) -> Result<()> {
+        let path = file.relative_path();
+        let bytes = vfs::read(file.full_path())?;
+
+        // The game ignores empty files, so there's nothing to check.
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        let Ok(content) = String::from_utf8(bytes) else {
+            self.report(
+                Rule::InvalidEncoding,
+                path,
+                0,
+                "file is not valid UTF-8".to_owned(),
+            );
+            return Ok(());
+        };
+
+        let stripped = Parser::strip_byte_order_mark(&content);
+        let has_bom = stripped.is_some();
+        let content = match stripped {
+            Some(content) => content,
+            None => {
+                self.report(
+                    Rule::MissingBom,
+                    path,
+                    1,
+                    "file doesn't start with a UTF-8 byte order mark and will be ignored by the game"
+                        .to_owned(),
+                );
+                &content
+            }
+        };
+
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(line_number, line)| (line_number + 1, Parser::strip_comment(line).trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let Some((header_line_number, header)) = lines.next() else {
+            return Ok(());
+        };
+
+        let header_language = match header.strip_suffix(':') {
+            Some(specifier) => match Language::from_language_specifier(specifier) {
+                Some(language) => Some(language),
+                None => {
+                    self.report(
+                        Rule::UnknownLanguage,
+                        path,
+                        header_line_number,
+                        format!("unknown language specifier `{}`", specifier),
+                    );
+                    None
+                }
+            },
+            None => {
+                self.report(
+                    Rule::BadHeader,
+                    path,
+                    header_line_number,
+                    format!("expected a language specifier, found `{}`", header),
+                );
+                None
+            }
+        };
+
+        let has_valid_file_name = self.check_file_name(file, header_language);
+
+        let Some(language) = header_language else {
+            return Ok(());
+        };
+
+        // The keys from files which the game ignores are still checked, but they don't take part
+        // in the checks across files.
+        let ignored_by_game = !has_bom || !has_valid_file_name;
+
+        let in_replace_folder = path
+            .components()
+            .any(|component| component.as_os_str() == Self::REPLACE_FOLDER_NAME);
+        let mut keys_in_file: HashMap<String, usize> = HashMap::new();
+
+        for (line_number, line) in lines {
+            let Some((key, value)) = self.check_entry(path, line_number, line) else {
+                continue;
+            };
+
+            if let Some(first_line_number) = keys_in_file.get(&key) {
+                self.report(
+                    Rule::DuplicateKey,
+                    path,
+                    line_number,
+                    format!(
+                        "key `{}` is already defined on line {}",
+                        key, first_line_number
+                    ),
+                );
+                continue;
+            }
+
+            keys_in_file.insert(key.clone(), line_number);
+
+            if ignored_by_game {
+                continue;
+            }
+
+            self.entries.entry(language).or_default().push(Entry {
+                key,
+                value,
+                file: path.to_owned(),
+                line: line_number,
+                in_replace_folder,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Returns false if the game ignores the file because of its name.
+    fn check_file_name(&mut self, file: &File, header_language: Option<Language>) -> bool {
+        let path = file.relative_path();
+        let file_name = file.file_name().to_string_lossy();
+        let stem = file_name.strip_suffix(".yml").unwrap_or(&file_name);
+
+        let Some((_, suffix)) = stem.rsplit_once("_l_") else {
+            self.report(
+                Rule::WrongFileNameSuffix,
+                path,
+                0,
+                "file name doesn't end with _l_<language>.yml and will be ignored by the game"
+                    .to_owned(),
+            );
+            return false;
+        };
+
+        let specifier = format!("l_{}", suffix);
+
+        match Language::from_language_specifier(&specifier) {
+            None => {
+                self.report(
+                    Rule::UnknownLanguage,
+                    path,
+                    0,
+                    format!("unknown language `{}` in file name", specifier),
+                );
+                false
+            }
+            Some(language) => {
+                if let Some(header_language) = header_language
+                    && header_language != language
+                {
+                    self.report(
+                        Rule::WrongFileNameSuffix,
+                        path,
+                        0,
+                        format!(
+                            "file name suffix `{}` doesn't match the header `{}`",
+                            specifier,
+                            header_language.specifier()
+                        ),
+                    );
+                    return false;
+                }
+
+                true
+            }
+        }
+    }
+
+    fn check_entry(
+        &mut self,
+        path: &Path,
+        line_number: usize,
+        line: &str,
+    ) -> Option<(String, String)> {
+        let Some((key, after_colon)) = line.split_once(':') else {
+            self.report(
+                Rule::MalformedEntry,
+                path,
+                line_number,
+                format!("expected `key:0 \"value\"`, found `{}`", line),
+            );
+            return None;
+        };
+
+        let key = key.trim();
+
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            self.report(
+                Rule::MalformedEntry,
+                path,
+                line_number,
+                format!("`{}` is not a valid key", key),
+            );
+            return None;
+        }
+
+        // Skip the revision number.
+        let value =
+            after_colon.trim_start_matches(|c: char| c.is_ascii_digit() || c.is_whitespace());
+
+        let balanced = value.len() >= 2
+            && value.starts_with('"')
+            && value.ends_with('"')
+            && unescaped_quote_count(value).is_multiple_of(2);
+
+        if !balanced {
+            self.report(
+                Rule::UnbalancedQuotes,
+                path,
+                line_number,
+                format!("value of key `{}` has unbalanced quotes: `{}`", key, value),
+            );
+            return None;
+        }
+
+        Some((key.to_owned(), value[1..value.len() - 1].to_owned()))
+    }
+
+    fn check_shadowed_keys(&mut self) {
+        let mut diagnostics = Vec::new();
+
+        for entries in self.entries.values() {
+            // Entries are in processing order, so a later definition overrides an earlier one.
+            let mut latest: HashMap<&str, &Entry> = HashMap::new();
+
+            for entry in entries {
+                if let Some(previous) = latest.get(entry.key.as_str()) {
+                    // Keys in a replace folder are meant to override the other definitions.
+                    if !previous.in_replace_folder && !entry.in_replace_folder {
+                        diagnostics.push((
+                            previous.file.clone(),
+                            previous.line,
+                            format!(
+                                "key `{}` is shadowed by the definition in `{}` on line {}",
+                                entry.key,
+                                entry.file.display(),
+                                entry.line
+                            ),
+                        ));
+                    }
+
+                    // Keys from a replace folder always win.
+                    if previous.in_replace_folder && !entry.in_replace_folder {
+                        continue;
+                    }
+                }
+
+                latest.insert(&entry.key, entry);
+            }
+        }
+
+        for (file, line, message) in diagnostics {
+            self.report(Rule::ShadowedKey, &file, line, message);
+        }
+    }
+
+    fn check_dangling_references(&mut self) {
+        let mut diagnostics = Vec::new();
+
+        for entries in self.entries.values() {
+            let keys: HashSet<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+
+            for entry in entries {
+                for reference in Self::references(&entry.value) {
+                    if !keys.contains(reference) {
+                        diagnostics.push((
+                            entry.file.clone(),
+                            entry.line,
+                            format!(
+                                "key `{}` references `${}$`, which doesn't exist",
+                                entry.key, reference
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (file, line, message) in diagnostics {
+            self.report(Rule::DanglingReference, &file, line, message);
+        }
+    }
+
+    fn check_untranslated_keys(&mut self) {
+        // English is the source language if it's present, otherwise there's nothing to compare
+        // against.
+        let Some(source_entries) = self.entries.get(&Language::English) else {
+            return;
+        };
+
+        let mut diagnostics = Vec::new();
+
+        for (language, entries) in &self.entries {
+            if *language == Language::English {
+                continue;
+            }
+
+            let keys: HashSet<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+            let mut reported = HashSet::new();
+
+            for entry in source_entries {
+                if !keys.contains(entry.key.as_str()) && reported.insert(entry.key.as_str()) {
+                    diagnostics.push((
+                        entry.file.clone(),
+                        entry.line,
+                        format!("key `{}` is not translated to {}", entry.key, language),
+                    ));
+                }
+            }
+        }
+
+        for (file, line, message) in diagnostics {
+            self.report(Rule::UntranslatedKey, &file, line, message);
+        }
+    }
+
+    // Returns the keys referenced with $key$ in a value. References written in uppercase are
+    // parameters filled in by the game (e.g. $YEAR$), so they're skipped.
+    fn references(value: &str) -> Vec<&str> {
+        let mut references = Vec::new();
+        let mut parts = value.split('$');
+
+        // Every odd part lies between a pair of dollar signs.
+        parts.next();
+
+        while let (Some(reference), Some(_)) = (parts.next(), parts.next()) {
+            // Formatting flags come after a pipe, e.g. $key|Y$.
+            let reference = reference.split('|').next().unwrap_or_default();
+
+            let is_identifier = !reference.is_empty()
+                && reference
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            let is_parameter = reference.chars().all(|c| !c.is_ascii_lowercase());
+
+            if is_identifier && !is_parameter {
+                references.push(reference);
+            }
+        }
+
+        references
+    }
+}
+
+// The number of quotes in the value which aren't escaped with a backslash, e.g. 2 in
+// `"say \"hi\""`.
+fn unescaped_quote_count(value: &str) -> usize {
+    let mut count = 0;
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => count += 1,
+            _ => {}
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::dir::WalkOptions;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    // Checks the files, given by their paths relative to the root, in a temporary dir tree.
+    fn check_files(name: &str, files: &[(&str, &str)]) -> Report {
+        let temp_dir = TempDir::new(&format!("lint-{}", name));
+        let root = temp_dir.path();
+
+        for (relative_path, content) in files {
+            let path = root.join(relative_path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let dir_tree = DirTree::new(root, &WalkOptions::default()).unwrap();
+        check(&dir_tree).unwrap()
+    }
+
+    fn found(report: &Report) -> Vec<(Rule, String, usize)> {
+        report
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.rule,
+                    Report::uri(&diagnostic.file),
+                    diagnostic.line,
+                )
+            })
+            .collect()
+    }
+
+    fn lint(name: &str, files: &[(&str, &str)]) -> Vec<(Rule, String, usize)> {
+        found(&check_files(name, files))
+    }
+
+    fn entry(rule: Rule, file: &str, line: usize) -> (Rule, String, usize) {
+        (rule, file.to_owned(), line)
+    }
+
+    #[test]
+    fn valid_files_pass() {
+        let files = [
+            (
+                "localization/a_l_english.yml",
+                "\u{feff}l_english:\n # A comment\n a:0 \"#bold A#! $b$\" # B\n b: \"B\"\n",
+            ),
+            (
+                "localization/a_l_german.yml",
+                "\u{feff}l_german:\n a:0 \"A\"\n b:0 \"B $YEAR$\"\n",
+            ),
+        ];
+
+        assert_eq!(lint("valid", &files), []);
+    }
+
+    #[test]
+    fn missing_bom_is_reported() {
+        let files = [("localization/a_l_english.yml", "l_english:\n a:0 \"A\"\n")];
+
+        assert_eq!(
+            lint("bom", &files),
+            [entry(Rule::MissingBom, "localization/a_l_english.yml", 1)]
+        );
+    }
+
+    #[test]
+    fn bad_header_is_reported() {
+        let files = [(
+            "localization/a_l_english.yml",
+            "\u{feff}\n\nl_english\n a:0 \"A\"\n",
+        )];
+
+        assert_eq!(
+            lint("header", &files),
+            [entry(Rule::BadHeader, "localization/a_l_english.yml", 3)]
+        );
+    }
+
+    #[test]
+    fn wrong_file_name_suffix_is_reported() {
+        let files = [
+            ("localization/a.yml", "\u{feff}l_english:\n a:0 \"A\"\n"),
+            (
+                "localization/b_l_german.yml",
+                "\u{feff}l_english:\n b:0 \"B\"\n",
+            ),
+        ];
+
+        assert_eq!(
+            lint("suffix", &files),
+            [
+                entry(Rule::WrongFileNameSuffix, "localization/a.yml", 0),
+                entry(Rule::WrongFileNameSuffix, "localization/b_l_german.yml", 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_language_is_reported() {
+        let files = [(
+            "localization/a_l_klingon.yml",
+            "\u{feff}l_klingon:\n a:0 \"A\"\n",
+        )];
+
+        assert_eq!(
+            lint("language", &files),
+            [
+                entry(Rule::UnknownLanguage, "localization/a_l_klingon.yml", 0),
+                entry(Rule::UnknownLanguage, "localization/a_l_klingon.yml", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_entries_and_unbalanced_quotes_are_reported() {
+        let files = [(
+            "localization/a_l_english.yml",
+            "\u{feff}l_english:\n a \"A\"\n b:0 \"B\n c:0 \"C\" \"\n d:0 \"D\"\n",
+        )];
+
+        assert_eq!(
+            lint("quotes", &files),
+            [
+                entry(Rule::MalformedEntry, "localization/a_l_english.yml", 2),
+                entry(Rule::UnbalancedQuotes, "localization/a_l_english.yml", 3),
+                entry(Rule::UnbalancedQuotes, "localization/a_l_english.yml", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_quotes_are_not_counted() {
+        let files = [(
+            "localization/a_l_english.yml",
+            "\u{feff}l_english:\n a:0 \"say \\\"hi\\\"\"\n b:0 \"B\\\"\n",
+        )];
+
+        assert_eq!(
+            lint("escaped", &files),
+            [entry(
+                Rule::UnbalancedQuotes,
+                "localization/a_l_english.yml",
+                3
+            )]
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_are_reported() {
+        let files = [(
+            "localization/a_l_english.yml",
+            "\u{feff}l_english:\n a:0 \"A\"\n b:0 \"B\"\n a:1 \"Other\"\n",
+        )];
+
+        assert_eq!(
+            lint("duplicate", &files),
+            [entry(Rule::DuplicateKey, "localization/a_l_english.yml", 4)]
+        );
+    }
+
+    #[test]
+    fn shadowed_keys_are_reported_outside_replace_folders() {
+        let files = [
+            (
+                "localization/a_l_english.yml",
+                "\u{feff}l_english:\n a:0 \"A\"\n b:0 \"B\"\n",
+            ),
+            (
+                "localization/b_l_english.yml",
+                "\u{feff}l_english:\n a:0 \"Other\"\n",
+            ),
+            (
+                "localization/replace/c_l_english.yml",
+                "\u{feff}l_english:\n b:0 \"Replaced\"\n",
+            ),
+        ];
+
+        // The files are processed from Z to A, so the key in `a` wins over the one in `b`.
+        assert_eq!(
+            lint("shadowed", &files),
+            [entry(Rule::ShadowedKey, "localization/b_l_english.yml", 2)]
+        );
+    }
+
+    #[test]
+    fn dangling_references_are_reported() {
+        let files = [(
+            "localization/a_l_english.yml",
+            "\u{feff}l_english:\n a:0 \"$b$ $missing|Y$ $COUNT$\"\n b:0 \"B\"\n",
+        )];
+
+        let report = check_files("dangling", &files);
+
+        assert_eq!(
+            found(&report),
+            [entry(
+                Rule::DanglingReference,
+                "localization/a_l_english.yml",
+                2
+            )]
+        );
+        assert!(report.diagnostics()[0].message.contains("$missing$"));
+    }
+
+    #[test]
+    fn untranslated_keys_are_reported() {
+        let files = [
+            (
+                "localization/a_l_english.yml",
+                "\u{feff}l_english:\n a:0 \"A\"\n b:0 \"B\"\n",
+            ),
+            (
+                "localization/a_l_french.yml",
+                "\u{feff}l_french:\n a:0 \"A\"\n",
+            ),
+        ];
+
+        let report = check_files("untranslated", &files);
+
+        assert_eq!(
+            found(&report),
+            [entry(
+                Rule::UntranslatedKey,
+                "localization/a_l_english.yml",
+                3
+            )]
+        );
+        assert!(report.diagnostics()[0].message.contains("French"));
+    }
+
+    #[test]
+    fn file_level_diagnostics_have_no_line_in_text() {
+        let files = [
+            ("localization/a.yml", "\u{feff}l_english:\n a:0 \"A\"\n"),
+            ("localization/b_l_english.yml", "l_english:\n b:0 \"B\"\n"),
+        ];
+
+        let text = check_files("text", &files).to_text();
+        let lines: Vec<_> = text.lines().map(|line| line.replace('\\', "/")).collect();
+
+        assert_eq!(
+            lines,
+            [
+                "localization/a.yml: error[wrong-file-name-suffix]: file name doesn't end with \
+                 _l_<language>.yml and will be ignored by the game",
+                "localization/b_l_english.yml:1: error[missing-bom]: file doesn't start with a \
+                 UTF-8 byte order mark and will be ignored by the game",
+                "2 error(s), 0 warning(s) found.",
+            ]
+        );
+    }
+
+    #[test]
+    fn sarif_lists_rules_and_results() {
+        let files = [
+            ("localization/a.yml", "\u{feff}l_english:\n a:0 \"A\"\n"),
+            (
+                "localization/b_l_english.yml",
+                "\u{feff}l_english:\n b:0 \"$c$\"\n",
+            ),
+        ];
+
+        let sarif = check_files("sarif", &files).to_sarif().unwrap();
+        let sarif: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            Rule::values().len()
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0]["ruleId"], "wrong-file-name-suffix");
+        assert_eq!(results[0]["level"], "error");
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "localization/a.yml");
+        // The file-level diagnostics have no region.
+        assert!(location.get("region").is_none());
+
+        assert_eq!(results[1]["ruleId"], "dangling-reference");
+        assert_eq!(results[1]["level"], "warning");
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(
+            location["artifactLocation"]["uri"],
+            "localization/b_l_english.yml"
+        );
+        assert_eq!(location["region"]["startLine"], 2);
+    }
+
+    #[test]
+    fn failure_depends_on_errors_and_warnings() {
+        let clean = check_files(
+            "exit-clean",
+            &[(
+                "localization/a_l_english.yml",
+                "\u{feff}l_english:\n a:0 \"A\"\n",
+            )],
+        );
+        let warning = check_files(
+            "exit-warning",
+            &[(
+                "localization/a_l_english.yml",
+                "\u{feff}l_english:\n a:0 \"$b$\"\n",
+            )],
+        );
+        let error = check_files(
+            "exit-error",
+            &[("localization/a_l_english.yml", "l_english:\n a:0 \"A\"\n")],
+        );
+
+        assert!(!clean.is_failure(false));
+        assert!(!clean.is_failure(true));
+        assert!(!warning.is_failure(false));
+        assert!(warning.is_failure(true));
+        assert!(error.is_failure(false));
+        assert!(error.is_failure(true));
+    }
+}
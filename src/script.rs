@@ -0,0 +1,821 @@
+use crate::core::*;
+use crate::database::dir::DirTree;
+use crate::database::{parallel, vfs};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+// A parser for the script files of the games, e.g.:
+//
+// namespace = flavor_fra
+// country_event = {
+//     id = flavor_fra.1
+//     trigger = { tag = FRA NOT = { stability = 1 } }
+//     option = { name = flavor_fra.1.a add_prestige = 10 }
+// }
+//
+// A file is a list of items: `key = value`, or a bare value as in `{ FRA ENG }`. Values are
+// scalars (words, numbers, dates and quoted strings) or blocks in braces, which may be tagged, as
+// in `color = rgb { 10 20 30 }`. Besides `=`, keys and values can be separated by the comparison
+// operators `<`, `<=`, `>`, `>=`, `!=`, `==` and `?=`.
+//
+// The games accept a lot of broken scripts, so the parser recovers from errors where it can and
+// returns them next to everything it could make sense of.
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Block {
+    // The tag in front of the opening brace, e.g. `rgb`.
+    pub tag: Option<String>,
+    pub items: Vec<Item>,
+    // The line of the opening brace (1-based); 0 for the top level of a file.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    // Bare values don't have a key.
+    pub key: Option<(Scalar, Operator)>,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(Scalar),
+    Block(Block),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scalar {
+    pub text: String,
+    pub quoted: bool,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equals,
+    DoubleEquals,
+    NotEquals,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    // Assigns only if the target exists.
+    QuestionEquals,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Operator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Equals => "=",
+            Self::DoubleEquals => "==",
+            Self::NotEquals => "!=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+            Self::QuestionEquals => "?=",
+        }
+    }
+}
+
+impl Block {
+    // The values of the items with the key, which is matched ignoring case like the games do.
+    pub fn get_all<'a, 'k>(
+        &'a self,
+        key: &'k str,
+    ) -> impl Iterator<Item = &'a Value> + use<'a, 'k> {
+        self.fields()
+            .filter(move |(item_key, _, _)| item_key.text.eq_ignore_ascii_case(key))
+            .map(|(_, _, value)| value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.get_all(key).next()
+    }
+
+    pub fn get_scalar(&self, key: &str) -> Option<&str> {
+        self.get_all(key).find_map(Value::as_scalar)
+    }
+
+    pub fn get_block(&self, key: &str) -> Option<&Block> {
+        self.get_all(key).find_map(Value::as_block)
+    }
+
+    // Whether the key is set to `yes`.
+    pub fn is_yes(&self, key: &str) -> bool {
+        self.get_scalar(key)
+            .is_some_and(|value| value.eq_ignore_ascii_case("yes"))
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&Scalar, Operator, &Value)> {
+        self.items.iter().filter_map(|item| {
+            let (key, operator) = item.key.as_ref()?;
+            Some((key, *operator, &item.value))
+        })
+    }
+
+    // The values without keys, e.g. the tags in `{ FRA ENG }`.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.items
+            .iter()
+            .filter(|item| item.key.is_none())
+            .map(|item| &item.value)
+    }
+
+    // Formats the items of the block as a script with one item per line, without the braces of
+    // the block itself.
+    pub fn to_script(&self) -> String {
+        let mut script = String::new();
+        self.write_items(&mut script, 0);
+        script
+    }
+
+    fn write_items(&self, script: &mut String, depth: usize) {
+        for item in &self.items {
+            script.push_str(&"    ".repeat(depth));
+
+            if let Some((key, operator)) = &item.key {
+                key.write(script);
+                script.push(' ');
+                script.push_str(operator.as_str());
+                script.push(' ');
+            }
+
+            item.value.write(script, depth);
+            script.push('\n');
+        }
+    }
+}
+
+impl Value {
+    pub fn as_scalar(&self) -> Option<&str> {
+        match self {
+            Self::Scalar(scalar) => Some(&scalar.text),
+            Self::Block(_) => None,
+        }
+    }
+
+    pub fn as_block(&self) -> Option<&Block> {
+        match self {
+            Self::Scalar(_) => None,
+            Self::Block(block) => Some(block),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Scalar(scalar) => scalar.line,
+            Self::Block(block) => block.line,
+        }
+    }
+
+    // Formats the value as a script, e.g. `"quoted"` or `{ FRA ENG }`.
+    pub fn to_script(&self) -> String {
+        let mut script = String::new();
+        self.write(&mut script, 0);
+        script
+    }
+
+    fn write(&self, script: &mut String, depth: usize) {
+        match self {
+            Self::Scalar(scalar) => scalar.write(script),
+            Self::Block(block) => {
+                if let Some(tag) = &block.tag {
+                    script.push_str(tag);
+                    script.push(' ');
+                }
+
+                // Short lists of scalars are kept on one line, e.g. `{ 10 20 30 }`.
+                if block.items.len() <= 8
+                    && block
+                        .items
+                        .iter()
+                        .all(|item| item.key.is_none() && matches!(item.value, Value::Scalar(_)))
+                {
+                    script.push('{');
+
+                    for item in &block.items {
+                        script.push(' ');
+                        item.value.write(script, depth);
+                    }
+
+                    script.push_str(" }");
+                    return;
+                }
+
+                script.push_str("{\n");
+                block.write_items(script, depth + 1);
+                script.push_str(&"    ".repeat(depth));
+                script.push('}');
+            }
+        }
+    }
+}
+
+impl Scalar {
+    fn write(&self, script: &mut String) {
+        if self.quoted {
+            script.push('"');
+            script.push_str(&self.text.replace('\\', "\\\\").replace('"', "\\\""));
+            script.push('"');
+        } else {
+            script.push_str(&self.text);
+        }
+    }
+}
+
+// Decodes the content of a script file. The games write their files in UTF-8 (with or without a
+// byte order mark) or, the older ones, in Windows-1252.
+pub fn decode(bytes: &[u8]) -> Cow<'_, str> {
+    let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Cow::Borrowed(text);
+    }
+
+    // The characters of 0x80..=0x9f, which differ from Latin-1. The unassigned ones are mapped to
+    // the control characters of the same value like Windows does.
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+
+    Cow::Owned(
+        bytes
+            .iter()
+            .map(|&byte| match byte {
+                0x80..=0x9f => HIGH[(byte - 0x80) as usize],
+                _ => byte as char,
+            })
+            .collect(),
+    )
+}
+
+pub fn parse(text: &str) -> (Block, Vec<SyntaxError>) {
+    let mut parser = Parser {
+        tokens: Tokens {
+            tokenizer: Tokenizer {
+                text,
+                position: 0,
+                line: 1,
+                errors: Vec::new(),
+            },
+            peeked: None,
+        },
+        errors: Vec::new(),
+    };
+
+    let items = parser.parse_items(None);
+    let mut errors = parser.errors;
+    errors.extend(parser.tokens.tokenizer.errors);
+    errors.sort_by_key(|error| error.line);
+
+    (
+        Block {
+            tag: None,
+            items,
+            line: 0,
+        },
+        errors,
+    )
+}
+
+// A parsed script file.
+#[derive(Debug, Clone)]
+pub struct ScriptFile {
+    pub relative_path: PathBuf,
+    pub full_path: PathBuf,
+    pub layer: u32,
+    // Whether a file with the same relative path in a later layer replaces this one.
+    pub is_replaced: bool,
+    pub block: Block,
+}
+
+// Where something is defined in the script files.
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub relative_path: PathBuf,
+    pub full_path: PathBuf,
+    pub line: usize,
+    pub layer: u32,
+}
+
+impl ScriptFile {
+    pub fn location(&self, line: usize) -> Location {
+        Location {
+            relative_path: self.relative_path.clone(),
+            full_path: self.full_path.clone(),
+            line,
+            layer: self.layer,
+        }
+    }
+}
+
+// Parses the `.txt` files in the folder (e.g. `events` or `common/on_actions`) and its subfolders
+// which the game loads. A file replaces the file with the same relative path in the layers below,
+// like the games do. The files are sorted by their relative path, which is the order they're read
+// in, and returned along with the warnings for the files which couldn't be read or parsed.
+pub fn load_folder(dir_trees: &[DirTree], folder: &str) -> Result<(Vec<ScriptFile>, Vec<String>)> {
+    load(dir_trees, folder, false)
+}
+
+// Like `load_folder`, but also parses the files which are replaced by later layers, which come
+// right before the file replacing them.
+pub fn load_folder_with_replaced(
+    dir_trees: &[DirTree],
+    folder: &str,
+) -> Result<(Vec<ScriptFile>, Vec<String>)> {
+    load(dir_trees, folder, true)
+}
+
+fn load(
+    dir_trees: &[DirTree],
+    folder: &str,
+    include_replaced: bool,
+) -> Result<(Vec<ScriptFile>, Vec<String>)> {
+    let paths = folder_files(dir_trees, folder, "txt", include_replaced);
+    let mut files = Vec::with_capacity(paths.len());
+    let mut warnings = Vec::new();
+
+    parallel::map_ordered(
+        &paths,
+        |(relative_path, full_path, layer, is_replaced)| {
+            let parsed = vfs::read(full_path).map(|bytes| parse(&decode(&bytes)));
+            (
+                relative_path.clone(),
+                full_path.clone(),
+                *layer,
+                *is_replaced,
+                parsed,
+            )
+        },
+        |(relative_path, full_path, layer, is_replaced, parsed)| {
+            let (block, errors) = match parsed {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    warnings.push(format!(
+                        "failed to read `{}`: {}",
+                        full_path.display(),
+                        error
+                    ));
+                    return Ok(());
+                }
+            };
+
+            // The games don't read the replaced files, so their errors don't matter.
+            if !is_replaced {
+                for error in errors {
+                    warnings.push(format!(
+                        "{}:{}: {}",
+                        relative_path.display(),
+                        error.line,
+                        error.message
+                    ));
+                }
+            }
+
+            files.push(ScriptFile {
+                relative_path,
+                full_path,
+                layer,
+                is_replaced,
+                block,
+            });
+
+            Ok(())
+        },
+    )?;
+
+    Ok((files, warnings))
+}
+
+// The files with the extension in the folder and its subfolders, sorted by their relative path,
+// as `(relative_path, full_path, layer, is_replaced)`. Only the versions of the files in the
+// topmost layer are included, unless `include_replaced` is set, in which case the replaced
+// versions come right before the version replacing them.
+pub(crate) fn folder_files(
+    dir_trees: &[DirTree],
+    folder: &str,
+    extension: &str,
+    include_replaced: bool,
+) -> Vec<(PathBuf, PathBuf, u32, bool)> {
+    // The versions of each file by layer, in the order of the layers.
+    let mut paths: BTreeMap<PathBuf, Vec<(PathBuf, u32)>> = BTreeMap::new();
+
+    for dir_tree in dir_trees {
+        for file in dir_tree.files() {
+            let relative_path = file.relative_path();
+
+            if relative_path.starts_with(folder)
+                && relative_path
+                    .extension()
+                    .is_some_and(|file_extension| file_extension.eq_ignore_ascii_case(extension))
+            {
+                paths
+                    .entry(relative_path.to_owned())
+                    .or_default()
+                    .push((file.full_path().to_owned(), dir_tree.layer()));
+            }
+        }
+    }
+
+    paths
+        .into_iter()
+        .flat_map(|(relative_path, versions)| {
+            let count = versions.len();
+            let skipped = if include_replaced { 0 } else { count - 1 };
+
+            versions
+                .into_iter()
+                .enumerate()
+                .skip(skipped)
+                .map(move |(idx, (full_path, layer))| {
+                    (relative_path.clone(), full_path, layer, idx + 1 < count)
+                })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Operator(Operator),
+    Word(&'a str),
+    Quoted(String),
+}
+
+struct Tokenizer<'a> {
+    text: &'a str,
+    position: usize,
+    line: usize,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn advance(&mut self, len: usize) -> &'a str {
+        let text = &self.text[self.position..self.position + len];
+        self.line += text.matches('\n').count();
+        self.position += len;
+        text
+    }
+
+    fn is_word_char(c: char) -> bool {
+        !c.is_whitespace() && !matches!(c, '{' | '}' | '=' | '<' | '>' | '!' | '?' | '#' | '"')
+    }
+
+    fn quoted(&mut self) -> String {
+        let line = self.line;
+        let mut text = String::new();
+        let mut escaped = false;
+
+        self.advance(1);
+
+        for (idx, c) in self.rest().char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => {
+                    self.advance(idx + 1);
+                    return text;
+                }
+                _ => {
+                    // Only quotes and backslashes are escaped; other escapes (e.g. `\n` in
+                    // localization) are kept as they are.
+                    if escaped && c != '"' && c != '\\' {
+                        text.push('\\');
+                    }
+
+                    text.push(c);
+                    escaped = false;
+                }
+            }
+        }
+
+        self.errors.push(SyntaxError {
+            line,
+            message: "unterminated string".to_owned(),
+        });
+        self.advance(self.rest().len());
+
+        text
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = (Token<'a>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Whitespace, comments and stray characters are skipped until there's a token.
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.advance(rest.len() - trimmed.len());
+
+            if self.rest().starts_with('#') {
+                let len = self.rest().find('\n').unwrap_or(self.rest().len());
+                self.advance(len);
+                continue;
+            }
+
+            let line = self.line;
+            let rest = self.rest();
+            let c = rest.chars().next()?;
+
+            let token = match c {
+                '{' => {
+                    self.advance(1);
+                    Token::Open
+                }
+                '}' => {
+                    self.advance(1);
+                    Token::Close
+                }
+                '"' => Token::Quoted(self.quoted()),
+                '=' | '<' | '>' | '!' | '?' => {
+                    let (operator, len) = match (c, rest[1..].starts_with('=')) {
+                        ('=', true) => (Operator::DoubleEquals, 2),
+                        ('=', false) => (Operator::Equals, 1),
+                        ('<', true) => (Operator::LessOrEqual, 2),
+                        ('<', false) => (Operator::Less, 1),
+                        ('>', true) => (Operator::GreaterOrEqual, 2),
+                        ('>', false) => (Operator::Greater, 1),
+                        ('!', true) => (Operator::NotEquals, 2),
+                        ('?', true) => (Operator::QuestionEquals, 2),
+                        _ => {
+                            self.errors.push(SyntaxError {
+                                line,
+                                message: format!("unexpected character `{}`", c),
+                            });
+                            self.advance(1);
+                            continue;
+                        }
+                    };
+
+                    self.advance(len);
+                    Token::Operator(operator)
+                }
+                _ => {
+                    // Inline math like `@[ stability * 2 ]` may contain spaces.
+                    let len = if rest.starts_with("@[") {
+                        rest.find(']').map_or(rest.len(), |idx| idx + 1)
+                    } else {
+                        rest.find(|c| !Self::is_word_char(c)).unwrap_or(rest.len())
+                    };
+
+                    Token::Word(self.advance(len))
+                }
+            };
+
+            return Some((token, line));
+        }
+    }
+}
+
+// The tokens with one token of lookahead. The tokenizer is kept reachable for its errors.
+struct Tokens<'a> {
+    tokenizer: Tokenizer<'a>,
+    peeked: Option<Option<(Token<'a>, usize)>>,
+}
+
+impl<'a> Tokens<'a> {
+    fn peek(&mut self) -> Option<&(Token<'a>, usize)> {
+        let tokenizer = &mut self.tokenizer;
+        self.peeked.get_or_insert_with(|| tokenizer.next()).as_ref()
+    }
+
+    fn next(&mut self) -> Option<(Token<'a>, usize)> {
+        match self.peeked.take() {
+            Some(token) => token,
+            None => self.tokenizer.next(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: Tokens<'a>,
+    errors: Vec<SyntaxError>,
+}
+
+impl Parser<'_> {
+    fn error(&mut self, line: usize, message: impl Into<String>) {
+        self.errors.push(SyntaxError {
+            line,
+            message: message.into(),
+        });
+    }
+
+    // Parses the items up to the closing brace of the block opened on the given line, or up to
+    // the end of the text at the top level.
+    fn parse_items(&mut self, open_line: Option<usize>) -> Vec<Item> {
+        let mut items = Vec::new();
+
+        while let Some((token, line)) = self.tokens.next() {
+            match token {
+                Token::Close => {
+                    if open_line.is_some() {
+                        return items;
+                    }
+
+                    self.error(line, "unexpected `}`");
+                }
+                Token::Open => {
+                    items.push(Item {
+                        key: None,
+                        value: Value::Block(self.parse_block(None, line)),
+                    });
+                }
+                Token::Operator(operator) => {
+                    self.error(
+                        line,
+                        format!("unexpected `{}` without a key", operator.as_str()),
+                    );
+                }
+                Token::Word(_) | Token::Quoted(_) => {
+                    let scalar = Self::scalar(token, line);
+                    items.push(self.parse_item(scalar));
+                }
+            }
+        }
+
+        if let Some(open_line) = open_line {
+            self.error(open_line, "missing `}` for the `{` on this line");
+        }
+
+        items
+    }
+
+    fn parse_item(&mut self, scalar: Scalar) -> Item {
+        match self.tokens.peek() {
+            Some((Token::Operator(operator), _)) => {
+                let operator = *operator;
+                let key_line = scalar.line;
+                self.tokens.next();
+
+                let value = match self.tokens.peek() {
+                    Some((Token::Open, line)) => {
+                        let line = *line;
+                        self.tokens.next();
+                        Value::Block(self.parse_block(None, line))
+                    }
+                    Some((Token::Word(_) | Token::Quoted(_), _)) => {
+                        let (token, line) = self.tokens.next().expect("peeked");
+                        self.parse_value(Self::scalar(token, line))
+                    }
+                    _ => {
+                        self.error(key_line, format!("missing value for `{}`", scalar.text));
+                        Value::Scalar(Scalar {
+                            text: String::new(),
+                            quoted: false,
+                            line: key_line,
+                        })
+                    }
+                };
+
+                Item {
+                    key: Some((scalar, operator)),
+                    value,
+                }
+            }
+            _ => Item {
+                key: None,
+                value: self.parse_value(scalar),
+            },
+        }
+    }
+
+    // A scalar followed by a block is the tag of the block, e.g. `rgb { 1 2 3 }`.
+    fn parse_value(&mut self, scalar: Scalar) -> Value {
+        if !scalar.quoted
+            && let Some((Token::Open, line)) = self.tokens.peek()
+        {
+            let line = *line;
+            self.tokens.next();
+            return Value::Block(self.parse_block(Some(scalar.text), line));
+        }
+
+        Value::Scalar(scalar)
+    }
+
+    fn parse_block(&mut self, tag: Option<String>, line: usize) -> Block {
+        Block {
+            tag,
+            items: self.parse_items(Some(line)),
+            line,
+        }
+    }
+
+    fn scalar(token: Token, line: usize) -> Scalar {
+        match token {
+            Token::Word(text) => Scalar {
+                text: text.to_owned(),
+                quoted: false,
+                line,
+            },
+            Token::Quoted(text) => Scalar {
+                text,
+                quoted: true,
+                line,
+            },
+            _ => unreachable!("only words and quoted strings are scalars"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_values_and_operators_are_parsed() {
+        let (block, errors) = parse(
+            "# a comment\n\
+             namespace = test\n\
+             country_event = {\n\
+             \tid = test.1\n\
+             \ttrigger = { tag = FRA stability >= 1 }\n\
+             \tcolor = rgb { 10 20 30 }\n\
+             \tname = \"Say \\\"hi\\\"\"\n\
+             }\n",
+        );
+
+        assert_eq!(errors, []);
+        assert_eq!(block.get_scalar("namespace"), Some("test"));
+
+        let event = block.get_block("country_event").unwrap();
+        assert_eq!(event.line, 3);
+        assert_eq!(event.get_scalar("ID"), Some("test.1"));
+        assert_eq!(event.get_scalar("name"), Some("Say \"hi\""));
+
+        let trigger = event.get_block("trigger").unwrap();
+        let (key, operator, value) = trigger.fields().nth(1).unwrap();
+        assert_eq!(
+            (key.text.as_str(), operator, value.as_scalar()),
+            ("stability", Operator::GreaterOrEqual, Some("1"))
+        );
+
+        let color = event.get_block("color").unwrap();
+        assert_eq!(color.tag.as_deref(), Some("rgb"));
+        assert_eq!(
+            color
+                .values()
+                .filter_map(Value::as_scalar)
+                .collect::<Vec<_>>(),
+            ["10", "20", "30"]
+        );
+    }
+
+    #[test]
+    fn broken_scripts_are_recovered_from() {
+        let (block, errors) = parse("a = { b = 1 }\n}\nc = 2\nd = { e = 3\n");
+
+        assert_eq!(block.get_scalar("c"), Some("2"));
+        assert_eq!(block.get_block("d").unwrap().get_scalar("e"), Some("3"));
+        assert_eq!(
+            errors.iter().map(|error| error.line).collect::<Vec<_>>(),
+            [2, 4]
+        );
+    }
+
+    #[test]
+    fn long_runs_of_stray_characters_are_skipped() {
+        let (block, errors) = parse(&format!("{}\na = 1\n", "!?".repeat(50_000)));
+
+        assert_eq!(block.get_scalar("a"), Some("1"));
+        assert_eq!(errors.len(), 100_000);
+    }
+
+    #[test]
+    fn scripts_are_formatted_one_item_per_line() {
+        let (block, _) = parse("a = { b = yes c = { 1 2 } } d = \"x y\"");
+
+        assert_eq!(
+            block.to_script(),
+            "a = {\n    b = yes\n    c = { 1 2 }\n}\nd = \"x y\"\n"
+        );
+    }
+
+    #[test]
+    fn windows_1252_is_decoded() {
+        assert_eq!(decode(b"\xef\xbb\xbfa = b"), "a = b");
+        assert_eq!(decode(b"name = \"Caf\xe9 \x80\""), "name = \"Café €\"");
+    }
+}